@@ -1,3 +1,12 @@
 fn main() {
-    tauri_build::build()
+    // Server pages are remote content and may only call plugin commands that
+    // a capability grants them; see `server_bridge_plugin` and
+    // capabilities/server-bridge.json.
+    tauri_build::try_build(tauri_build::Attributes::new().plugin(
+        "server-bridge",
+        tauri_build::InlinedPlugin::new()
+            .commands(&["notify", "report_title"])
+            .default_permission(tauri_build::DefaultPermissionRule::AllowAllCommands),
+    ))
+    .expect("failed to run tauri-build")
 }
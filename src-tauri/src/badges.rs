@@ -0,0 +1,508 @@
+// ── Unread badges ────────────────────────────────────────────────────────────
+//
+// Which servers have something unread, kept here so the sidebar, the tray
+// icon and the taskbar / dock agree. Two sources feed it, both from the
+// scripts injected into server pages:
+//   • the page title — BADGE_SCRIPT reports every change, and an "(N) …"
+//     style prefix becomes the server's unread count;
+//   • notifications — each one `notify` receives while the user isn't looking
+//     at that server is a mention, until the server is next viewed.
+// The UI says which server is on screen (`set_viewed_server`); window focus
+// comes from the main window's events.
+
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+use crate::{ServerId, SharedServerRegistry};
+use crate::notifications::bridge_caller;
+use crate::tray::{TRAY_ID, refresh_tray_menu, tray_base_icon, tray_tooltip};
+
+pub(crate) const BADGE_SCRIPT: &str = r#"
+(function () {
+  var _internals = window.__TAURI_INTERNALS__;
+  if (!_internals) return;
+
+  var _last = null;
+  function _report() {
+    var title = document.title;
+    if (title === _last) return;
+    _last = title;
+    _internals.invoke('plugin:server-bridge|report_title', { title: title }).catch(function () {});
+  }
+
+  /* Pages change the title through document.title or by editing <title>;
+     watching <head> catches both. */
+  function _watch() {
+    new MutationObserver(_report).observe(document.head || document.documentElement, {
+      subtree: true, childList: true, characterData: true
+    });
+    _report();
+  }
+  if (document.readyState === 'loading') document.addEventListener('DOMContentLoaded', _watch);
+  else _watch();
+})();
+"#;
+
+const BADGES_CHANGED_EVENT: &str = "badges://changed";
+
+/// Badge fill: the red the sidebar uses for mentions.
+const BADGE_COLOR: [u8; 3] = [0xED, 0x42, 0x45];
+
+/// 3×5 pixel glyphs for badge labels, one row per byte, leftmost pixel in
+/// the highest of the three bits.
+const BADGE_GLYPHS: [(char, [u8; 5]); 11] = [
+    ('0', [0b111, 0b101, 0b101, 0b101, 0b111]),
+    ('1', [0b010, 0b110, 0b010, 0b010, 0b111]),
+    ('2', [0b111, 0b001, 0b111, 0b100, 0b111]),
+    ('3', [0b111, 0b001, 0b111, 0b001, 0b111]),
+    ('4', [0b101, 0b101, 0b111, 0b001, 0b001]),
+    ('5', [0b111, 0b100, 0b111, 0b001, 0b111]),
+    ('6', [0b111, 0b100, 0b111, 0b101, 0b111]),
+    ('7', [0b111, 0b001, 0b001, 0b001, 0b001]),
+    ('8', [0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', [0b111, 0b101, 0b111, 0b001, 0b111]),
+    ('+', [0b000, 0b010, 0b111, 0b010, 0b000]),
+];
+
+/// What a page title says about unread activity. Chat apps put a count in
+/// front ("(3) Sharkord", "[12] …") or just mark it ("• Sharkord").
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TitleBadge {
+    Clear,
+    Marked,
+    Count(u32),
+}
+
+/// Read the unread marker off the front of a page title.
+pub fn parse_title_badge(title: &str) -> TitleBadge {
+    let title = title.trim_start();
+    for (open, close) in [('(', ')'), ('[', ']')] {
+        let Some(rest) = title.strip_prefix(open) else { continue };
+        let Some((inner, _)) = rest.split_once(close) else { continue };
+        let digits = inner.trim().trim_end_matches('+');
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            continue;
+        }
+        return match digits.parse::<u32>() {
+            Ok(0) => TitleBadge::Clear,
+            Ok(n) => TitleBadge::Count(n),
+            // More digits than fit is still a lot of unread.
+            Err(_) => TitleBadge::Count(u32::MAX),
+        };
+    }
+    if title.starts_with(['•', '●', '*']) {
+        return TitleBadge::Marked;
+    }
+    TitleBadge::Clear
+}
+
+/// Unread state of one server, as the sidebar shows it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerBadge {
+    /// The page's title says there is unread activity.
+    pub unread: bool,
+    /// How much, when the title gives a number.
+    pub unread_count: Option<u32>,
+    /// Notifications raised while the server wasn't in view.
+    pub mentions: u32,
+}
+
+/// Sum over all servers, for the tray icon and the taskbar / dock.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BadgeTotal {
+    pub mentions: u32,
+    pub unread: bool,
+    /// Sum of the counts page titles give.
+    pub unread_count: u32,
+}
+
+impl BadgeTotal {
+    /// The number on the badge: mentions while there are any, since they
+    /// are what the user was notified of; otherwise what the titles count.
+    pub fn count(&self) -> u32 {
+        if self.mentions > 0 { self.mentions } else { self.unread_count }
+    }
+
+    /// Text drawn on the badge: `count`, or None for a plain dot (unread,
+    /// nothing counted) or no badge at all.
+    pub fn label(&self) -> Option<String> {
+        match self.count() {
+            0 => None,
+            n @ 1..=9 => Some(n.to_string()),
+            _ => Some("9+".to_string()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mentions == 0 && !self.unread
+    }
+}
+
+pub struct BadgeState {
+    servers: std::collections::HashMap<ServerId, ServerBadge>,
+    /// Server the main window shows, as reported by the UI.
+    viewed: Option<ServerId>,
+    window_focused: bool,
+    /// Last total drawn on the tray icon and taskbar.
+    shown: BadgeTotal,
+}
+
+impl Default for BadgeState {
+    fn default() -> Self {
+        Self {
+            servers: std::collections::HashMap::new(),
+            viewed: None,
+            // The main window opens focused.
+            window_focused: true,
+            shown: BadgeTotal::default(),
+        }
+    }
+}
+
+impl BadgeState {
+    pub fn servers(&self) -> &std::collections::HashMap<ServerId, ServerBadge> {
+        &self.servers
+    }
+
+    pub fn viewed(&self) -> Option<&ServerId> {
+        self.viewed.as_ref()
+    }
+
+    pub fn total(&self) -> BadgeTotal {
+        self.servers.values().fold(BadgeTotal::default(), |total, badge| BadgeTotal {
+            mentions: total.mentions.saturating_add(badge.mentions),
+            unread: total.unread || badge.unread,
+            unread_count: total.unread_count.saturating_add(badge.unread_count.unwrap_or(0)),
+        })
+    }
+
+    /// Record what the page title of `id` says. Each method returns whether
+    /// anything changed.
+    pub fn set_title(&mut self, id: &ServerId, title: TitleBadge) -> bool {
+        let badge = self.servers.entry(id.clone()).or_default();
+        let before = *badge;
+        (badge.unread, badge.unread_count) = match title {
+            TitleBadge::Clear => (false, None),
+            TitleBadge::Marked => (true, None),
+            TitleBadge::Count(n) => (true, Some(n)),
+        };
+        *badge != before
+    }
+
+    /// A notification from `id`. It only counts when the user isn't looking:
+    /// neither the page (`page_focused`) nor the main window on that server
+    /// had focus.
+    pub fn add_mention(&mut self, id: &ServerId, page_focused: bool) -> bool {
+        if page_focused || (self.window_focused && self.viewed.as_ref() == Some(id)) {
+            return false;
+        }
+        let badge = self.servers.entry(id.clone()).or_default();
+        badge.mentions = badge.mentions.saturating_add(1);
+        true
+    }
+
+    /// The UI switched to `id` (None: home or settings). Its mentions have
+    /// now been seen.
+    pub fn set_viewed(&mut self, id: Option<ServerId>) -> bool {
+        self.viewed = id;
+        self.clear_viewed_mentions()
+    }
+
+    pub fn set_window_focused(&mut self, focused: bool) -> bool {
+        self.window_focused = focused;
+        focused && self.clear_viewed_mentions()
+    }
+
+    pub fn remove(&mut self, id: &ServerId) -> bool {
+        self.servers.remove(id).is_some()
+    }
+
+    fn clear_viewed_mentions(&mut self) -> bool {
+        let Some(badge) = self.viewed.as_ref().and_then(|id| self.servers.get_mut(id)) else { return false };
+        std::mem::take(&mut badge.mentions) > 0
+    }
+}
+
+pub type SharedBadgeState = Arc<Mutex<BadgeState>>;
+
+/// Payload of `badges://changed`: every server's badge plus the total.
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BadgesChangedEvent {
+    pub servers: std::collections::HashMap<ServerId, ServerBadge>,
+    pub total: BadgeTotal,
+}
+
+impl BadgesChangedEvent {
+    fn new(state: &BadgeState) -> Self {
+        Self { servers: state.servers().clone(), total: state.total() }
+    }
+}
+
+/// Apply `change` and, if it changed anything, tell the UI and redraw the
+/// tray icon and taskbar badge.
+pub(crate) fn update_badges(app: &AppHandle, change: impl FnOnce(&mut BadgeState) -> bool) {
+    let state = app.state::<SharedBadgeState>();
+    let mut locked = state.lock().unwrap();
+    if !change(&mut locked) {
+        return;
+    }
+    let event = BadgesChangedEvent::new(&locked);
+    let total = event.total;
+    let _ = app.emit(BADGES_CHANGED_EVENT, event);
+    let redraw = total != locked.shown;
+    locked.shown = total;
+    drop(locked);
+    if redraw {
+        show_badge_total(app, total);
+    }
+}
+
+/// Blend `color` over the pixel at (x, y) with the given coverage.
+fn blend_pixel(img: &mut image::RgbaImage, x: u32, y: u32, color: [u8; 3], coverage: f32) {
+    if coverage <= 0.0 || x >= img.width() || y >= img.height() {
+        return;
+    }
+    let a = coverage.min(1.0);
+    let px = img.get_pixel_mut(x, y);
+    let dst_a = px[3] as f32 / 255.0;
+    let out_a = a + dst_a * (1.0 - a);
+    for c in 0..3 {
+        let blended = (color[c] as f32 * a + px[c] as f32 * dst_a * (1.0 - a)) / out_a;
+        px[c] = blended.round() as u8;
+    }
+    px[3] = (out_a * 255.0).round() as u8;
+}
+
+/// Draw a badge — a red disc of diameter `d` with `label` in white, or a
+/// plain disc when there is no label — with its top-left corner at (x0, y0).
+fn draw_badge(img: &mut image::RgbaImage, x0: u32, y0: u32, d: u32, label: Option<&str>) {
+    let r = d as f32 / 2.0;
+    for y in 0..d {
+        for x in 0..d {
+            let (dx, dy) = (x as f32 + 0.5 - r, y as f32 + 0.5 - r);
+            let coverage = r - (dx * dx + dy * dy).sqrt() + 0.5;
+            blend_pixel(img, x0 + x, y0 + y, BADGE_COLOR, coverage);
+        }
+    }
+
+    let Some(label) = label else { return };
+    let glyphs: Vec<&[u8; 5]> = label
+        .chars()
+        .filter_map(|c| BADGE_GLYPHS.iter().find(|(g, _)| *g == c).map(|(_, rows)| rows))
+        .collect();
+    if glyphs.is_empty() {
+        return;
+    }
+    // Whole-pixel scaling keeps the digits crisp; shrink until they fit
+    // inside the disc.
+    let n = glyphs.len() as u32;
+    let width_at = |s: u32| n * 3 * s + (n - 1) * s;
+    let mut s = (d * 3 / 5 / 5).max(1);
+    while s > 1 && width_at(s) > d * 4 / 5 {
+        s -= 1;
+    }
+    let tx = x0 + d.saturating_sub(width_at(s)) / 2;
+    let ty = y0 + d.saturating_sub(5 * s) / 2;
+    for (i, rows) in glyphs.iter().enumerate() {
+        let gx = tx + i as u32 * 4 * s;
+        for (row, bits) in rows.iter().enumerate() {
+            for col in 0..3 {
+                if bits & (0b100 >> col) == 0 {
+                    continue;
+                }
+                for py in 0..s {
+                    for px in 0..s {
+                        blend_pixel(img, gx + col * s + px, ty + row as u32 * s + py, [255, 255, 255], 1.0);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// `base` with a badge in its bottom-right corner; without a label it is a
+/// smaller dot.
+pub fn overlay_badge(base: &image::RgbaImage, label: Option<&str>) -> image::RgbaImage {
+    let mut img = base.clone();
+    let size = img.width().min(img.height());
+    let d = if label.is_some() { size * 11 / 20 } else { size * 7 / 20 };
+    let (x0, y0) = (img.width() - d, img.height() - d);
+    draw_badge(&mut img, x0, y0, d, label);
+    img
+}
+
+/// A badge on its own, `size` pixels square (the Windows taskbar overlay).
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn badge_image(size: u32, label: Option<&str>) -> image::RgbaImage {
+    let mut img = image::RgbaImage::new(size, size);
+    draw_badge(&mut img, 0, 0, size, label);
+    img
+}
+
+pub(crate) fn tauri_image(img: image::RgbaImage) -> tauri::image::Image<'static> {
+    let (width, height) = img.dimensions();
+    tauri::image::Image::new_owned(img.into_raw(), width, height)
+}
+
+/// Draw `total` on the tray icon and the taskbar / dock entry.
+fn show_badge_total(app: &AppHandle, total: BadgeTotal) {
+    let label = total.label();
+
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        if let Some(base) = tray_base_icon(app) {
+            let icon = if total.is_empty() { base.clone() } else { overlay_badge(base, label.as_deref()) };
+            if let Err(e) = tray.set_icon(Some(tauri_image(icon))) {
+                log::warn!("Failed to update tray icon: {e}");
+            }
+        }
+        let _ = tray.set_tooltip(Some(tray_tooltip(app, total)));
+    }
+
+    let Some(window) = app.get_window("main") else { return };
+    // Windows has no badge count, only an overlay icon; the dock takes any
+    // label; Linux launchers (Unity API) only show a number.
+    #[cfg(windows)]
+    let result = window.set_overlay_icon((!total.is_empty()).then(|| tauri_image(badge_image(32, label.as_deref()))));
+    #[cfg(target_os = "macos")]
+    let result = window.set_badge_label((!total.is_empty()).then(|| label.unwrap_or_else(|| "•".to_string())));
+    #[cfg(not(any(windows, target_os = "macos")))]
+    let result = window.set_badge_count((total.count() > 0).then_some(i64::from(total.count())));
+    if let Err(e) = result {
+        log::warn!("Failed to update taskbar badge: {e}");
+    }
+}
+
+/// Called by BADGE_SCRIPT whenever a server page's title changes, from the
+/// server's own pages only (`bridge_caller`).
+#[tauri::command]
+pub(crate) fn report_title(
+    app: AppHandle,
+    webview: tauri::Webview,
+    registry: tauri::State<'_, SharedServerRegistry>,
+    title: String,
+) -> Result<(), String> {
+    let server = bridge_caller(&webview, &registry)?;
+    let badge = parse_title_badge(&title);
+    update_badges(&app, |state| state.set_title(&server.id, badge));
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn get_badges(state: tauri::State<'_, SharedBadgeState>) -> BadgesChangedEvent {
+    BadgesChangedEvent::new(&state.lock().unwrap())
+}
+
+/// The UI switched views; `server_id` is None for home and settings.
+#[tauri::command]
+pub(crate) fn set_viewed_server(app: AppHandle, server_id: Option<ServerId>) {
+    update_badges(&app, |state| state.set_viewed(server_id));
+    refresh_tray_menu(&app);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::ID;
+
+    #[test]
+    fn parse_title_badge_reads_counts_and_marks() {
+        for (title, expected) in [
+            ("(3) Sharkord", TitleBadge::Count(3)),
+            ("  [12] general — Sharkord", TitleBadge::Count(12)),
+            ("( 7 ) Sharkord", TitleBadge::Count(7)),
+            ("(99+) Sharkord", TitleBadge::Count(99)),
+            ("(99999999999) Sharkord", TitleBadge::Count(u32::MAX)),
+            ("(0) Sharkord", TitleBadge::Clear),
+            ("• Sharkord", TitleBadge::Marked),
+            ("● Sharkord", TitleBadge::Marked),
+            ("* Sharkord", TitleBadge::Marked),
+            ("Sharkord", TitleBadge::Clear),
+            ("", TitleBadge::Clear),
+            ("(beta) Sharkord", TitleBadge::Clear),
+            ("(3 Sharkord", TitleBadge::Clear),
+            ("(-3) Sharkord", TitleBadge::Clear),
+            ("Sharkord (3)", TitleBadge::Clear),
+        ] {
+            assert_eq!(parse_title_badge(title), expected, "{title:?}");
+        }
+    }
+
+    #[test]
+    fn badge_state_counts_mentions_only_while_not_looking() {
+        let a = ServerId::parse(ID).unwrap();
+        let b = ServerId::parse("0a1b2c3d-0000-4000-8000-000000000000").unwrap();
+        let mut state = BadgeState::default();
+
+        // The page had focus, or the window shows that server: seen.
+        assert!(!state.add_mention(&a, true));
+        state.set_viewed(Some(a.clone()));
+        assert!(!state.add_mention(&a, false));
+        // Another server, or the window in the background: counted.
+        assert!(state.add_mention(&b, false));
+        state.set_window_focused(false);
+        assert!(state.add_mention(&a, false));
+        assert_eq!(state.total().mentions, 2);
+
+        // Focusing the window clears the viewed server's mentions only.
+        assert!(state.set_window_focused(true));
+        assert_eq!(state.servers()[&a].mentions, 0);
+        assert!(state.set_viewed(Some(b.clone())));
+        assert_eq!(state.total(), BadgeTotal::default());
+        assert!(!state.set_viewed(None));
+    }
+
+    #[test]
+    fn badge_total_falls_back_to_title_counts() {
+        let a = ServerId::parse(ID).unwrap();
+        let b = ServerId::parse("0a1b2c3d-0000-4000-8000-000000000000").unwrap();
+        let mut state = BadgeState::default();
+        state.set_viewed(Some(a.clone()));
+
+        assert!(state.set_title(&a, TitleBadge::Count(4)));
+        assert!(!state.set_title(&a, TitleBadge::Count(4)));
+        assert!(state.set_title(&b, TitleBadge::Marked));
+        let total = state.total();
+        assert_eq!((total.unread, total.unread_count, total.mentions), (true, 4, 0));
+        assert_eq!(total.label().as_deref(), Some("4"));
+
+        // Mentions take over the number while there are any.
+        state.add_mention(&b, false);
+        assert_eq!(state.total().label().as_deref(), Some("1"));
+        state.set_title(&b, TitleBadge::Count(u32::MAX));
+        assert_eq!(state.total().unread_count, u32::MAX);
+        state.set_viewed(Some(b.clone()));
+        assert_eq!(state.total().label().as_deref(), Some("9+"));
+
+        // Only a mark: a plain dot.
+        state.set_title(&a, TitleBadge::Clear);
+        state.set_title(&b, TitleBadge::Marked);
+        let total = state.total();
+        assert_eq!((total.label(), total.is_empty()), (None, false));
+        assert!(state.remove(&b));
+        assert!(state.total().is_empty());
+    }
+
+    #[test]
+    fn overlay_badge_draws_in_the_bottom_right_corner() {
+        let base = image::RgbaImage::from_pixel(32, 32, image::Rgba([0, 0, 255, 255]));
+        let red = |img: &image::RgbaImage, x, y| img.get_pixel(x, y).0[..3] == BADGE_COLOR;
+
+        let dot = overlay_badge(&base, None);
+        assert_eq!(dot.dimensions(), (32, 32));
+        // 7/20 of 32: an 11 px dot, centred at (26.5, 26.5).
+        assert!(red(&dot, 26, 26));
+        assert!(!red(&dot, 20, 26));
+        assert_eq!(dot.get_pixel(0, 0), base.get_pixel(0, 0));
+
+        let counted = overlay_badge(&base, Some("9+"));
+        // 11/20 of 32: a 17 px badge, reaching further in than the dot.
+        assert!(red(&counted, 17, 24));
+        assert!(!red(&dot, 17, 24));
+        // White digits inside it.
+        assert!(counted.pixels().any(|p| p.0 == [255, 255, 255, 255]));
+        assert!(!dot.pixels().any(|p| p.0 == [255, 255, 255, 255]));
+        assert_eq!(counted.get_pixel(0, 0), base.get_pixel(0, 0));
+    }
+}
@@ -1,7 +1,26 @@
-use tauri::{AppHandle, Emitter, LogicalPosition, LogicalSize, Manager, WebviewUrl};
+use tauri::{AppHandle, Emitter, LogicalPosition, LogicalSize, Manager, Runtime, WebviewUrl};
 use tauri::webview::WebviewBuilder;
 use std::sync::{Arc, Mutex};
 
+#[cfg(target_os = "linux")]
+mod pulse;
+mod pinning;
+mod proxy;
+mod probe;
+mod navigation;
+mod notifications;
+mod badges;
+mod tray;
+mod shortcuts;
+
+use badges::{BADGE_SCRIPT, SharedBadgeState, update_badges};
+use navigation::{NavigationTarget, classify_navigation, open_navigation_externally, server_in_app_domains};
+use notifications::{NOTIFICATION_SCRIPT, NotificationSettings, SharedNotificationSettings, server_bridge_plugin};
+use probe::{probe, read_body_limited, server_http_client};
+use proxy::{ServerProxy, server_connection_async, store_proxy_password};
+use shortcuts::{SharedShortcuts, load_shortcuts, recheck_shortcuts, register_shortcuts};
+use tray::{SharedTraySettings, TraySettings, build_tray, hides_on_close, refresh_tray_menu};
+
 const CONTEXT_MENU_SCRIPT: &str = r#"
 (function () {
   /* Styles are injected lazily on first _show() call — DO NOT touch the DOM
@@ -194,679 +213,5069 @@ const CONTEXT_MENU_SCRIPT: &str = r#"
 })();
 "#;
 
+// ── Server ids ───────────────────────────────────────────────────────────────
+//
+// Server ids arrive from the frontend and end up in filesystem paths and
+// webview labels, so every command takes a parsed `ServerId` rather than a raw
+// String: a malformed id is rejected at the IPC boundary instead of being
+// joined onto the data directory.
+
+/// A server id as issued by the frontend (`crypto.randomUUID()`): a strict
+/// lowercase, hyphenated UUID. Uppercase is rejected rather than folded so
+/// one server can never map to two spellings of the same directory on a
+/// case-insensitive filesystem.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ServerId(String);
+
+impl ServerId {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let valid = s.len() == 36
+            && s.bytes().enumerate().all(|(i, b)| match i {
+                8 | 13 | 18 | 23 => b == b'-',
+                _ => b.is_ascii_digit() || (b'a'..=b'f').contains(&b),
+            });
+        if !valid {
+            return Err(format!("Invalid server id: {s:?}"));
+        }
+        Ok(Self(s.to_string()))
+    }
+
+    /// Parse the id back out of a server webview label (`srv-<id>`).
+    pub fn from_webview_label(label: &str) -> Result<Self, String> {
+        label
+            .strip_prefix(SERVER_WEBVIEW_PREFIX)
+            .ok_or_else(|| format!("Not a server webview: {label:?}"))
+            .and_then(Self::parse)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn webview_label(&self) -> String {
+        format!("{SERVER_WEBVIEW_PREFIX}{}", self.0)
+    }
 
-fn uuid_to_bytes(uuid_str: &str) -> [u8; 16] {
-    let hex: String = uuid_str.chars().filter(|c| *c != '-').collect();
-    let mut bytes = [0u8; 16];
-    for i in 0..16 {
-        let slice = hex.get(i * 2..i * 2 + 2).unwrap_or("00");
-        bytes[i] = u8::from_str_radix(slice, 16).unwrap_or(0);
+    /// The 16 raw UUID bytes.
+    pub fn to_bytes(&self) -> [u8; 16] {
+        fn nibble(b: u8) -> u8 {
+            match b {
+                b'0'..=b'9' => b - b'0',
+                _ => b - b'a' + 10,
+            }
+        }
+        let hex: Vec<u8> = self.0.bytes().filter(|b| *b != b'-').collect();
+        let mut bytes = [0u8; 16];
+        for (byte, pair) in bytes.iter_mut().zip(hex.chunks_exact(2)) {
+            *byte = nibble(pair[0]) << 4 | nibble(pair[1]);
+        }
+        bytes
     }
-    bytes
 }
 
-#[tauri::command]
-async fn create_server_webview(
-    app: AppHandle,
-    label: String,
-    url: String,
-    server_id: String,
-    x: f64,
-    y: f64,
-    width: f64,
-    height: f64,
-    mic_id: Option<String>,
-    cam_id: Option<String>,
-    speaker_id: Option<String>,
-) -> Result<(), String> {
-    if let Some(existing) = app.get_webview(&label) {
-        existing.close().map_err(|e: tauri::Error| e.to_string())?;
+impl std::fmt::Display for ServerId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
     }
+}
 
-    let parsed_url: url::Url = url.parse().map_err(|e: url::ParseError| e.to_string())?;
+impl serde::Serialize for ServerId {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&self.0)
+    }
+}
 
-    // Each server gets its own subdirectory under the app data dir:
-    //   Windows: %APPDATA%\Sweetshark-client-v2\servers\<uuid>\
-    //   macOS:   ~/Library/Application Support/Sweetshark-client-v2/servers/<uuid>/
-    //   Linux:   ~/.local/share/Sweetshark-client-v2/servers/<uuid>/
-    let data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e: tauri::Error| e.to_string())?
-        .join("servers")
-        .join(&server_id);
+impl<'de> serde::Deserialize<'de> for ServerId {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(d)?;
+        Self::parse(&s).map_err(serde::de::Error::custom)
+    }
+}
 
-    // Tauri never auto-creates directories — we must do it ourselves.
-    std::fs::create_dir_all(&data_dir)
-        .map_err(|e| format!("Failed to create data dir: {e}"))?;
+/// `<root>/<id>`, refusing anything that — once symlinks are resolved — is
+/// not a direct child of `root`. `root` must already be canonical. The
+/// directory itself may not exist yet, in which case the joined path is
+/// returned as-is (a valid `ServerId` cannot contain separators or `..`).
+pub fn contained_server_dir(root: &std::path::Path, id: &ServerId) -> Result<std::path::PathBuf, String> {
+    let dir = root.join(id.as_str());
+    let resolved = match dir.canonicalize() {
+        Ok(p) => p,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => dir.clone(),
+        Err(e) => return Err(format!("Failed to resolve server data dir: {e}")),
+    };
+    if resolved.parent() != Some(root) {
+        return Err(format!(
+            "Server data dir escapes {}: {}",
+            root.display(),
+            resolved.display()
+        ));
+    }
+    Ok(dir)
+}
 
-    let window = app
-        .get_window("main")
-        .ok_or_else(|| "Main window not found".to_string())?;
+/// The data directory for one server:
+///   Windows: %APPDATA%\Sweetshark-client-v2\servers\<uuid>\
+///   macOS:   ~/Library/Application Support/Sweetshark-client-v2/servers/<uuid>/
+///   Linux:   ~/.local/share/Sweetshark-client-v2/servers/<uuid>/
+fn server_data_dir(app: &AppHandle, id: &ServerId) -> Result<std::path::PathBuf, String> {
+    contained_server_dir(&servers_root(app)?, id)
+}
 
-    // Build the device-preference override script with the chosen device IDs
-    // baked in at webview creation time. It patches getUserMedia, enumerateDevices,
-    // and auto-applies setSinkId on audio/video elements so the Sharkord server
-    // can only ever see / use the devices the user has selected.
-    let mic_js    = mic_id    .as_deref().unwrap_or("");
-    let cam_js    = cam_id    .as_deref().unwrap_or("");
-    let speaker_js = speaker_id.as_deref().unwrap_or("");
-    let device_script = format!(r#"
-(function(){{
-  var MIC_ID     = {mic_json};
-  var CAM_ID     = {cam_json};
-  var SPEAKER_ID = {speaker_json};
+// ── Server registry ──────────────────────────────────────────────────────────
+//
+// The list of servers lives here rather than in the frontend so the backend
+// knows which servers exist (to restore webviews, garbage-collect orphaned
+// data dirs, …). It is persisted to the same config.json store the frontend
+// uses, under a schema version so older blobs can be migrated on load.
 
-  /* ── Helpers: resolve a stored device label to a live deviceId ─────── */
-  // Device IDs are not stable across isolated WebView2 contexts (each server
-  // webview has its own data directory, so the browser assigns different IDs
-  // to the same physical device). Labels ARE stable — we use those instead.
-  function _resolveId(kind, label) {{
-    // Returns a Promise<string|null>
-    var _e = navigator.mediaDevices.enumerateDevices.__ss_orig ||
-             navigator.mediaDevices.enumerateDevices.bind(navigator.mediaDevices);
-    return _e().then(function(all) {{
-      var match = all.find(function(d) {{ return d.kind === kind && d.label === label; }});
-      return match ? match.deviceId : null;
-    }});
-  }}
+/// Store file shared with the frontend.
+const CONFIG_STORE: &str = "config.json";
+const SERVERS_KEY: &str = "servers";
+const SERVERS_VERSION_KEY: &str = "serversVersion";
 
-  /* ── getUserMedia: force selected device by label ───────────────────── */
-  var _gum = navigator.mediaDevices.getUserMedia.bind(navigator.mediaDevices);
-  navigator.mediaDevices.getUserMedia = function(c) {{
-    c = c ? JSON.parse(JSON.stringify(c)) : {{}};
-    var promises = [];
-    if (MIC_ID && c.audio) {{
-      promises.push(_resolveId('audioinput', MIC_ID).then(function(id) {{
-        if (id) c.audio = typeof c.audio === 'object'
-          ? Object.assign({{}}, c.audio, {{ deviceId: {{ exact: id }} }})
-          : {{ deviceId: {{ exact: id }} }};
-      }}));
-    }}
-    if (CAM_ID && c.video) {{
-      promises.push(_resolveId('videoinput', CAM_ID).then(function(id) {{
-        if (id) c.video = typeof c.video === 'object'
-          ? Object.assign({{}}, c.video, {{ deviceId: {{ exact: id }} }})
-          : {{ deviceId: {{ exact: id }} }};
-      }}));
-    }}
-    return Promise.all(promises).then(function() {{ return _gum(c); }});
-  }};
+/// Version of the layout under `SERVERS_KEY`.
+///   0 — written by the frontend before the registry existed (no version key)
+///   1 — same fields, ids guaranteed to be valid `ServerId`s
+///   2 — custom icons moved out of the store into servers/<id>/icon.png,
+///       recorded by `iconRev`
+///   3 — `certSha256` pins a self-signed certificate; bumped so an older
+///       build can't save the list and silently drop the pins
+///   4 — `proxy` per server (its password is in the OS credential store)
+///   5 — `inAppDomains`, sign-in hosts kept inside the server webview
+///   6 — `notificationsMuted`
+const SERVERS_SCHEMA_VERSION: u64 = 6;
 
-  /* ── enumerateDevices: hide unselected devices (filter by label) ─────── */
-  var _enum = navigator.mediaDevices.enumerateDevices.bind(navigator.mediaDevices);
-  // Store original so _resolveId can bypass our override
-  navigator.mediaDevices.enumerateDevices.__ss_orig = _enum;
-  navigator.mediaDevices.enumerateDevices = function() {{
-    return _enum().then(function(all) {{
-      return all.filter(function(d) {{
-        // Always pass through devices with empty deviceId or label — these are
-        // returned before permission is granted (browser privacy masking).
-        if (!d.deviceId || !d.label) return true;
-        if (d.kind === 'audioinput')  return !MIC_ID     || d.deviceId === 'default' || d.label === MIC_ID;
-        if (d.kind === 'videoinput')  return !CAM_ID     || d.label === CAM_ID;
-        if (d.kind === 'audiooutput') return !SPEAKER_ID || d.deviceId === 'default' || d.label === SPEAKER_ID;
-        return true;
-      }});
-    }});
-  }};
+/// One saved server. Mirrors `Server` in App.tsx.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Server {
+    pub id: ServerId,
+    pub name: String,
+    pub url: String,
+    pub icon_letter: String,
+    pub icon_color: String,
+    /// A custom icon not yet cached on disk (pre-v2 entries, or a data URL
+    /// sent by the frontend). `add_server` / `update_server` move it to the
+    /// icon file, so stored entries no longer carry one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+    /// Set while servers/<id>/icon.png holds a custom icon; changes with it.
+    /// None (and no `icon_url`) shows the letter.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_rev: Option<u64>,
+    /// When false the webview is destroyed while the server isn't active.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_loaded: Option<bool>,
+    /// Fingerprint of a certificate the user chose to trust although it fails
+    /// normal verification. Only changed by `trust_server_certificate`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cert_sha256: Option<String>,
+    /// Only changed by `set_server_proxy`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<ServerProxy>,
+    /// SSO / OAuth domains that open inside the webview instead of the
+    /// system browser. Only changed by `set_server_in_app_domains`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub in_app_domains: Vec<String>,
+    /// Drop this server's notifications. Only changed by
+    /// `set_server_notifications_muted`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub notifications_muted: bool,
+}
 
-  /* ── setSinkId: auto-apply speaker to all audio/video elements ──────── */
-  if (SPEAKER_ID) {{
-    // Resolve speaker label to actual deviceId for setSinkId
-    var _resolvedSpeakerId = null;
-    _resolveId('audiooutput', SPEAKER_ID).then(function(id) {{ _resolvedSpeakerId = id; }});
-    function _sink(el) {{
-      if (el.setSinkId && !el.__ssSink) {{
-        el.__ssSink = true;
-        var sid = _resolvedSpeakerId || SPEAKER_ID;
-        el.setSinkId(sid).catch(function(){{}});
-      }}
-    }}
-    var _obs = new MutationObserver(function(ms) {{
-      ms.forEach(function(m) {{
-        m.addedNodes.forEach(function(n) {{
-          if (n.nodeType !== 1) return;
-          if (n.tagName === 'AUDIO' || n.tagName === 'VIDEO') _sink(n);
-          if (n.querySelectorAll) n.querySelectorAll('audio,video').forEach(_sink);
-        }});
-      }});
-    }});
-    _obs.observe(document.documentElement, {{ subtree: true, childList: true }});
-    document.addEventListener('DOMContentLoaded', function() {{
-      document.querySelectorAll('audio,video').forEach(_sink);
-    }});
-  }}
-}})();
-"#,
-        mic_json     = serde_json::to_string(mic_js)    .unwrap_or_default(),
-        cam_json     = serde_json::to_string(cam_js)    .unwrap_or_default(),
-        speaker_json = serde_json::to_string(speaker_js).unwrap_or_default(),
-    );
+/// Everything `add_server` needs; the id is assigned by the registry.
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewServer {
+    pub name: String,
+    pub url: String,
+    pub icon_letter: String,
+    pub icon_color: String,
+    #[serde(default)]
+    pub icon_url: Option<String>,
+}
 
+#[derive(Clone, Default)]
+pub struct ServerRegistry {
+    /// In sidebar order: the sidebar lists `list_servers` as it comes and has
+    /// no sorting or drag-to-reorder, so `add` appends and every other change
+    /// keeps positions. "Switch to server N" shortcuts count in this order;
+    /// reordering the sidebar would have to reorder this list too.
+    servers: Vec<Server>,
+    /// Set when the store was written by a newer build, or couldn't be read
+    /// at all; we then never save, so a downgrade or a bad read can't clobber
+    /// what is stored.
+    read_only: bool,
+    /// Stored entries that don't parse as a `Server`, kept verbatim and
+    /// written back after `servers` so a save never loses them.
+    unreadable: Vec<serde_json::Value>,
+    /// Some stored servers are missing from `servers`: the list couldn't be
+    /// read, or some entries couldn't. Their data dirs then look orphaned.
+    incomplete: bool,
+}
 
-    // PTT is now handled at the OS level via IAudioEndpointVolume (Windows Core
-    // Audio API). No JavaScript injection needed for mute control.
-    const PTT_SCRIPT: &str = "";
+pub type SharedServerRegistry = Arc<Mutex<ServerRegistry>>;
 
-    let combined_init = format!("{CONTEXT_MENU_SCRIPT}
-{device_script}
-{PTT_SCRIPT}");
+impl ServerRegistry {
+    /// Build a registry from the raw store values, migrating older layouts.
+    /// Entries that can't be parsed are set aside (and logged) rather than
+    /// failing the whole load, and saved back unchanged.
+    pub fn from_stored(version: Option<u64>, servers: Option<serde_json::Value>) -> Self {
+        let version = version.unwrap_or(0);
+        let mut registry = Self { read_only: version > SERVERS_SCHEMA_VERSION, ..Self::default() };
+        if registry.read_only {
+            log::warn!("Server list was saved by a newer version (schema {version}); not saving changes");
+        }
+        let entries = match servers {
+            Some(serde_json::Value::Array(entries)) => entries,
+            None | Some(serde_json::Value::Null) => Vec::new(),
+            Some(other) => {
+                // Nothing to keep apart from the whole value, so leave it be.
+                log::error!("Server list is not a list ({other}); not saving changes");
+                return Self::unavailable();
+            }
+        };
+        // v0 → v1: the layout is unchanged; the typed parse below is the
+        // migration, since it rejects ids the frontend never validated.
+        for entry in entries {
+            match serde_json::from_value::<Server>(entry.clone()) {
+                Ok(server) if registry.get(&server.id).is_none() => registry.servers.push(server),
+                Ok(server) => log::warn!("Dropping duplicate server entry {}", server.id),
+                Err(e) => {
+                    log::warn!("Keeping unreadable server entry aside: {e}");
+                    registry.unreadable.push(entry);
+                    registry.incomplete = true;
+                }
+            }
+        }
+        registry
+    }
 
-    let mut builder = WebviewBuilder::new(&label, WebviewUrl::External(parsed_url))
-        .data_directory(data_dir)
-        .initialization_script(&combined_init)
-        // Tauri intercepts drag-and-drop at the OS level by default, which
-        // prevents the browser's native DataTransfer / drop events from firing
-        // inside the webview. Disabling it lets Sharkord's file upload handler
-        // receive drag-and-drop events exactly as it would in a normal browser.
-        .disable_drag_drop_handler();
+    /// Stand-in for a server list that couldn't be read: empty, never saved
+    /// over what is stored, and not trusted for the orphan sweep.
+    pub fn unavailable() -> Self {
+        Self { read_only: true, incomplete: true, ..Self::default() }
+    }
 
-    // data_store_identifier adds WebView2-level environment isolation on Windows,
-    // on top of the separate data_directory above.
-    #[cfg(target_os = "windows")]
-    {
-        builder = builder.data_store_identifier(uuid_to_bytes(&server_id));
+    /// Whether every stored server is listed, so a data dir that belongs to
+    /// none of them really is orphaned.
+    pub fn is_complete(&self) -> bool {
+        !self.incomplete
     }
 
-    let wv = window
-        .add_child(
-            builder,
-            LogicalPosition::new(x, y),
-            LogicalSize::new(width, height),
-        )
-        .map_err(|e: tauri::Error| e.to_string())?;
+    /// The value saved under `SERVERS_KEY`: the servers, then any entries
+    /// that couldn't be read.
+    fn stored_value(&self) -> Result<serde_json::Value, String> {
+        let mut entries: Vec<serde_json::Value> =
+            self.servers.iter().map(serde_json::to_value).collect::<Result<_, _>>().map_err(|e| e.to_string())?;
+        entries.extend(self.unreadable.iter().cloned());
+        Ok(serde_json::Value::Array(entries))
+    }
 
-    // On Windows: disable the native WebView2 context menu entirely at the OS
-    // level via ICoreWebView2Settings. This is the only reliable way to prevent
-    // the native edit/image/inspect menus from appearing alongside our custom JS
-    // menu.  Our initialization_script still shows the custom JS menu because
-    // the JS `contextmenu` event fires independently of AreDefaultContextMenusEnabled.
-    // We also disable DevTools (F12 / Ctrl+Shift+I) since this is a production client.
-    #[cfg(windows)]
-    {
-        let wv_clone = wv.clone();
-        // spawn_blocking so we don't deadlock the async command's thread —
-        // with_webview dispatches the closure to the main thread synchronously.
-        tauri::async_runtime::spawn(async move {
-            wv_clone.with_webview(|webview| {
-                unsafe {
-                    // Settings() already returns ICoreWebView2Settings — no cast needed
-                    let settings = webview
-                        .controller()
-                        .CoreWebView2()
-                        .expect("get CoreWebView2")
-                        .Settings()
-                        .expect("get ICoreWebView2Settings");
-
-                    // webview2-com 0.38 wraps these methods to take plain Rust bool
-                    settings
-                        .SetAreDefaultContextMenusEnabled(false)
-                        .expect("SetAreDefaultContextMenusEnabled");
-                    settings
-                        .SetAreDevToolsEnabled(false)
-                        .expect("SetAreDevToolsEnabled");
-                }
-            }).ok();
-        });
+    fn load(app: &AppHandle) -> Result<Self, String> {
+        use tauri_plugin_store::StoreExt;
+
+        let store = app.store(CONFIG_STORE).map_err(|e| e.to_string())?;
+        let version = store.get(SERVERS_VERSION_KEY).and_then(|v| v.as_u64());
+        let mut registry = Self::from_stored(version, store.get(SERVERS_KEY));
+        // v1 → v2: needs the filesystem, so it runs here rather than in
+        // `from_stored`. An icon that can't be cached stays inline and is
+        // retried on the next launch.
+        let mut icons_moved = false;
+        if !registry.read_only {
+            for server in &mut registry.servers {
+                icons_moved |= cache_inline_icon(app, server);
+            }
+        }
+        if (icons_moved || version != Some(SERVERS_SCHEMA_VERSION)) && !registry.read_only {
+            // Write the migrated layout back now; if that fails the next
+            // change will try again.
+            if let Err(e) = registry.save(app) {
+                log::warn!("Failed to save migrated server list: {e}");
+            }
+        }
+        Ok(registry)
     }
 
-    Ok(())
+    fn save(&self, app: &AppHandle) -> Result<(), String> {
+        use tauri_plugin_store::StoreExt;
+
+        if self.read_only {
+            return Ok(());
+        }
+        let store = app.store(CONFIG_STORE).map_err(|e| e.to_string())?;
+        store.set(SERVERS_KEY, self.stored_value()?);
+        store.set(SERVERS_VERSION_KEY, SERVERS_SCHEMA_VERSION);
+        store.save().map_err(|e| e.to_string())
+    }
+
+    pub fn list(&self) -> &[Server] {
+        &self.servers
+    }
+
+    pub fn get(&self, id: &ServerId) -> Option<&Server> {
+        self.servers.iter().find(|s| &s.id == id)
+    }
+
+    pub fn add(&mut self, server: Server) -> Result<(), String> {
+        if self.get(&server.id).is_some() {
+            return Err(format!("Server {} already exists", server.id));
+        }
+        self.servers.push(server);
+        Ok(())
+    }
+
+    /// Replace an existing entry, keeping its position.
+    pub fn update(&mut self, server: Server) -> Result<(), String> {
+        let slot = self
+            .servers
+            .iter_mut()
+            .find(|s| s.id == server.id)
+            .ok_or_else(|| format!("Unknown server {}", server.id))?;
+        *slot = server;
+        Ok(())
+    }
+
+    /// Change one field or more of an existing entry in place.
+    pub fn modify(&mut self, id: &ServerId, f: impl FnOnce(&mut Server)) -> Result<(), String> {
+        let server = self
+            .servers
+            .iter_mut()
+            .find(|s| &s.id == id)
+            .ok_or_else(|| format!("Unknown server {id}"))?;
+        f(server);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, id: &ServerId) -> Result<Server, String> {
+        let index = self
+            .servers
+            .iter()
+            .position(|s| &s.id == id)
+            .ok_or_else(|| format!("Unknown server {id}"))?;
+        Ok(self.servers.remove(index))
+    }
 }
 
-#[tauri::command]
-async fn reload_server_webview(app: AppHandle, label: String) -> Result<(), String> {
-    let webview = app
-        .get_webview(&label)
-        .ok_or_else(|| format!("Webview not found: {label}"))?;
-    webview
-        .eval("window.location.reload()")
-        .map_err(|e: tauri::Error| e.to_string())
+/// What changed in a `servers://changed` event.
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ServerChange {
+    Added { id: ServerId },
+    Updated { id: ServerId },
+    Removed { id: ServerId },
 }
 
-#[tauri::command]
-/// Delete the on-disk data directory for a server that has been removed.
-/// Called by the frontend after destroying the webview so all cached cookies,
-/// IndexedDB, localStorage, and WebView2 profile data are fully wiped.
-///
-/// On Windows, WebView2 holds an exclusive lock on its data directory until
-/// the browser process fully exits. We retry deletion for up to ~3 seconds
-/// to give the process time to release the lock after wv.close() returns.
-async fn delete_server_data(app: AppHandle, server_id: String) -> Result<(), String> {
-    let data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e: tauri::Error| e.to_string())?
-        .join("servers")
-        .join(&server_id);
+/// Payload of `servers://changed`: the change plus the full list afterwards,
+/// so listeners can simply replace their copy.
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServersChangedEvent {
+    pub change: ServerChange,
+    pub servers: Vec<Server>,
+}
 
-    if !data_dir.exists() {
-        return Ok(()); // already gone
-    }
+/// Apply `edit` to a copy of the registry, persist the copy, and only then
+/// swap it in and tell the frontend. A failed edit or save leaves the live
+/// registry as it was, so it never describes servers config.json doesn't.
+/// The lock is held throughout so saves and events keep the order changes
+/// were made in.
+fn commit_server_change<T>(
+    app: &AppHandle,
+    registry: &SharedServerRegistry,
+    change: ServerChange,
+    edit: impl FnOnce(&mut ServerRegistry) -> Result<T, String>,
+) -> Result<T, String> {
+    let mut locked = registry.lock().unwrap();
+    let mut next = locked.clone();
+    let value = edit(&mut next)?;
+    next.save(app)?;
+    *locked = next;
+    let _ = app.emit(
+        "servers://changed",
+        ServersChangedEvent { change, servers: locked.list().to_vec() },
+    );
+    drop(locked);
+    refresh_tray_menu(app);
+    Ok(value)
+}
 
-    // Retry loop: WebView2 on Windows keeps files locked for a short time after
-    // the webview is closed. Retry every 200 ms for up to 3 seconds.
-    let max_attempts = 15u32;
-    let mut last_err = String::new();
-    for attempt in 0..max_attempts {
-        match std::fs::remove_dir_all(&data_dir) {
-            Ok(_) => return Ok(()),
-            Err(e) => {
-                last_err = e.to_string();
-                // Only sleep between retries, not after the last attempt
-                if attempt + 1 < max_attempts {
-                    std::thread::sleep(std::time::Duration::from_millis(200));
-                }
-            }
+/// If `server` carries an inline data-URL icon, move it to the icon file.
+/// On failure the inline icon is kept and the error logged. Returns whether
+/// the entry changed.
+fn cache_inline_icon(app: &AppHandle, server: &mut Server) -> bool {
+    let Some(data_url) = server.icon_url.as_deref().filter(|u| u.starts_with("data:")) else { return false };
+    match store_server_icon(app, &server.id, data_url) {
+        Ok(()) => {
+            server.icon_url = None;
+            server.icon_rev = Some(icon_revision());
+            true
+        }
+        Err(e) => {
+            log::warn!("Failed to cache icon for {}: {e}", server.id);
+            false
         }
     }
+}
 
-    Err(format!("Failed to delete server data for {server_id} after {max_attempts} attempts: {last_err}"))
+/// Server URLs are loaded straight into a webview; only web origins make sense.
+fn validate_server_url(raw: &str) -> Result<url::Url, String> {
+    let url = url::Url::parse(raw).map_err(|e| format!("Invalid server URL: {e}"))?;
+    match url.scheme() {
+        "http" | "https" => Ok(url),
+        other => Err(format!("Unsupported server URL scheme: {other}")),
+    }
 }
 
 #[tauri::command]
-fn greet(name: &str) -> String {
-    format!("Hello, {}! Welcome to Sweetshark Client.", name)
+fn list_servers(registry: tauri::State<'_, SharedServerRegistry>) -> Vec<Server> {
+    registry.lock().unwrap().list().to_vec()
 }
 
-/// Returns the current app version string from the compiled-in Cargo.toml version.
+/// Register a new server under a freshly generated id and return it.
 #[tauri::command]
-fn get_app_version() -> String {
-    env!("CARGO_PKG_VERSION").to_string()
+async fn add_server(
+    app: AppHandle,
+    registry: tauri::State<'_, SharedServerRegistry>,
+    server: NewServer,
+) -> Result<Server, String> {
+    validate_server_url(&server.url)?;
+    let mut server = Server {
+        id: ServerId::parse(&uuid::Uuid::new_v4().to_string())?,
+        name: server.name,
+        url: server.url,
+        icon_letter: server.icon_letter,
+        icon_color: server.icon_color,
+        icon_url: server.icon_url,
+        icon_rev: None,
+        keep_loaded: None,
+        cert_sha256: None,
+        proxy: None,
+        in_app_domains: Vec::new(),
+        notifications_muted: false,
+    };
+    // The icon is cosmetic — if it can't be cached the server is still
+    // added, keeping it inline. Decoding runs on the blocking pool; the id is
+    // new, so nothing else can touch its icon file meanwhile.
+    let icon_app = app.clone();
+    let server = tauri::async_runtime::spawn_blocking(move || {
+        cache_inline_icon(&icon_app, &mut server);
+        server
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    commit_server_change(&app, &registry, ServerChange::Added { id: server.id.clone() }, |next| {
+        next.add(server.clone())
+    })?;
+    Ok(server)
 }
 
-/// Checks the GitHub releases API for the latest release tag on the
-/// Sweets-omg/Sweetshark-Client-v2 repo and returns it as a string,
-/// or an empty string if the check fails (network unavailable, etc.).
-/// Tags are expected in the format "V.X.X.X" (e.g. "V.2.0.1").
 #[tauri::command]
-async fn check_for_update() -> String {
-    let client = match reqwest::Client::builder()
-        .user_agent("Sweetshark-Client-v2-updater")
-        .timeout(std::time::Duration::from_secs(8))
-        .build()
-    {
-        Ok(c) => c,
-        Err(_) => return String::new(),
+async fn update_server(
+    app: AppHandle,
+    registry: tauri::State<'_, SharedServerRegistry>,
+    server: Server,
+) -> Result<(), String> {
+    validate_server_url(&server.url)?;
+    let mut server = server;
+    let id = server.id.clone();
+    // A new icon is decoded before the registry is locked.
+    let new_icon = match server.icon_url.clone().filter(|u| u.starts_with("data:")) {
+        Some(data_url) => Some(
+            tauri::async_runtime::spawn_blocking(move || decode_icon_data_url(&data_url))
+                .await
+                .map_err(|e| e.to_string())??,
+        ),
+        None => None,
     };
+    commit_server_change(&app, &registry, ServerChange::Updated { id: id.clone() }, |next| {
+        let stored = next.get(&id).ok_or_else(|| format!("Unknown server {id}"))?;
+        let stored_rev = stored.icon_rev;
+        // Pins, proxies, sign-in domains and notification mutes have their
+        // own commands.
+        server.cert_sha256 = stored.cert_sha256.clone();
+        server.proxy = stored.proxy.clone();
+        server.in_app_domains = stored.in_app_domains.clone();
+        server.notifications_muted = stored.notifications_muted;
 
-    let res = client
-        .get("https://api.github.com/repos/Sweets-omg/Sweetshark-Client-v2/releases/latest")
-        .send()
-        .await;
-
-    let body = match res {
-        Ok(r) => match r.json::<serde_json::Value>().await {
-            Ok(j) => j,
-            Err(_) => return String::new(),
-        },
-        Err(_) => return String::new(),
-    };
+        // Icon changes: a data URL replaces the cached icon; clearing
+        // `iconRev` removes it; otherwise the stored revision stands,
+        // whatever was sent.
+        if let Some(png) = &new_icon {
+            write_server_icon(&app, &id, png)?;
+            server.icon_url = None;
+            server.icon_rev = Some(icon_revision());
+        } else if server.icon_rev.is_none() {
+            if stored_rev.is_some() {
+                remove_server_icon(&app, &id);
+            }
+        } else {
+            server.icon_rev = stored_rev;
+        }
 
-    // GitHub returns { "tag_name": "V.2.0.1", ... }
-    body["tag_name"]
-        .as_str()
-        .unwrap_or("")
-        .to_string()
+        next.update(server)
+    })
 }
 
+/// Forget a server, and any proxy password stored for it. Its webview and
+/// data directory are torn down separately (`delete_server_data`).
 #[tauri::command]
-async fn open_url(url: String) -> Result<(), String> {
-    #[cfg(target_os = "windows")]
-    {
-        std::process::Command::new("cmd")
-            .args(["/c", "start", "", &url])
-            .spawn()
-            .map_err(|e| e.to_string())?;
-    }
-    #[cfg(target_os = "macos")]
-    {
-        std::process::Command::new("open")
-            .arg(&url)
-            .spawn()
-            .map_err(|e| e.to_string())?;
-    }
-    #[cfg(target_os = "linux")]
-    {
-        std::process::Command::new("xdg-open")
-            .arg(&url)
-            .spawn()
-            .map_err(|e| e.to_string())?;
+fn remove_server(
+    app: AppHandle,
+    registry: tauri::State<'_, SharedServerRegistry>,
+    server_id: ServerId,
+) -> Result<(), String> {
+    let removed = commit_server_change(&app, &registry, ServerChange::Removed { id: server_id.clone() }, |next| {
+        next.remove(&server_id)
+    })?;
+    update_badges(&app, |state| state.remove(&server_id));
+    // Its own push-to-talk binding goes with it. Stopping the worker may
+    // unmute the OS mic, so it runs off the main thread.
+    let ptt_app = app.clone();
+    let ptt_scope = server_id.to_string();
+    std::thread::spawn(move || remove_ptt_scope(&ptt_app, &ptt_scope));
+    if removed.proxy.is_some_and(|p| p.username.is_some()) {
+        std::thread::spawn(move || {
+            if let Err(e) = store_proxy_password(&app, &server_id, None) {
+                log::warn!("{e}");
+            }
+        });
     }
     Ok(())
 }
 
-
-// ── Push-to-talk ─────────────────────────────────────────────────────────────
-//
-// Uses GetAsyncKeyState polling on a dedicated thread so that PTT works
-// regardless of which window has focus — including when the Tauri/WebView2
-// window itself is focused.
+// ── Server metadata ──────────────────────────────────────────────────────────
 //
-// WH_KEYBOARD_LL was tried first but WebView2 consumes keyboard events before
-// they reach a low-level hook's message pump when the webview has focus.
-// GetAsyncKeyState reads key state directly from the keyboard driver and is
-// completely unaffected by focus or message routing.
-//
-// Mouse buttons (right, middle, XButton1/2) also have real Win32 VK codes
-// that GetAsyncKeyState understands, so we use one unified approach for
-// everything — no hooks, no message loop, no unsafe pointer juggling.
-//
-// Poll interval: 5 ms  (~200 Hz) — imperceptible latency for PTT.
+// Name and icon for a server, read from its landing page and web app
+// manifest so adding a server needs no typing. Icons are normalised to a
+// small PNG and cached at servers/<id>/icon.png; config.json only records
+// that one exists (`iconRev`), never the image itself.
 
-#[derive(Clone, serde::Serialize, serde::Deserialize, Default)]
-pub struct PttConfig {
-    pub enabled: bool,
-    /// Normalised key tokens sent from the frontend, e.g. ["alt", "mouse4"]
-    pub keys: Vec<String>,
+const SERVER_ICON_FILE: &str = "icon.png";
+/// Edge length icons are scaled down to; the sidebar shows them at 48 px.
+const SERVER_ICON_SIZE: u32 = 128;
+const ICON_DOWNLOAD_LIMIT: usize = 2 * 1024 * 1024;
+const PAGE_DOWNLOAD_LIMIT: usize = 256 * 1024;
+
+#[derive(Clone, Debug, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerMetadata {
+    pub name: Option<String>,
+    /// The normalised icon as a PNG data URL, for previewing. Handed back to
+    /// `add_server` as `iconUrl`, which moves it to disk.
+    pub icon_url: Option<String>,
 }
 
-pub struct PttState {
-    pub config: PttConfig,
-    /// Send () here to stop the current poll thread.
-    pub stop_tx: Option<std::sync::mpsc::Sender<()>>,
+/// Decode an image in any supported format, scale it down to fit
+/// `SERVER_ICON_SIZE` (cropping to a square), and re-encode it as PNG.
+fn normalize_icon(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let mut img = image::load_from_memory(bytes).map_err(|e| format!("Unreadable icon: {e}"))?;
+    if img.width() > SERVER_ICON_SIZE || img.height() > SERVER_ICON_SIZE {
+        img = img.resize_to_fill(SERVER_ICON_SIZE, SERVER_ICON_SIZE, image::imageops::FilterType::Lanczos3);
+    }
+    let mut png = Vec::new();
+    img.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode icon: {e}"))?;
+    Ok(png)
 }
 
-type SharedPttState = Arc<Mutex<PttState>>;
+fn png_data_url(png: &[u8]) -> String {
+    use base64::Engine;
+    format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(png))
+}
 
-#[tauri::command]
-fn get_ptt_config(state: tauri::State<SharedPttState>) -> PttConfig {
-    state.lock().unwrap().config.clone()
+/// The payload of a base64 `data:` URL.
+fn decode_data_url(url: &str) -> Result<Vec<u8>, String> {
+    use base64::Engine;
+    let (meta, data) = url
+        .strip_prefix("data:")
+        .and_then(|rest| rest.split_once(','))
+        .ok_or("Not a data URL")?;
+    if !meta.ends_with(";base64") {
+        return Err("Icon data URL is not base64".into());
+    }
+    base64::engine::general_purpose::STANDARD
+        .decode(data.trim())
+        .map_err(|e| format!("Invalid icon data: {e}"))
 }
 
-/// Convert a frontend key token to a Win32 virtual-key code.
-///
-/// Accepts browser KeyboardEvent.code style ("ShiftLeft", "AltLeft"),
-/// common short names ("shift", "alt", "mouse4"), and single characters.
-/// Case-insensitive.
-///
-/// Mouse buttons map to their real Win32 VK codes:
-///   VK_RBUTTON  = 0x02  (right)
-///   VK_MBUTTON  = 0x04  (middle / mouse3)
-///   VK_XBUTTON1 = 0x05  (mouse4 / side-back)
-///   VK_XBUTTON2 = 0x06  (mouse5 / side-forward)
-#[cfg(windows)]
-fn token_to_vk(token: &str) -> Option<u32> {
-    use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
-    let t = token.to_ascii_lowercase();
-    let t = t.trim();
-    Some(match t {
-        // ── Ctrl ──────────────────────────────────────────────────────────────
-        "ctrl" | "control" | "lctrl" | "lcontrol" | "controlleft" => VK_LCONTROL as u32,
-        "rctrl" | "rcontrol" | "controlright"                      => VK_RCONTROL as u32,
-        // ── Shift ─────────────────────────────────────────────────────────────
-        "shift" | "lshift" | "shiftleft"                           => VK_LSHIFT as u32,
-        "rshift" | "shiftright"                                    => VK_RSHIFT as u32,
-        // ── Alt ───────────────────────────────────────────────────────────────
-        "alt" | "lalt" | "lmenu" | "altleft" | "menu"             => VK_LMENU as u32,
-        "ralt" | "rmenu" | "altright" | "altgr"                    => VK_RMENU as u32,
-        // ── Win / Super ───────────────────────────────────────────────────────
-        "super" | "meta" | "win" | "metaleft" | "osleft"           => VK_LWIN as u32,
-        "metaright" | "osright"                                    => VK_RWIN as u32,
-        // ── Common keys ───────────────────────────────────────────────────────
-        "space"                                                    => VK_SPACE as u32,
-        "enter" | "return" | "numpadenter"                         => VK_RETURN as u32,
-        "escape" | "esc"                                           => VK_ESCAPE as u32,
-        "backspace"                                                => VK_BACK as u32,
-        "tab"                                                      => VK_TAB as u32,
-        "delete" | "del"                                           => VK_DELETE as u32,
-        "insert" | "ins"                                           => VK_INSERT as u32,
-        "home"                                                     => VK_HOME as u32,
-        "end"                                                      => VK_END as u32,
-        "pageup"                                                   => VK_PRIOR as u32,
-        "pagedown"                                                 => VK_NEXT as u32,
-        "arrowup"    | "up"                                        => VK_UP as u32,
-        "arrowdown"  | "down"                                      => VK_DOWN as u32,
-        "arrowleft"  | "left"                                      => VK_LEFT as u32,
-        "arrowright" | "right"                                     => VK_RIGHT as u32,
-        "capslock"                                                 => VK_CAPITAL as u32,
-        // ── Function keys ─────────────────────────────────────────────────────
-        "f1"  => VK_F1  as u32, "f2"  => VK_F2  as u32,
-        "f3"  => VK_F3  as u32, "f4"  => VK_F4  as u32,
-        "f5"  => VK_F5  as u32, "f6"  => VK_F6  as u32,
-        "f7"  => VK_F7  as u32, "f8"  => VK_F8  as u32,
-        "f9"  => VK_F9  as u32, "f10" => VK_F10 as u32,
-        "f11" => VK_F11 as u32, "f12" => VK_F12 as u32,
-        // ── Misc ──────────────────────────────────────────────────────────────
-        "print" | "printscreen"                                    => VK_SNAPSHOT as u32,
-        "scrolllock"                                               => VK_SCROLL as u32,
-        "pause"                                                    => VK_PAUSE as u32,
-        "numlock"                                                  => VK_NUMLOCK as u32,
-        // ── Mouse buttons (real Win32 VK codes) ───────────────────────────────
-        "mouse2" | "mouseright"  | "rightbutton"                   => VK_RBUTTON  as u32,
-        "mouse3" | "mousemiddle" | "middlebutton"                  => VK_MBUTTON  as u32,
-        "mouse4" | "xbutton1"                                      => VK_XBUTTON1 as u32,
-        "mouse5" | "xbutton2"                                      => VK_XBUTTON2 as u32,
-        // ── Single-character fallback via VkKeyScanW ──────────────────────────
-        other => {
-            let chars: Vec<char> = other.chars().collect();
-            if chars.len() == 1 {
-                let scan = unsafe {
-                    windows_sys::Win32::UI::Input::KeyboardAndMouse::VkKeyScanW(chars[0] as u16)
-                };
-                if scan != -1i16 {
-                    (scan & 0xFF) as u32
-                } else {
-                    return None;
-                }
-            } else {
-                return None;
-            }
+/// Attribute text of every `<tag …>` in `html`, case-insensitively. Not a
+/// full parser — enough for the head of a web app's landing page.
+fn html_tags<'a>(html: &'a str, tag: &str) -> Vec<&'a str> {
+    let lower = html.to_ascii_lowercase();
+    let open = format!("<{tag}");
+    let mut tags = Vec::new();
+    let mut from = 0;
+    while let Some(start) = lower[from..].find(&open).map(|i| from + i + open.len()) {
+        // `<linkfoo` is a different tag.
+        if !lower[start..].starts_with(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/') {
+            from = start;
+            continue;
         }
-    })
+        let Some(end) = lower[start..].find('>').map(|i| start + i) else { break };
+        tags.push(&html[start..end]);
+        from = end;
+    }
+    tags
 }
 
-#[cfg(not(windows))]
-fn token_to_vk(_token: &str) -> Option<u32> { None }
-
-/// Spawn a dedicated poll thread that watches ONLY the exact VK codes in
-/// `vk_set` — nothing else is ever read.
-///
-/// Privacy guarantee:
-///   • This thread is started ONLY when PTT is enabled by the user.
-///   • It calls GetAsyncKeyState for the assigned key(s) only — no other
-///     keys, no text, no clipboard, no window titles.
-///   • Sending () on the returned channel stops the thread immediately.
-///     The thread is also the only thing that owns the VK list; when it
-///     exits the list is dropped and no key state is ever read again.
-///   • No data leaves the process: the only output is a boolean
-///     "ptt://pressed" / "ptt://released" Tauri event.
-/// Mute or unmute the default Windows microphone at the OS/driver level using
-/// the Core Audio API (IAudioEndpointVolume). This is reliable regardless of
-/// how the web app (mediasoup/WebRTC) manages its audio pipeline internally.
-#[cfg(windows)]
-fn ptt_set_mic_mute(muted: bool) {
-    use windows::{
-        Win32::Media::Audio::{eCapture, eConsole, IMMDeviceEnumerator, MMDeviceEnumerator, Endpoints::IAudioEndpointVolume},
-        Win32::System::Com::{CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_ALL, COINIT_APARTMENTTHREADED},
-        Win32::Foundation::BOOL,
-    };
+/// Value of attribute `name` in a tag's attribute text.
+fn html_attr(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(i) = lower[from..].find(name).map(|i| from + i) {
+        from = i + name.len();
+        let preceded = i == 0 || lower.as_bytes()[i - 1].is_ascii_whitespace();
+        let rest = lower[from..].trim_start();
+        if !preceded || !rest.starts_with('=') {
+            continue;
+        }
+        let value_start = tag.len() - rest.len() + 1;
+        let value = tag[value_start..].trim_start();
+        let value = match value.chars().next() {
+            Some(q @ ('"' | '\'')) => value[1..].split(q).next().unwrap_or_default(),
+            // Unquoted values may contain `/` (`href=/icon.png`); only one
+            // right before the `>` closes the tag instead.
+            _ => match value.find(|c: char| c.is_ascii_whitespace()) {
+                Some(end) => &value[..end],
+                None => value.strip_suffix('/').unwrap_or(value),
+            },
+        };
+        return Some(html_unescape(value));
+    }
+    None
+}
 
-    unsafe {
-        let hr_init = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
-        let com_inited = hr_init.is_ok() || hr_init.0 == 1; // S_OK or S_FALSE
+fn html_unescape(s: &str) -> String {
+    s.replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
 
-        let enumerator: Result<IMMDeviceEnumerator, _> =
-            CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL);
-        let Ok(enumerator) = enumerator else {
-            if com_inited { CoUninitialize(); }
-            return;
-        };
+fn html_title(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let start = lower.find("<title")?;
+    let start = start + lower[start..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    Some(html_unescape(html[start..end].trim())).filter(|t| !t.is_empty())
+}
 
-        let Ok(device) = enumerator.GetDefaultAudioEndpoint(eCapture, eConsole) else {
-            if com_inited { CoUninitialize(); }
-            return;
-        };
+/// `<meta name|property=… content=…>` lookup.
+fn html_meta(html: &str, key: &str) -> Option<String> {
+    html_tags(html, "meta").into_iter().find_map(|tag| {
+        let named = html_attr(tag, "name").or_else(|| html_attr(tag, "property"))?;
+        named.eq_ignore_ascii_case(key).then(|| html_attr(tag, "content")).flatten()
+    })
+}
 
-        let Ok(vol) = device.Activate::<IAudioEndpointVolume>(CLSCTX_ALL, None) else {
-            if com_inited { CoUninitialize(); }
-            return;
-        };
+/// Largest edge from a `sizes` attribute ("32x32 192x192", "any").
+fn icon_size_hint(sizes: Option<&str>) -> u32 {
+    sizes
+        .unwrap_or_default()
+        .split_ascii_whitespace()
+        .map(|s| if s.eq_ignore_ascii_case("any") { u32::MAX } else {
+            s.split(['x', 'X']).next().and_then(|n| n.parse().ok()).unwrap_or(0)
+        })
+        .max()
+        .unwrap_or(0)
+}
 
-        let mute_val = BOOL::from(muted);
-        let _ = vol.SetMute(mute_val, std::ptr::null());
+/// Icon URLs a page advertises, best first: the manifest's icons, then
+/// `<link rel=icon|apple-touch-icon>`, then /favicon.ico. SVG is skipped —
+/// it can't be rasterised here.
+fn icon_candidates(
+    page_url: &url::Url,
+    html: &str,
+    manifest: Option<(&url::Url, &serde_json::Value)>,
+) -> Vec<url::Url> {
+    let mut found: Vec<(u32, url::Url)> = Vec::new();
+    let mut push = |base: &url::Url, href: &str, sizes: Option<&str>, mime: Option<&str>| {
+        let is_svg = mime.is_some_and(|m| m.contains("svg")) || href.to_ascii_lowercase().ends_with(".svg");
+        if let (false, Ok(url)) = (is_svg, base.join(href)) {
+            if matches!(url.scheme(), "http" | "https" | "data") {
+                found.push((icon_size_hint(sizes), url));
+            }
+        }
+    };
 
-        if com_inited { CoUninitialize(); }
+    if let Some((manifest_url, manifest)) = manifest {
+        for icon in manifest["icons"].as_array().into_iter().flatten() {
+            if let Some(src) = icon["src"].as_str() {
+                push(manifest_url, src, icon["sizes"].as_str(), icon["type"].as_str());
+            }
+        }
+    }
+    for tag in html_tags(html, "link") {
+        let rel = html_attr(tag, "rel").unwrap_or_default().to_ascii_lowercase();
+        if rel.split_ascii_whitespace().any(|r| r == "icon" || r == "apple-touch-icon") {
+            if let Some(href) = html_attr(tag, "href") {
+                push(page_url, &href, html_attr(tag, "sizes").as_deref(), html_attr(tag, "type").as_deref());
+            }
+        }
     }
+    // Stable sort: among equal sizes the manifest wins.
+    found.sort_by_key(|(size, _)| std::cmp::Reverse(*size));
+    let mut urls: Vec<url::Url> = found.into_iter().map(|(_, u)| u).collect();
+    if let Ok(favicon) = page_url.join("/favicon.ico") {
+        urls.push(favicon);
+    }
+    urls.dedup();
+    urls
 }
 
-#[cfg(not(windows))]
-fn ptt_set_mic_mute(_muted: bool) {}
+async fn fetch_icon(client: &reqwest::Client, url: &url::Url) -> Result<Vec<u8>, String> {
+    if url.scheme() == "data" {
+        return normalize_icon(&decode_data_url(url.as_str())?);
+    }
+    let response = client
+        .get(url.clone())
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| e.to_string())?;
+    let (bytes, truncated) = read_body_limited(response, ICON_DOWNLOAD_LIMIT).await.map_err(|e| e.to_string())?;
+    if truncated {
+        return Err(format!("Icon larger than {} MB", ICON_DOWNLOAD_LIMIT / (1024 * 1024)));
+    }
+    normalize_icon(&bytes)
+}
 
-#[cfg(windows)]
-fn start_ptt_hook(
+/// Read a server's display name and icon from its landing page and web app
+/// manifest. Missing pieces come back as None; only an unreachable page is
+/// an error. With `server_id`, that server's pinned certificate is accepted;
+/// without one (a server still being added) only publicly trusted
+/// certificates are, and the Add Server dialog explains the failure.
+#[tauri::command]
+async fn fetch_server_metadata(
     app: AppHandle,
-    vk_set: Vec<u32>,
-) -> std::sync::mpsc::Sender<()> {
-    use std::sync::mpsc;
+    url: String,
+    server_id: Option<ServerId>,
+) -> Result<ServerMetadata, String> {
+    let page_url = validate_server_url(&url)?;
+    let connection = server_connection_async(&app, server_id).await?;
+    let client = server_http_client(&connection).map_err(|e| e.to_string())?;
 
-    let (stop_tx, stop_rx) = mpsc::channel::<()>();
+    let response = client
+        .get(page_url.clone())
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to load {page_url}: {e}"))?;
+    // Relative links resolve against where redirects ended up.
+    let page_url = response.url().clone();
+    let (body, _) = read_body_limited(response, PAGE_DOWNLOAD_LIMIT).await.map_err(|e| e.to_string())?;
+    let html = String::from_utf8_lossy(&body);
 
-    // Move the VK list into the thread — nothing outside the thread can
-    // access it once we hand it over.
-    let vks: Vec<i32> = vk_set.into_iter().map(|v| v as i32).collect();
+    let manifest_url = html_tags(&html, "link")
+        .into_iter()
+        .find(|tag| html_attr(tag, "rel").is_some_and(|r| r.eq_ignore_ascii_case("manifest")))
+        .and_then(|tag| html_attr(tag, "href"))
+        .and_then(|href| page_url.join(&href).ok());
+    let manifest = match &manifest_url {
+        Some(manifest_url) => match client.get(manifest_url.clone()).send().await.and_then(|r| r.error_for_status()) {
+            Ok(response) => read_body_limited(response, PAGE_DOWNLOAD_LIMIT)
+                .await
+                .ok()
+                .and_then(|(body, _)| serde_json::from_slice::<serde_json::Value>(&body).ok()),
+            Err(_) => None,
+        },
+        None => None,
+    };
 
-    std::thread::Builder::new()
-        // Named so it's identifiable in debuggers / task managers.
-        .name("sweetshark-ptt-poll".into())
-        .spawn(move || {
-            let mut was_active = false;
+    let name = manifest
+        .as_ref()
+        .and_then(|m| m["name"].as_str().or_else(|| m["short_name"].as_str()))
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .or_else(|| html_meta(&html, "application-name"))
+        .or_else(|| html_meta(&html, "og:site_name"))
+        .or_else(|| html_title(&html));
 
-            loop {
-                // Exit as soon as the stop signal arrives.
-                if stop_rx.try_recv().is_ok() {
-                    if was_active {
-                        // Re-mute on disable so audio doesn't stay open.
-                        ptt_set_mic_mute(true);
-                        let _ = app.emit("ptt://released", ());
-                    }
-                    // vks is dropped here — no key state is read after this point.
-                    break;
-                }
+    let mut icon_url = None;
+    let manifest_ref = manifest_url.as_ref().zip(manifest.as_ref());
+    for candidate in icon_candidates(&page_url, &html, manifest_ref) {
+        match fetch_icon(&client, &candidate).await {
+            Ok(png) => {
+                icon_url = Some(png_data_url(&png));
+                break;
+            }
+            Err(e) => log::debug!("Skipping icon {candidate}: {e}"),
+        }
+    }
 
-                // GetAsyncKeyState reads raw hardware key state.
-                // We call it ONLY for the keys in vks — the user-assigned PTT
-                // keys — and we do nothing with the result except check whether
-                // ALL of them are simultaneously held (bit 15 set = down).
-                // No keystroke data is stored, logged, or transmitted.
-                let all_down = vks.iter().all(|&vk| {
-                    (unsafe {
-                        windows_sys::Win32::UI::Input::KeyboardAndMouse::GetAsyncKeyState(vk)
-                    } as u16) & 0x8000 != 0
-                });
-
-                if all_down && !was_active {
-                    was_active = true;
-                    // Unmute: PTT key pressed → allow microphone audio through.
-                    ptt_set_mic_mute(false);
-                    let _ = app.emit("ptt://pressed", ());
-                } else if !all_down && was_active {
-                    was_active = false;
-                    // Mute: PTT key released → silence microphone.
-                    ptt_set_mic_mute(true);
-                    let _ = app.emit("ptt://released", ());
-                }
+    Ok(ServerMetadata { name, icon_url })
+}
 
-                std::thread::sleep(std::time::Duration::from_millis(5));
-            }
-        })
-        .expect("failed to spawn PTT poll thread");
+/// Decode a data-URL icon and normalise it. Slow for large images.
+fn decode_icon_data_url(data_url: &str) -> Result<Vec<u8>, String> {
+    normalize_icon(&decode_data_url(data_url)?)
+}
 
-    stop_tx
+/// Normalise a data-URL icon and cache it as the server's icon file.
+fn store_server_icon(app: &AppHandle, id: &ServerId, data_url: &str) -> Result<(), String> {
+    write_server_icon(app, id, &decode_icon_data_url(data_url)?)
 }
 
-#[cfg(not(windows))]
-fn start_ptt_hook(
-    _app: AppHandle,
-    _vk_set: Vec<u32>,
-) -> std::sync::mpsc::Sender<()> {
-    let (tx, _rx) = std::sync::mpsc::channel();
-    tx
+/// Cache an already normalised icon as the server's icon file.
+fn write_server_icon(app: &AppHandle, id: &ServerId, png: &[u8]) -> Result<(), String> {
+    let dir = server_data_dir(app, id)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create data dir: {e}"))?;
+    std::fs::write(dir.join(SERVER_ICON_FILE), png).map_err(|e| format!("Failed to save icon: {e}"))
 }
 
-/// Enable or disable PTT.
-///
-/// When `enabled` is false (or `keys` is empty) any running poll thread is
-/// stopped immediately and no key state is read until the user explicitly
-/// re-enables PTT. This is the sole entry-point that controls whether the
-/// poll thread exists at all.
-#[tauri::command]
-async fn set_ptt_config(
-    app: AppHandle,
-    state: tauri::State<'_, SharedPttState>,
-    keys: Vec<String>,
-    enabled: bool,
-) -> Result<(), String> {
-    // Always stop the existing thread first — whether we're disabling, changing
-    // keys, or re-enabling. This guarantees only one poll thread ever exists.
-    {
-        let mut locked = state.lock().unwrap();
-        if let Some(tx) = locked.stop_tx.take() {
-            let _ = tx.send(());
-            // stop_tx is now None — thread will exit on its next iteration.
+fn remove_server_icon(app: &AppHandle, id: &ServerId) {
+    if let Ok(dir) = server_data_dir(app, id) {
+        match std::fs::remove_file(dir.join(SERVER_ICON_FILE)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => log::warn!("Failed to remove icon for {id}: {e}"),
+            _ => {}
         }
-        locked.config.keys    = keys.clone();
-        locked.config.enabled = enabled;
     }
+}
 
-    // Only start a new thread when PTT is explicitly enabled with a valid keybind.
-    // If disabled or no keys are set, nothing runs — no key state is ever polled.
-    if enabled && !keys.is_empty() {
-        let vk_set: Vec<u32> = keys.iter()
-            .filter_map(|k| token_to_vk(k))
-            .collect();
+/// A new `iconRev`: changes whenever the cached icon does, so the frontend
+/// can use it to bust the webview's image cache.
+fn icon_revision() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
 
-        if vk_set.is_empty() {
-            return Err(format!("No recognisable keys in: {:?}", keys));
-        }
+/// Serves cached icons at `server-icon://localhost/<server id>` (on Windows,
+/// `http://server-icon.localhost/<server id>`).
+fn server_icon_protocol(app: &AppHandle, request: &tauri::http::Request<Vec<u8>>) -> tauri::http::Response<Vec<u8>> {
+    let response = |status: u16, body: Vec<u8>| {
+        tauri::http::Response::builder()
+            .status(status)
+            .header("Content-Type", "image/png")
+            .header("Cache-Control", "no-cache")
+            .body(body)
+            .unwrap()
+    };
+    let Ok(id) = ServerId::parse(request.uri().path().trim_start_matches('/')) else {
+        return response(400, Vec::new());
+    };
+    match server_data_dir(app, &id).and_then(|dir| std::fs::read(dir.join(SERVER_ICON_FILE)).map_err(|e| e.to_string())) {
+        Ok(png) => response(200, png),
+        Err(_) => response(404, Vec::new()),
+    }
+}
 
-        // PTT starting: webviews begin in muted state (the init script already
-        // does this, but ensure any already-running webviews are muted too).
-        ptt_set_mic_mute(true);
+// ── Storage ──────────────────────────────────────────────────────────────────
+//
+// Disk usage per server data dir, and cleanup of dirs no registry entry owns
+// any more (a crash mid-removal, or a delete that exhausted its retries).
+// Only directories named by a valid `ServerId` are ever considered; anything
+// else under servers/ is left alone.
 
-        let stop_tx = start_ptt_hook(app, vk_set);
-        state.lock().unwrap().stop_tx = Some(stop_tx);
-    } else {
-        // PTT disabled: restore normal open-mic behaviour in all webviews.
-        ptt_set_mic_mute(false);
+/// Bytes used by one server data dir, split the way users think about it.
+#[derive(Clone, Debug, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageUsage {
+    /// HTTP, code, GPU and CacheStorage caches.
+    pub cache: u64,
+    pub indexed_db: u64,
+    pub local_storage: u64,
+    /// Cookies, session storage, service worker scripts, profile files, …
+    pub other: u64,
+    pub total: u64,
+}
+
+impl StorageUsage {
+    /// Attribute a file to a bucket from its path relative to the data dir.
+    /// Matches both engines' layouts: WebView2 (`EBWebView/Default/IndexedDB`,
+    /// `Local Storage`, `Cache`, `Code Cache`, …) and WebKitGTK (`indexeddb`,
+    /// `localstorage`, `WebKitCache`, and per-origin `storage/…/IndexedDB`).
+    fn add(&mut self, rel: &std::path::Path, bytes: u64) {
+        let parts: Vec<String> = rel
+            .components()
+            .map(|c| normalized_entry_name(c.as_os_str()))
+            .collect();
+        let has = |f: &dyn Fn(&str) -> bool| parts.iter().any(|p| f(p));
+        let bucket = if has(&|p| p == "indexeddb") {
+            &mut self.indexed_db
+        } else if has(&|p| p == "localstorage") {
+            &mut self.local_storage
+        } else if has(&|p| p.contains("cache")) {
+            &mut self.cache
+        } else {
+            &mut self.other
+        };
+        *bucket += bytes;
+        self.total += bytes;
     }
+}
 
-    Ok(())
+/// Lowercased with spaces removed, so `Local Storage` and `localstorage`
+/// (WebView2 and WebKitGTK spellings) compare equal.
+fn normalized_entry_name(name: &std::ffi::OsStr) -> String {
+    name.to_string_lossy().to_ascii_lowercase().replace(' ', "")
 }
 
-/// Returns true if the PTT poll thread is currently running.
-/// The frontend can expose this to users as proof that no key polling
-/// is happening when PTT is disabled.
-#[tauri::command]
-fn get_ptt_active(state: tauri::State<'_, SharedPttState>) -> bool {
-    state.lock().unwrap().stop_tx.is_some()
+/// Walk `dir` without following symlinks and total its files.
+fn measure_dir(dir: &std::path::Path) -> StorageUsage {
+    let mut usage = StorageUsage::default();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(current) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&current) else { continue };
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.path().symlink_metadata() else { continue };
+            if meta.is_dir() {
+                stack.push(entry.path());
+            } else if meta.is_file() {
+                let rel = entry.path().strip_prefix(dir).map(|p| p.to_path_buf()).unwrap_or_default();
+                usage.add(&rel, meta.len());
+            }
+        }
+    }
+    usage
 }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
+/// Server-id-named directories directly under the servers root. Symlinks are
+/// skipped — we never created any, so one there is not ours to touch.
+fn server_dirs_on_disk(root: &std::path::Path) -> Vec<ServerId> {
+    let Ok(entries) = std::fs::read_dir(root) else { return Vec::new() };
+    entries
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|e| ServerId::parse(&e.file_name().to_string_lossy()).ok())
+        .collect()
+}
+
+/// The canonical servers root, created if missing — Tauri never
+/// auto-creates directories, we must do it ourselves.
+fn servers_root(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    let root = app
+        .path()
+        .app_data_dir()
+        .map_err(|e: tauri::Error| e.to_string())?
+        .join("servers");
+    std::fs::create_dir_all(&root)
+        .map_err(|e| format!("Failed to create data dir: {e}"))?;
+    root.canonicalize()
+        .map_err(|e| format!("Failed to resolve data dir: {e}"))
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStorage {
+    pub server_id: ServerId,
+    /// Registry name; None when the directory is orphaned.
+    pub name: Option<String>,
+    pub usage: StorageUsage,
+}
+
+/// Report disk usage for every server data dir, largest first. Orphaned
+/// dirs are included with no name.
+#[tauri::command]
+async fn get_storage_usage(
+    app: AppHandle,
+    registry: tauri::State<'_, SharedServerRegistry>,
+) -> Result<Vec<ServerStorage>, String> {
+    let root = servers_root(&app)?;
+    let names: std::collections::HashMap<ServerId, String> = registry
+        .lock()
+        .unwrap()
+        .list()
+        .iter()
+        .map(|s| (s.id.clone(), s.name.clone()))
+        .collect();
+
+    let mut report = tauri::async_runtime::spawn_blocking(move || {
+        server_dirs_on_disk(&root)
+            .into_iter()
+            .map(|id| ServerStorage {
+                usage: measure_dir(&root.join(id.as_str())),
+                name: names.get(&id).cloned(),
+                server_id: id,
+            })
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    report.sort_by_key(|s| std::cmp::Reverse(s.usage.total));
+    Ok(report)
+}
+
+/// Ids of server dirs on disk that no registry entry owns. None at all when
+/// the registry is missing stored servers: their dirs would look orphaned.
+fn orphaned_server_ids(root: &std::path::Path, registry: &ServerRegistry) -> Vec<ServerId> {
+    if !registry.is_complete() {
+        log::warn!("Server list was not fully loaded; skipping the orphaned data sweep");
+        return Vec::new();
+    }
+    server_dirs_on_disk(root)
+        .into_iter()
+        .filter(|id| registry.get(id).is_none())
+        .collect()
+}
+
+/// Startup sweep: list orphaned server dirs with their sizes so the UI can
+/// offer to delete or quarantine them. Nothing is touched here, and nothing
+/// is listed unless the server list loaded cleanly.
+#[tauri::command]
+async fn find_orphaned_server_data(
+    app: AppHandle,
+    registry: tauri::State<'_, SharedServerRegistry>,
+) -> Result<Vec<ServerStorage>, String> {
+    let root = servers_root(&app)?;
+    let orphans = orphaned_server_ids(&root, &registry.lock().unwrap());
+    tauri::async_runtime::spawn_blocking(move || {
+        orphans
+            .into_iter()
+            .map(|id| ServerStorage { usage: measure_dir(&root.join(id.as_str())), name: None, server_id: id })
+            .collect()
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OrphanAction {
+    /// Remove the directory for good.
+    Delete,
+    /// Move it to `<app data>/quarantine/<id>-<unix time>` for manual
+    /// inspection or recovery.
+    Quarantine,
+}
+
+/// Delete or quarantine orphaned server dirs. Each id is re-checked against
+/// the registry first, so a server added since the sweep (or one the registry
+/// couldn't read) is never touched.
+#[tauri::command]
+async fn resolve_orphaned_server_data(
+    app: AppHandle,
+    registry: tauri::State<'_, SharedServerRegistry>,
+    server_ids: Vec<ServerId>,
+    action: OrphanAction,
+) -> Result<(), String> {
+    let root = servers_root(&app)?;
+    let orphans = orphaned_server_ids(&root, &registry.lock().unwrap());
+    let quarantine = app
+        .path()
+        .app_data_dir()
+        .map_err(|e: tauri::Error| e.to_string())?
+        .join("quarantine");
+
+    let mut failed = Vec::new();
+    for id in server_ids.iter().filter(|id| orphans.contains(id)) {
+        let dir = contained_server_dir(&root, id)?;
+        let result = match action {
+            // Same background path as removing a server: move into
+            // tombstones/, then remove, both with backoff.
+            OrphanAction::Delete => {
+                new_tombstone(&app, id).and_then(|t| spawn_deletion_job(&app, id.clone(), Some(dir), t))
+            }
+            OrphanAction::Quarantine => {
+                let stamp = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0);
+                std::fs::create_dir_all(&quarantine)
+                    .and_then(|_| std::fs::rename(&dir, quarantine.join(format!("{id}-{stamp}"))))
+                    .map_err(|e| e.to_string())
+            }
+        };
+        if let Err(e) = result {
+            failed.push(format!("{id}: {e}"));
+        }
+    }
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Failed to clean up server data: {}", failed.join("; ")))
+    }
+}
+
+/// What `clear_server_data` wipes, as exact paths in the engine's profile
+/// layout (`ProfileLayout`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ClearScope {
+    /// HTTP, code, GPU and CacheStorage caches. Nothing the user would notice
+    /// besides a slower first load.
+    Cache,
+    /// Cookies and session state; signs the user out.
+    Cookies,
+    /// IndexedDB, local storage, service workers and other per-site storage.
+    Storage,
+}
+
+/// Where an engine keeps each `ClearScope` under a server's data dir. Paths
+/// are relative and `/`-separated; a `*` component stands for any one
+/// directory name. Anything not listed is never touched.
+struct ProfileLayout {
+    cache: &'static [&'static str],
+    cookies: &'static [&'static str],
+    storage: &'static [&'static str],
+}
+
+/// WebView2 puts its user data folder's profile under `EBWebView/Default`;
+/// GPU shader caches sit one level up, shared by the folder's profiles.
+#[cfg(any(windows, test))]
+const WEBVIEW2_LAYOUT: ProfileLayout = ProfileLayout {
+    cache: &[
+        "EBWebView/Default/Cache",
+        "EBWebView/Default/Code Cache",
+        "EBWebView/Default/GPUCache",
+        "EBWebView/Default/DawnGraphiteCache",
+        "EBWebView/Default/DawnWebGPUCache",
+        "EBWebView/Default/Service Worker/CacheStorage",
+        "EBWebView/Default/Service Worker/ScriptCache",
+        "EBWebView/ShaderCache",
+        "EBWebView/GrShaderCache",
+        "EBWebView/GraphiteDawnCache",
+    ],
+    cookies: &[
+        "EBWebView/Default/Network/Cookies",
+        "EBWebView/Default/Network/Cookies-journal",
+        // Before the network service moved them under Network/.
+        "EBWebView/Default/Cookies",
+        "EBWebView/Default/Cookies-journal",
+        "EBWebView/Default/Session Storage",
+        "EBWebView/Default/Sessions",
+    ],
+    storage: &[
+        "EBWebView/Default/IndexedDB",
+        "EBWebView/Default/Local Storage",
+        "EBWebView/Default/databases",
+        "EBWebView/Default/File System",
+        "EBWebView/Default/Service Worker",
+        "EBWebView/Default/Shared Storage",
+        "EBWebView/Default/WebStorage",
+    ],
+};
+
+/// wry gives WebKitGTK the data dir as both its base data and base cache
+/// directory, and keeps cookies in a text file of its own. Per-origin storage
+/// lives under `storage/<top origin>/<origin>/`; session storage is never
+/// written to disk.
+#[cfg(any(target_os = "linux", test))]
+const WEBKITGTK_LAYOUT: ProfileLayout = ProfileLayout {
+    cache: &["WebKitCache", "CacheStorage", "applications", "storage/*/*/CacheStorage"],
+    cookies: &["cookies"],
+    // databases/ holds WebSQL and, under databases/indexeddb, IndexedDB.
+    storage: &["localstorage", "databases", "serviceworkers", "storage"],
+};
+
+/// The layout of this platform's engine, or None where site data doesn't live
+/// in the data dir (WKWebView keeps it in its own website data store).
+const ENGINE_LAYOUT: Option<&ProfileLayout> = {
+    #[cfg(windows)]
+    let layout = Some(&WEBVIEW2_LAYOUT);
+    #[cfg(target_os = "linux")]
+    let layout = Some(&WEBKITGTK_LAYOUT);
+    #[cfg(not(any(windows, target_os = "linux")))]
+    let layout = None;
+    layout
+};
+
+impl ClearScope {
+    fn paths(self, layout: &ProfileLayout) -> &'static [&'static str] {
+        match self {
+            Self::Cache => layout.cache,
+            Self::Cookies => layout.cookies,
+            Self::Storage => layout.storage,
+        }
+    }
+}
+
+/// Remove each of `scope`'s paths in `layout` under `dir`. Directories are
+/// removed whole; symlinks are removed themselves and never followed.
+fn clear_scope(dir: &std::path::Path, layout: &ProfileLayout, scope: ClearScope) -> std::io::Result<()> {
+    for path in scope.paths(layout) {
+        remove_layout_path(dir, &path.split('/').collect::<Vec<_>>())?;
+    }
+    Ok(())
+}
+
+fn remove_layout_path(dir: &std::path::Path, components: &[&str]) -> std::io::Result<()> {
+    let Some((first, rest)) = components.split_first() else { return Ok(()) };
+    let names = if *first == "*" {
+        match std::fs::read_dir(dir) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            entries => entries?.map(|e| e.map(|e| e.file_name())).collect::<std::io::Result<Vec<_>>>()?,
+        }
+    } else {
+        vec![std::ffi::OsString::from(first)]
+    };
+    for name in names {
+        let path = dir.join(name);
+        let meta = match path.symlink_metadata() {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            meta => meta?,
+        };
+        if !rest.is_empty() {
+            if meta.is_dir() {
+                remove_layout_path(&path, rest)?;
+            }
+        } else if meta.is_dir() {
+            std::fs::remove_dir_all(&path)?;
+        } else {
+            std::fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Wipe one kind of data for a server while keeping its registry entry —
+/// for fixing a broken session without re-adding the server. The server's
+/// webview must be closed first; the frontend tears it down and recreates
+/// it around this call.
+#[tauri::command]
+async fn clear_server_data(app: AppHandle, server_id: ServerId, scope: ClearScope) -> Result<(), String> {
+    if app.get_webview(&server_id.webview_label()).is_some() {
+        return Err(format!("Close the webview for {server_id} before clearing its data"));
+    }
+    let layout = ENGINE_LAYOUT.ok_or("Clearing site data is not supported on this platform")?;
+    let dir = server_data_dir(&app, &server_id)?;
+
+    // WebView2 releases its file locks shortly after the webview closes, so
+    // a few quick retries cover the usual race. Runs on a blocking thread —
+    // never sleep on an async worker.
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut delay = DELETE_INITIAL_BACKOFF;
+        let mut attempt = 1;
+        loop {
+            match clear_scope(&dir, layout, scope) {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt >= CLEAR_MAX_ATTEMPTS => {
+                    return Err(format!("Failed to clear data for {server_id}: {e}"));
+                }
+                Err(_) => {
+                    std::thread::sleep(delay);
+                    delay *= 2;
+                    attempt += 1;
+                }
+            }
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn create_server_webview(
+    app: AppHandle,
+    label: String,
+    url: String,
+    server_id: ServerId,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    mic_id: Option<String>,
+    cam_id: Option<String>,
+    speaker_id: Option<String>,
+    probe_first: Option<bool>,
+) -> Result<(), String> {
+    // The label is derived from the id everywhere else (PTT gating, cleanup),
+    // so a mismatched pair would leave a webview nothing can find again.
+    if label != server_id.webview_label() {
+        return Err(format!("Label {label:?} does not match server id {server_id}"));
+    }
+
+    // Optionally refuse to point a webview at a server that is down. A
+    // reachable server that doesn't look like Sharkord still loads — the
+    // detection is a heuristic and must not lock anyone out.
+    let connection = server_connection_async(&app, Some(server_id.clone())).await?;
+    if probe_first.unwrap_or(false) {
+        let report = probe(&validate_server_url(&url)?, &connection).await;
+        if !report.reachable() {
+            return Err(report.summary());
+        }
+    }
+
+    if let Some(existing) = app.get_webview(&label) {
+        existing.close().map_err(|e: tauri::Error| e.to_string())?;
+    }
+
+    let parsed_url: url::Url = url.parse().map_err(|e: url::ParseError| e.to_string())?;
+
+    // Each server gets its own subdirectory under the app data dir.
+    let data_dir = server_data_dir(&app, &server_id)?;
+    std::fs::create_dir_all(&data_dir)
+        .map_err(|e| format!("Failed to create data dir: {e}"))?;
+
+    let window = app
+        .get_window("main")
+        .ok_or_else(|| "Main window not found".to_string())?;
+
+    // Validate the mic/speaker selection against the live device list before
+    // baking it in: ids resolve to the device's current label, and a device
+    // that has gone away falls back to the system default rather than leaving
+    // the page with no usable input. If enumeration fails altogether the
+    // stored selection is passed through unchanged.
+    let (mic_id, speaker_id) = match tauri::async_runtime::spawn_blocking(enumerate_audio_devices).await {
+        Ok(Ok(devices)) => (
+            validate_device_selection(&devices, AudioDeviceKind::Input, mic_id),
+            validate_device_selection(&devices, AudioDeviceKind::Output, speaker_id),
+        ),
+        _ => (mic_id, speaker_id),
+    };
+
+    // Build the device-preference override script with the chosen device IDs
+    // baked in at webview creation time. It patches getUserMedia, enumerateDevices,
+    // and auto-applies setSinkId on audio/video elements so the Sharkord server
+    // can only ever see / use the devices the user has selected.
+    let mic_js    = mic_id    .as_deref().unwrap_or("");
+    let cam_js    = cam_id    .as_deref().unwrap_or("");
+    let speaker_js = speaker_id.as_deref().unwrap_or("");
+    let device_script = format!(r#"
+(function(){{
+  var MIC_ID     = {mic_json};
+  var CAM_ID     = {cam_json};
+  var SPEAKER_ID = {speaker_json};
+
+  /* ── Helpers: resolve a stored device label to a live deviceId ─────── */
+  // Device IDs are not stable across isolated WebView2 contexts (each server
+  // webview has its own data directory, so the browser assigns different IDs
+  // to the same physical device). Labels ARE stable — we use those instead.
+  function _resolveId(kind, label) {{
+    // Returns a Promise<string|null>
+    var _e = navigator.mediaDevices.enumerateDevices.__ss_orig ||
+             navigator.mediaDevices.enumerateDevices.bind(navigator.mediaDevices);
+    return _e().then(function(all) {{
+      var match = all.find(function(d) {{ return d.kind === kind && d.label === label; }});
+      return match ? match.deviceId : null;
+    }});
+  }}
+
+  /* ── getUserMedia: force selected device by label ───────────────────── */
+  var _gum = navigator.mediaDevices.getUserMedia.bind(navigator.mediaDevices);
+  navigator.mediaDevices.getUserMedia = function(c) {{
+    c = c ? JSON.parse(JSON.stringify(c)) : {{}};
+    var promises = [];
+    if (MIC_ID && c.audio) {{
+      promises.push(_resolveId('audioinput', MIC_ID).then(function(id) {{
+        if (id) c.audio = typeof c.audio === 'object'
+          ? Object.assign({{}}, c.audio, {{ deviceId: {{ exact: id }} }})
+          : {{ deviceId: {{ exact: id }} }};
+      }}));
+    }}
+    if (CAM_ID && c.video) {{
+      promises.push(_resolveId('videoinput', CAM_ID).then(function(id) {{
+        if (id) c.video = typeof c.video === 'object'
+          ? Object.assign({{}}, c.video, {{ deviceId: {{ exact: id }} }})
+          : {{ deviceId: {{ exact: id }} }};
+      }}));
+    }}
+    return Promise.all(promises).then(function() {{ return _gum(c); }});
+  }};
+
+  /* ── enumerateDevices: hide unselected devices (filter by label) ─────── */
+  var _enum = navigator.mediaDevices.enumerateDevices.bind(navigator.mediaDevices);
+  // Store original so _resolveId can bypass our override
+  navigator.mediaDevices.enumerateDevices.__ss_orig = _enum;
+  navigator.mediaDevices.enumerateDevices = function() {{
+    return _enum().then(function(all) {{
+      return all.filter(function(d) {{
+        // Always pass through devices with empty deviceId or label — these are
+        // returned before permission is granted (browser privacy masking).
+        if (!d.deviceId || !d.label) return true;
+        if (d.kind === 'audioinput')  return !MIC_ID     || d.deviceId === 'default' || d.label === MIC_ID;
+        if (d.kind === 'videoinput')  return !CAM_ID     || d.label === CAM_ID;
+        if (d.kind === 'audiooutput') return !SPEAKER_ID || d.deviceId === 'default' || d.label === SPEAKER_ID;
+        return true;
+      }});
+    }});
+  }};
+
+  /* ── setSinkId: auto-apply speaker to all audio/video elements ──────── */
+  if (SPEAKER_ID) {{
+    // Resolve speaker label to actual deviceId for setSinkId
+    var _resolvedSpeakerId = null;
+    _resolveId('audiooutput', SPEAKER_ID).then(function(id) {{ _resolvedSpeakerId = id; }});
+    function _sink(el) {{
+      if (el.setSinkId && !el.__ssSink) {{
+        el.__ssSink = true;
+        var sid = _resolvedSpeakerId || SPEAKER_ID;
+        el.setSinkId(sid).catch(function(){{}});
+      }}
+    }}
+    var _obs = new MutationObserver(function(ms) {{
+      ms.forEach(function(m) {{
+        m.addedNodes.forEach(function(n) {{
+          if (n.nodeType !== 1) return;
+          if (n.tagName === 'AUDIO' || n.tagName === 'VIDEO') _sink(n);
+          if (n.querySelectorAll) n.querySelectorAll('audio,video').forEach(_sink);
+        }});
+      }});
+    }});
+    _obs.observe(document.documentElement, {{ subtree: true, childList: true }});
+    document.addEventListener('DOMContentLoaded', function() {{
+      document.querySelectorAll('audio,video').forEach(_sink);
+    }});
+  }}
+}})();
+"#,
+        mic_json     = serde_json::to_string(mic_js)    .unwrap_or_default(),
+        cam_json     = serde_json::to_string(cam_js)    .unwrap_or_default(),
+        speaker_json = serde_json::to_string(speaker_js).unwrap_or_default(),
+    );
+
+    // Per-server PTT: wrap every audio track this page obtains so Rust can gate
+    // it via window.__ssPtt without touching the OS mic. The page keeps full
+    // control of its own mute state — PTT only ever narrows it. System-wide PTT
+    // needs no injection; it works at the OS level (see ptt_set_mic_mute).
+    // The app-wide mute (see `VoiceState`) closes the same tracks.
+    let (ptt_gated, ptt_open) = {
+        let state = app.state::<SharedPttState>();
+        let locked = state.lock().unwrap();
+        (locked.tracks_gated(server_id.as_str()), locked.tracks_initially_open(server_id.as_str()))
+    };
+    let voice = *app.state::<SharedVoiceState>().lock().unwrap();
+    let voice_muted = voice.mic_muted();
+    let ptt_script = format!(r#"
+(function(){{
+  var GATED = {ptt_gated};
+  var _open = {ptt_open};
+  var _muted = {voice_muted};
+  var _tracks = [];
+  var _desc = Object.getOwnPropertyDescriptor(MediaStreamTrack.prototype, 'enabled');
+
+  function _effective(want) {{ return want && !_muted && (!GATED || _open); }}
+  function _apply() {{
+    _tracks = _tracks.filter(function(e) {{ return e.t.readyState !== 'ended'; }});
+    _tracks.forEach(function(e) {{ _desc.set.call(e.t, _effective(e.want)); }});
+  }}
+  function _adopt(t, want) {{
+    var e = {{ t: t, want: want }};
+    t.__ssPtt = e;
+    Object.defineProperty(t, 'enabled', {{
+      configurable: true,
+      get: function() {{ return e.want; }},
+      set: function(v) {{ e.want = !!v; _desc.set.call(t, _effective(e.want)); }}
+    }});
+    _tracks.push(e);
+  }}
+
+  window.__ssPtt = {{
+    setGated: function(g) {{ GATED = !!g; _apply(); }},
+    setOpen:  function(o) {{ _open = !!o; _apply(); }},
+    setMuted: function(m) {{ _muted = !!m; _apply(); }}
+  }};
+
+  /* Clones (e.g. made by the SFU client before producing) stay gated too. */
+  var _clone = MediaStreamTrack.prototype.clone;
+  MediaStreamTrack.prototype.clone = function() {{
+    var c = _clone.call(this);
+    if (this.__ssPtt) {{ _adopt(c, this.__ssPtt.want); _apply(); }}
+    return c;
+  }};
+
+  var _gumPtt = navigator.mediaDevices.getUserMedia.bind(navigator.mediaDevices);
+  navigator.mediaDevices.getUserMedia = function(c) {{
+    return _gumPtt(c).then(function(stream) {{
+      stream.getAudioTracks().forEach(function(t) {{ _adopt(t, _desc.get.call(t)); }});
+      _apply();
+      return stream;
+    }});
+  }};
+}})();
+"#);
+
+    let deafen_script = DEAFEN_SCRIPT.replace("__DEAFENED__", &voice.deafened.to_string());
+
+    let combined_init = format!("{CONTEXT_MENU_SCRIPT}
+{NOTIFICATION_SCRIPT}
+{BADGE_SCRIPT}
+{device_script}
+{ptt_script}
+{deafen_script}");
+
+    let builder = WebviewBuilder::new(&label, WebviewUrl::External(parsed_url.clone()))
+        .data_directory(data_dir)
+        .initialization_script(&combined_init)
+        // Tauri intercepts drag-and-drop at the OS level by default, which
+        // prevents the browser's native DataTransfer / drop events from firing
+        // inside the webview. Disabling it lets Sharkord's file upload handler
+        // receive drag-and-drop events exactly as it would in a normal browser.
+        .disable_drag_drop_handler();
+
+    let builder = match &connection.proxy {
+        Some(proxy) => builder.proxy_url(proxy.url.clone()),
+        None => builder,
+    };
+
+    // Keep the webview on its server; see `classify_navigation`. Under
+    // WebKitGTK navigations are decided by `watch_navigations` instead.
+    #[cfg(not(target_os = "linux"))]
+    let builder = {
+        let nav_app = app.clone();
+        let nav_server = server_id.clone();
+        let nav_origin = parsed_url.clone();
+        builder.on_navigation(move |target| {
+            let domains = server_in_app_domains(&nav_app, &nav_server);
+            match classify_navigation(&nav_origin, target, &domains) {
+                NavigationTarget::InApp => true,
+                NavigationTarget::External => {
+                    open_navigation_externally(&nav_app, target);
+                    false
+                }
+            }
+        })
+    };
+    let popup_app = app.clone();
+    let popup_server = server_id.clone();
+    let popup_origin = parsed_url.clone();
+    let builder = builder
+        // window.open / target=_blank. In-app targets get the engine's own
+        // popup, which keeps `window.opener` for OAuth popup flows.
+        .on_new_window(move |target, _features| {
+            let domains = server_in_app_domains(&popup_app, &popup_server);
+            match classify_navigation(&popup_origin, &target, &domains) {
+                NavigationTarget::InApp => tauri::webview::NewWindowResponse::Allow,
+                NavigationTarget::External => {
+                    open_navigation_externally(&popup_app, &target);
+                    tauri::webview::NewWindowResponse::Deny
+                }
+            }
+        });
+
+    // data_store_identifier adds WebView2-level environment isolation on Windows,
+    // on top of the separate data_directory above.
+    #[cfg(target_os = "windows")]
+    let builder = builder.data_store_identifier(server_id.to_bytes());
+
+    #[cfg_attr(not(any(windows, target_os = "linux")), allow(unused_variables))]
+    let wv = window
+        .add_child(
+            builder,
+            LogicalPosition::new(x, y),
+            LogicalSize::new(width, height),
+        )
+        .map_err(|e: tauri::Error| e.to_string())?;
+
+    // On Windows: disable the native WebView2 context menu entirely at the OS
+    // level via ICoreWebView2Settings. This is the only reliable way to prevent
+    // the native edit/image/inspect menus from appearing alongside our custom JS
+    // menu.  Our initialization_script still shows the custom JS menu because
+    // the JS `contextmenu` event fires independently of AreDefaultContextMenusEnabled.
+    // We also disable DevTools (F12 / Ctrl+Shift+I) since this is a production client.
+    #[cfg(windows)]
+    {
+        let wv_clone = wv.clone();
+        let cert_app = app.clone();
+        let cert_server = server_id.clone();
+        // spawn_blocking so we don't deadlock the async command's thread —
+        // with_webview dispatches the closure to the main thread synchronously.
+        tauri::async_runtime::spawn(async move {
+            wv_clone.with_webview(move |webview| {
+                unsafe {
+                    let core = webview.controller().CoreWebView2().expect("get CoreWebView2");
+                    // Settings() already returns ICoreWebView2Settings — no cast needed
+                    let settings = core.Settings().expect("get ICoreWebView2Settings");
+
+                    // webview2-com 0.39 wraps these methods to take plain Rust bool
+                    settings
+                        .SetAreDefaultContextMenusEnabled(false)
+                        .expect("SetAreDefaultContextMenusEnabled");
+                    settings
+                        .SetAreDevToolsEnabled(false)
+                        .expect("SetAreDevToolsEnabled");
+
+                    if let Err(e) = pinning::watch_certificate_errors(&core, cert_app, cert_server) {
+                        log::warn!("Certificate pinning unavailable in this WebView2 runtime: {e}");
+                    }
+                }
+            }).ok();
+        });
+    }
+
+    // On Linux: let WebKitGTK accept the server's pinned certificate, sign
+    // in to its proxy, and keep navigations on the server.
+    #[cfg(target_os = "linux")]
+    {
+        let wv_clone = wv.clone();
+        let cert_app = app.clone();
+        let cert_server = server_id.clone();
+        let nav_app = app.clone();
+        let nav_server = server_id.clone();
+        let nav_origin = parsed_url.clone();
+        let proxy_auth = connection.proxy.as_ref().and_then(|p| Some((p.username.clone()?, p.password.clone()?)));
+        tauri::async_runtime::spawn(async move {
+            wv_clone
+                .with_webview(move |webview| {
+                    let inner = webview.inner();
+                    pinning::watch_certificate_errors(&inner, cert_app, cert_server);
+                    navigation::watch_navigations(&inner, nav_app, nav_server, nav_origin);
+                    if let Some((username, password)) = proxy_auth {
+                        proxy::answer_proxy_auth(&inner, username, password);
+                    }
+                })
+                .ok();
+        });
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn reload_server_webview(app: AppHandle, label: String) -> Result<(), String> {
+    // Only server webviews may be reloaded this way — never the main UI.
+    ServerId::from_webview_label(&label)?;
+    let webview = app
+        .get_webview(&label)
+        .ok_or_else(|| format!("Webview not found: {label}"))?;
+    webview
+        .eval("window.location.reload()")
+        .map_err(|e: tauri::Error| e.to_string())
+}
+
+// ── Server data deletion ─────────────────────────────────────────────────────
+//
+// Removing a server must not wait on the filesystem. A background thread
+// renames the data dir into tombstones/, which frees the server's slot under
+// servers/, then removes the tombstone. Both steps retry with exponential
+// backoff, because WebView2 keeps the profile locked for a while after its
+// webview closes — on Windows even the rename fails until it lets go.
+// Progress is reported on "server-data://deletion". A tombstone that outlives
+// the app is picked up again on the next launch; a data dir that never made it
+// into tombstones/ is offered by the orphan sweep instead.
+
+const DELETION_EVENT: &str = "server-data://deletion";
+const DELETE_INITIAL_BACKOFF: std::time::Duration = std::time::Duration::from_millis(200);
+const DELETE_MAX_BACKOFF: std::time::Duration = std::time::Duration::from_secs(30);
+const DELETE_MAX_ATTEMPTS: u32 = 12;
+/// `clear_server_data` keeps the user waiting, so it gives up after ~3 s.
+const CLEAR_MAX_ATTEMPTS: u32 = 5;
+
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum DeletionStatus {
+    /// The data dir has left servers/; removal of the tombstone has begun.
+    Tombstoned,
+    /// An attempt failed; the next one runs after `retryInMs`.
+    #[serde(rename_all = "camelCase")]
+    Retrying { attempt: u32, retry_in_ms: u64, error: String },
+    Deleted,
+    /// Out of attempts for this session. A tombstone stays on disk and is
+    /// retried on the next launch; a data dir that couldn't be moved stays
+    /// under servers/ for the orphan sweep.
+    Failed { error: String },
+    /// Stopped by `cancel_server_data_deletion`. Whatever is left is picked
+    /// up on the next launch, as for `Failed`.
+    Cancelled,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeletionEvent {
+    pub server_id: ServerId,
+    #[serde(flatten)]
+    pub status: DeletionStatus,
+}
+
+/// Cancellation for one deletion job; `wait` doubles as the backoff sleep so
+/// a cancel takes effect immediately rather than after the current delay.
+#[derive(Default)]
+pub struct CancelFlag {
+    cancelled: Mutex<bool>,
+    wake: std::sync::Condvar,
+}
+
+impl CancelFlag {
+    pub fn cancel(&self) {
+        *self.cancelled.lock().unwrap() = true;
+        self.wake.notify_all();
+    }
+
+    /// Sleep for `timeout` unless cancelled first. Returns true if cancelled.
+    pub fn wait(&self, timeout: std::time::Duration) -> bool {
+        let guard = self.cancelled.lock().unwrap();
+        let (guard, _) = self.wake.wait_timeout_while(guard, timeout, |c| !*c).unwrap();
+        *guard
+    }
+}
+
+/// Running deletion jobs, keyed by tombstone path.
+#[derive(Default)]
+pub struct DeletionJobs {
+    jobs: Mutex<std::collections::HashMap<std::path::PathBuf, (ServerId, Arc<CancelFlag>)>>,
+}
+
+/// The tombstones root, created if missing.
+fn tombstones_root(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    let root = app
+        .path()
+        .app_data_dir()
+        .map_err(|e: tauri::Error| e.to_string())?
+        .join("tombstones");
+    std::fs::create_dir_all(&root)
+        .map_err(|e| format!("Failed to create tombstone dir: {e}"))?;
+    root.canonicalize()
+        .map_err(|e| format!("Failed to resolve tombstone dir: {e}"))
+}
+
+/// Tombstones are named `<server id>-<unix millis>`; returns the owner of a
+/// tombstone name, or None for anything we did not create.
+fn tombstone_owner(name: &str) -> Option<ServerId> {
+    let (id, stamp) = name.split_at_checked(36)?;
+    stamp.strip_prefix('-')?.parse::<u128>().ok()?;
+    ServerId::parse(id).ok()
+}
+
+/// A fresh tombstone path for `id`. Not created — the data dir is renamed
+/// onto it.
+fn new_tombstone(app: &AppHandle, id: &ServerId) -> Result<std::path::PathBuf, String> {
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    Ok(tombstones_root(app)?.join(format!("{id}-{stamp}")))
+}
+
+/// One attempt at moving `source` onto `tombstone`. Returns false if there
+/// was no data dir to move.
+fn tombstone_attempt(source: &std::path::Path, tombstone: &std::path::Path) -> Result<bool, String> {
+    match std::fs::rename(source, tombstone) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(format!("Failed to move server data aside: {e}")),
+    }
+}
+
+/// One removal attempt; a tombstone that is already gone counts as removed.
+fn delete_attempt(tombstone: &std::path::Path) -> Result<(), String> {
+    match std::fs::remove_dir_all(tombstone) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
+
+/// Run `attempt` until it succeeds, up to `DELETE_MAX_ATTEMPTS` times,
+/// sleeping from `initial` doubling to `DELETE_MAX_BACKOFF` in between.
+/// `retrying` hears about each failure that is about to be retried. Ends
+/// with `Failed` out of attempts, or `Cancelled` once `flag` is set.
+fn with_backoff<T>(
+    flag: &CancelFlag,
+    initial: std::time::Duration,
+    mut attempt: impl FnMut() -> Result<T, String>,
+    mut retrying: impl FnMut(DeletionStatus),
+) -> Result<T, DeletionStatus> {
+    let mut delay = initial;
+    for n in 1.. {
+        match attempt() {
+            Ok(value) => return Ok(value),
+            Err(error) if n >= DELETE_MAX_ATTEMPTS => return Err(DeletionStatus::Failed { error }),
+            Err(error) => {
+                retrying(DeletionStatus::Retrying { attempt: n, retry_in_ms: delay.as_millis() as u64, error });
+                if flag.wait(delay) {
+                    return Err(DeletionStatus::Cancelled);
+                }
+                delay = (delay * 2).min(DELETE_MAX_BACKOFF);
+            }
+        }
+    }
+    unreachable!()
+}
+
+/// Start a background job removing `tombstone`, after moving `data_dir` onto
+/// it when given (a tombstone resumed from an earlier session has none).
+/// Returns immediately.
+fn spawn_deletion_job(
+    app: &AppHandle,
+    server_id: ServerId,
+    data_dir: Option<std::path::PathBuf>,
+    tombstone: std::path::PathBuf,
+) -> Result<(), String> {
+    let flag = Arc::new(CancelFlag::default());
+    app.state::<DeletionJobs>()
+        .jobs
+        .lock()
+        .unwrap()
+        .insert(tombstone.clone(), (server_id.clone(), flag.clone()));
+
+    let app = app.clone();
+    std::thread::Builder::new()
+        .name(format!("sweetshark-delete-{server_id}"))
+        .spawn(move || {
+            let emit = |status: DeletionStatus| {
+                let _ = app.emit(DELETION_EVENT, DeletionEvent { server_id: server_id.clone(), status });
+            };
+            let moved = match &data_dir {
+                Some(source) => with_backoff(&flag, DELETE_INITIAL_BACKOFF, || tombstone_attempt(source, &tombstone), &emit),
+                None => Ok(true),
+            };
+            let status = match moved {
+                // Nothing on disk: already as good as deleted.
+                Ok(false) => DeletionStatus::Deleted,
+                Ok(true) => {
+                    emit(DeletionStatus::Tombstoned);
+                    match with_backoff(&flag, DELETE_INITIAL_BACKOFF, || delete_attempt(&tombstone), &emit) {
+                        Ok(()) => DeletionStatus::Deleted,
+                        Err(status) => status,
+                    }
+                }
+                Err(status) => status,
+            };
+            if let DeletionStatus::Failed { error } = &status {
+                log::error!("Failed to delete server data for {server_id}: {error}");
+            }
+
+            let jobs = app.state::<DeletionJobs>();
+            let mut jobs = jobs.jobs.lock().unwrap();
+            if jobs.get(&tombstone).is_some_and(|(_, f)| Arc::ptr_eq(f, &flag)) {
+                jobs.remove(&tombstone);
+            }
+            drop(jobs);
+            emit(status);
+        })
+        .map(|_| ())
+        .map_err(|e| format!("Failed to start deletion: {e}"))
+}
+
+/// Resume removal of tombstones left by a previous session. Called once at
+/// startup.
+fn resume_tombstones(app: &AppHandle) -> Result<(), String> {
+    resume_tombstones_in(&tombstones_root(app)?, |id, tombstone| spawn_deletion_job(app, id, None, tombstone))
+}
+
+/// Hand each of our tombstones under `root` to `resume`. Entries that are not
+/// ours (bad name, symlink, file) are ignored, and one that cannot be resumed
+/// does not hold up the rest.
+fn resume_tombstones_in(
+    root: &std::path::Path,
+    mut resume: impl FnMut(ServerId, std::path::PathBuf) -> Result<(), String>,
+) -> Result<(), String> {
+    let entries = std::fs::read_dir(root)
+        .map_err(|e| format!("Failed to read tombstone dir: {e}"))?;
+    for entry in entries.flatten() {
+        if !entry.file_type().is_ok_and(|t| t.is_dir()) {
+            continue;
+        }
+        if let Some(id) = tombstone_owner(&entry.file_name().to_string_lossy()) {
+            if let Err(e) = resume(id, entry.path()) {
+                log::warn!("Failed to resume deletion of {}: {e}", entry.path().display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Delete the on-disk data directory for a server that has been removed, so
+/// all cached cookies, IndexedDB, localStorage, and WebView2 profile data are
+/// wiped. Called by the frontend after destroying the webview.
+///
+/// Returns once the background job has started: moving the data dir into
+/// tombstones/ and removing it both happen there, with retries, and the
+/// outcome arrives as "server-data://deletion" events.
+#[tauri::command]
+fn delete_server_data(app: AppHandle, server_id: ServerId) -> Result<(), String> {
+    // Contained under servers/ — the rename and remove_dir_all never see a
+    // path that resolves anywhere else.
+    let source = server_data_dir(&app, &server_id)?;
+    let tombstone = new_tombstone(&app, &server_id)?;
+    spawn_deletion_job(&app, server_id, Some(source), tombstone)
+}
+
+/// Stop any running deletion jobs for `server_id`. Their tombstones stay on
+/// disk and are resumed on the next launch.
+#[tauri::command]
+fn cancel_server_data_deletion(jobs: tauri::State<'_, DeletionJobs>, server_id: ServerId) {
+    for (id, flag) in jobs.jobs.lock().unwrap().values() {
+        if *id == server_id {
+            flag.cancel();
+        }
+    }
+}
+
+#[tauri::command]
+fn greet(name: &str) -> String {
+    format!("Hello, {}! Welcome to Sweetshark Client.", name)
+}
+
+/// Returns the current app version string from the compiled-in Cargo.toml version.
+#[tauri::command]
+fn get_app_version() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
+
+/// Checks the GitHub releases API for the latest release tag on the
+/// Sweets-omg/Sweetshark-Client-v2 repo and returns it as a string,
+/// or an empty string if the check fails (network unavailable, etc.).
+/// Tags are expected in the format "V.X.X.X" (e.g. "V.2.0.1").
+#[tauri::command]
+async fn check_for_update() -> String {
+    let client = match reqwest::Client::builder()
+        .user_agent("Sweetshark-Client-v2-updater")
+        .timeout(std::time::Duration::from_secs(8))
+        .build()
+    {
+        Ok(c) => c,
+        Err(_) => return String::new(),
+    };
+
+    let res = client
+        .get("https://api.github.com/repos/Sweets-omg/Sweetshark-Client-v2/releases/latest")
+        .send()
+        .await;
+
+    let body = match res {
+        Ok(r) => match r.json::<serde_json::Value>().await {
+            Ok(j) => j,
+            Err(_) => return String::new(),
+        },
+        Err(_) => return String::new(),
+    };
+
+    // GitHub returns { "tag_name": "V.2.0.1", ... }
+    body["tag_name"]
+        .as_str()
+        .unwrap_or("")
+        .to_string()
+}
+
+// ── Mute and deafen ──────────────────────────────────────────────────────────
+//
+// App-wide switches, like a voice client's own mute and deafen buttons. They
+// act inside the server pages only: mute closes the audio tracks through the
+// same wrapper PTT uses (`window.__ssPtt.setMuted`), deafen silences the
+// pages' media elements and audio contexts (`DEAFEN_SCRIPT`). The OS mic
+// and other apps are untouched. Not persisted: every launch starts live.
+//
+// Sharkord isn't told: both work underneath the page, so its own mute and
+// deafen buttons, and the state other members see, stay as they were. The
+// settings page and the sidebar indicator say so. Sharkord has no API for
+// its controls, and clicking them by DOM selector would break with any UI
+// change.
+
+const VOICE_CHANGED_EVENT: &str = "voice://changed";
+
+/// Output side of deafen; `__DEAFENED__` is replaced with the state at
+/// webview creation.
+const DEAFEN_SCRIPT: &str = r#"
+(function () {
+  var _deaf = __DEAFENED__;
+  var _muted = Object.getOwnPropertyDescriptor(HTMLMediaElement.prototype, 'muted');
+  var _want = new WeakMap(); /* element → muted as the page last set it */
+  var _elements = new Set();
+  var _contexts = [];
+
+  function _sync(el) { _muted.set.call(el, _want.get(el) || _deaf); }
+  function _track(el) {
+    if (!_want.has(el)) _want.set(el, _muted.get.call(el));
+    _elements.add(el);
+    _sync(el);
+  }
+
+  /* The page keeps reading and writing its own value; deafen only ever adds
+     a mute on top of it. */
+  Object.defineProperty(HTMLMediaElement.prototype, 'muted', {
+    configurable: true,
+    enumerable: _muted.enumerable,
+    get: function () { return _want.has(this) ? _want.get(this) : _muted.get.call(this); },
+    set: function (v) { _want.set(this, !!v); _track(this); }
+  });
+
+  /* Voice audio is often played from elements that never enter the DOM, so
+     catch elements as they are given something to play. */
+  var _play = HTMLMediaElement.prototype.play;
+  HTMLMediaElement.prototype.play = function () { _track(this); return _play.apply(this, arguments); };
+  var _src = Object.getOwnPropertyDescriptor(HTMLMediaElement.prototype, 'srcObject');
+  Object.defineProperty(HTMLMediaElement.prototype, 'srcObject', {
+    configurable: true,
+    enumerable: _src.enumerable,
+    get: _src.get,
+    set: function (v) { _track(this); _src.set.call(this, v); }
+  });
+  new MutationObserver(function (ms) {
+    ms.forEach(function (m) {
+      m.addedNodes.forEach(function (n) {
+        if (n.nodeType !== 1) return;
+        if (n instanceof HTMLMediaElement) _track(n);
+        if (n.querySelectorAll) n.querySelectorAll('audio,video').forEach(_track);
+      });
+    });
+  }).observe(document.documentElement, { subtree: true, childList: true });
+
+  /* Web Audio output is stopped by suspending the contexts we suspended
+     ourselves, and only those are resumed again. */
+  var _AudioContext = window.AudioContext || window.webkitAudioContext;
+  if (_AudioContext) {
+    var Wrapped = function (opts) {
+      var ctx = new _AudioContext(opts);
+      _contexts.push({ ctx: ctx, held: false });
+      if (_deaf) _hold(_contexts[_contexts.length - 1]);
+      return ctx;
+    };
+    Wrapped.prototype = _AudioContext.prototype;
+    window.AudioContext = Wrapped;
+    if (window.webkitAudioContext) window.webkitAudioContext = Wrapped;
+  }
+  function _hold(e) {
+    if (e.ctx.state === 'running') { e.held = true; e.ctx.suspend().catch(function () {}); }
+  }
+
+  window.__ssDeafen = {
+    set: function (d) {
+      _deaf = !!d;
+      _elements.forEach(function (el) {
+        if (!el.isConnected && el.paused) _elements.delete(el);
+        else _sync(el);
+      });
+      _contexts = _contexts.filter(function (e) { return e.ctx.state !== 'closed'; });
+      _contexts.forEach(function (e) {
+        if (_deaf) _hold(e);
+        else if (e.held) { e.held = false; e.ctx.resume().catch(function () {}); }
+      });
+    }
+  };
+})();
+"#;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VoiceState {
+    pub muted: bool,
+    /// Silences the server pages. Implies a muted mic, whatever `muted` says.
+    pub deafened: bool,
+}
+
+impl VoiceState {
+    pub fn mic_muted(&self) -> bool {
+        self.muted || self.deafened
+    }
+
+    /// What the mute button does. Unmuting while deafened also undeafens,
+    /// since the mic can't be live in that state.
+    pub fn toggled_mute(self) -> Self {
+        let muted = !self.mic_muted();
+        Self { muted, deafened: self.deafened && muted }
+    }
+
+    /// What the deafen button does. `muted` is kept, so undeafening goes
+    /// back to the mute the user had before.
+    pub fn toggled_deafen(self) -> Self {
+        Self { deafened: !self.deafened, ..self }
+    }
+}
+
+pub type SharedVoiceState = Arc<Mutex<VoiceState>>;
+
+/// Switch mute and deafen in every live server webview and tell the UI and
+/// the tray. New webviews pick the state up in `create_server_webview`.
+fn apply_voice_state(app: &AppHandle, voice: VoiceState) {
+    {
+        let state = app.state::<SharedVoiceState>();
+        let mut locked = state.lock().unwrap();
+        if *locked == voice {
+            return;
+        }
+        *locked = voice;
+    }
+    let (muted, deafened) = (voice.mic_muted(), voice.deafened);
+    for (label, wv) in app.webviews() {
+        if label.starts_with(SERVER_WEBVIEW_PREFIX) {
+            let _ = wv.eval(format!(
+                "window.__ssPtt && window.__ssPtt.setMuted({muted}); window.__ssDeafen && window.__ssDeafen.set({deafened})"
+            ));
+        }
+    }
+    let _ = app.emit(VOICE_CHANGED_EVENT, voice);
+    refresh_tray_menu(app);
+}
+
+/// Apply `change` to the current voice state (a toggle from the tray or a
+/// shortcut).
+fn update_voice_state(app: &AppHandle, change: impl FnOnce(VoiceState) -> VoiceState) {
+    let voice = *app.state::<SharedVoiceState>().lock().unwrap();
+    apply_voice_state(app, change(voice));
+}
+
+#[tauri::command]
+fn get_voice_state(state: tauri::State<'_, SharedVoiceState>) -> VoiceState {
+    *state.lock().unwrap()
+}
+
+#[tauri::command]
+fn set_voice_state(app: AppHandle, voice: VoiceState) {
+    apply_voice_state(&app, voice);
+}
+
+// ── Audio devices ────────────────────────────────────────────────────────────
+//
+// Native enumeration of capture and render endpoints. Browser deviceIds are
+// not stable across the isolated per-server webview profiles, which is why
+// the device script matches by label; the ids here are stable, so a stored
+// selection survives the device being renamed.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AudioDeviceKind {
+    Input,
+    Output,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioDevice {
+    /// Stable backend id: the PulseAudio source/sink name on Linux, the
+    /// MMDevice endpoint id on Windows.
+    pub id: String,
+    /// Display name — the same string webviews report as the device label.
+    pub name: String,
+    pub kind: AudioDeviceKind,
+    pub is_default: bool,
+}
+
+/// Enumerate sources and sinks through the PulseAudio native protocol
+/// (pipewire-pulse on PipeWire desktops). Monitor sources are left out —
+/// they mirror playback and are never a microphone.
+#[cfg(target_os = "linux")]
+fn enumerate_audio_devices() -> Result<Vec<AudioDevice>, String> {
+    let mut conn = pulse::Connection::connect()?;
+    let defaults = conn.server_defaults()?;
+    let mut devices = Vec::new();
+    for (kind, list, default) in [
+        (AudioDeviceKind::Input, conn.sources()?, defaults.source),
+        (AudioDeviceKind::Output, conn.sinks()?, defaults.sink),
+    ] {
+        devices.extend(list.into_iter().map(|d| AudioDevice {
+            is_default: default.as_deref() == Some(d.name.as_str()),
+            id: d.name,
+            name: d.description,
+            kind,
+        }));
+    }
+    Ok(devices)
+}
+
+/// Enumerate active capture and render endpoints through Core Audio
+/// (IMMDeviceEnumerator), named by their PKEY_Device_FriendlyName.
+#[cfg(windows)]
+fn enumerate_audio_devices() -> Result<Vec<AudioDevice>, String> {
+    use windows::{
+        Win32::Devices::FunctionDiscovery::PKEY_Device_FriendlyName,
+        Win32::Media::Audio::{eCapture, eConsole, eRender, IMMDevice, IMMDeviceEnumerator, MMDeviceEnumerator, DEVICE_STATE_ACTIVE},
+        Win32::System::Com::{CoCreateInstance, CoInitializeEx, CoTaskMemFree, CoUninitialize, CLSCTX_ALL, COINIT_APARTMENTTHREADED, STGM_READ},
+    };
+
+    /// Read an endpoint id, freeing the COM-allocated string.
+    unsafe fn endpoint_id(device: &IMMDevice) -> Result<String, String> {
+        let raw = device.GetId().map_err(|e| format!("Core Audio: GetId failed: {e}"))?;
+        let id = raw.to_string().map_err(|e| e.to_string());
+        CoTaskMemFree(Some(raw.0 as *const _));
+        id
+    }
+
+    unsafe {
+        let hr_init = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+        let com_inited = hr_init.is_ok() || hr_init.0 == 1; // S_OK or S_FALSE
+
+        let result = (|| -> Result<Vec<AudioDevice>, String> {
+            let enumerator: IMMDeviceEnumerator =
+                CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)
+                    .map_err(|e| format!("Core Audio: device enumerator unavailable: {e}"))?;
+            let mut devices = Vec::new();
+            for (kind, flow) in [(AudioDeviceKind::Input, eCapture), (AudioDeviceKind::Output, eRender)] {
+                // No default endpoint is normal when nothing of that kind is plugged in.
+                let default_id = enumerator
+                    .GetDefaultAudioEndpoint(flow, eConsole)
+                    .ok()
+                    .and_then(|d| endpoint_id(&d).ok());
+                let collection = enumerator
+                    .EnumAudioEndpoints(flow, DEVICE_STATE_ACTIVE)
+                    .map_err(|e| format!("Core Audio: failed to enumerate endpoints: {e}"))?;
+                let count = collection.GetCount().map_err(|e| e.to_string())?;
+                for i in 0..count {
+                    let device = collection.Item(i).map_err(|e| e.to_string())?;
+                    let id = endpoint_id(&device)?;
+                    let name = device
+                        .OpenPropertyStore(STGM_READ)
+                        .and_then(|store| store.GetValue(&PKEY_Device_FriendlyName))
+                        .map(|v| v.to_string())
+                        .unwrap_or_else(|_| id.clone());
+                    devices.push(AudioDevice {
+                        is_default: default_id.as_deref() == Some(id.as_str()),
+                        id,
+                        name,
+                        kind,
+                    });
+                }
+            }
+            Ok(devices)
+        })();
+
+        if com_inited { CoUninitialize(); }
+        result
+    }
+}
+
+/// Elsewhere (macOS) fall back to cpal, which exposes no stable endpoint id;
+/// the device name doubles as the id.
+#[cfg(not(any(windows, target_os = "linux")))]
+fn enumerate_audio_devices() -> Result<Vec<AudioDevice>, String> {
+    use cpal::traits::{DeviceTrait, HostTrait};
+
+    let host = cpal::default_host();
+    let default_in = host.default_input_device().and_then(|d| d.name().ok());
+    let default_out = host.default_output_device().and_then(|d| d.name().ok());
+    let mut devices = Vec::new();
+    for (kind, list, default) in [
+        (AudioDeviceKind::Input, host.input_devices(), default_in),
+        (AudioDeviceKind::Output, host.output_devices(), default_out),
+    ] {
+        let list = list.map_err(|e| e.to_string())?;
+        devices.extend(list.filter_map(|d| d.name().ok()).map(|name| AudioDevice {
+            is_default: default.as_deref() == Some(name.as_str()),
+            id: name.clone(),
+            name,
+            kind,
+        }));
+    }
+    Ok(devices)
+}
+
+/// Resolve a stored device selection to the label the device script matches
+/// on. Accepts an id from `list_audio_devices` or — for selections saved
+/// before ids existed — a label. None means the device is gone.
+fn resolve_device_selection(devices: &[AudioDevice], kind: AudioDeviceKind, selected: &str) -> Option<String> {
+    let of_kind = || devices.iter().filter(move |d| d.kind == kind);
+    of_kind()
+        .find(|d| d.id == selected)
+        .or_else(|| of_kind().find(|d| d.name == selected))
+        .map(|d| d.name.clone())
+}
+
+/// The label a new server webview should use for a stored selection, or
+/// None for the system default: when nothing is selected, or when the
+/// selected device is gone (which is logged).
+fn validate_device_selection(devices: &[AudioDevice], kind: AudioDeviceKind, selected: Option<String>) -> Option<String> {
+    let selected = selected.filter(|s| !s.is_empty())?;
+    let resolved = resolve_device_selection(devices, kind, &selected);
+    if resolved.is_none() {
+        log::warn!("Selected {kind:?} device not found, using system default: {selected}");
+    }
+    resolved
+}
+
+/// List capture and render endpoints with stable ids, names and default flags.
+#[tauri::command]
+async fn list_audio_devices() -> Result<Vec<AudioDevice>, String> {
+    tauri::async_runtime::spawn_blocking(enumerate_audio_devices)
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+// ── Push-to-talk ─────────────────────────────────────────────────────────────
+//
+// Uses GetAsyncKeyState polling on a dedicated thread so that PTT works
+// regardless of which window has focus — including when the Tauri/WebView2
+// window itself is focused.
+//
+// WH_KEYBOARD_LL was tried first but WebView2 consumes keyboard events before
+// they reach a low-level hook's message pump when the webview has focus.
+// GetAsyncKeyState reads key state directly from the keyboard driver and is
+// completely unaffected by focus or message routing.
+//
+// Mouse buttons (right, middle, XButton1/2) also have real Win32 VK codes
+// that GetAsyncKeyState understands, so we use one unified approach for
+// everything — no hooks, no message loop, no unsafe pointer juggling.
+//
+// Poll interval: 5 ms  (~200 Hz) — imperceptible latency for PTT.
+
+/// What a PTT binding actually silences.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, serde::Serialize, serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum PttTarget {
+    /// Mute the OS default capture device — every app and every server.
+    System,
+    /// Only toggle `MediaStreamTrack.enabled` on the audio tracks a server's
+    /// webview obtained through our patched getUserMedia. Nothing outside
+    /// that webview is affected, so this is the default.
+    #[default]
+    Tracks,
+}
+
+/// How the microphone is opened.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, serde::Serialize, serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum PttMode {
+    /// Mic is open only while all bound keys are held.
+    #[default]
+    PushToTalk,
+    /// Each press of the bound keys flips the mic between closed and open.
+    Toggle,
+    /// Mic is open by default; holding the bound keys mutes it.
+    PushToMute,
+    /// Mic opens while the captured signal is above `VadConfig::threshold_db`.
+    /// No keys are polled in this mode.
+    VoiceActivity,
+}
+
+/// Voice-activity gate tuning.
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VadConfig {
+    /// RMS level in dBFS above which the signal counts as speech.
+    pub threshold_db: f32,
+    /// How long the signal must stay above the threshold before opening.
+    pub attack_ms: u32,
+    /// Hangover: how long the signal must stay below the threshold before
+    /// closing, so pauses between words don't chop the audio.
+    pub release_ms: u32,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self { threshold_db: -50.0, attack_ms: 20, release_ms: 300 }
+    }
+}
+
+/// One named key chord. All of its keys must be held at once; separate
+/// bindings are OR-ed together.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct PttBinding {
+    /// Shown to the user and reported in events, e.g. "main" or "priority".
+    pub name: String,
+    /// Normalised key tokens sent from the frontend, e.g. ["alt", "mouse4"]
+    pub keys: Vec<String>,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PttConfig {
+    pub enabled: bool,
+    /// Any one of these chords opens the mic.
+    #[serde(default)]
+    pub bindings: Vec<PttBinding>,
+    #[serde(default)]
+    pub target: PttTarget,
+    #[serde(default)]
+    pub mode: PttMode,
+    #[serde(default)]
+    pub vad: VadConfig,
+    /// Keep the mic open this long after the keys are released so the end of
+    /// a sentence isn't clipped. Not applied to push-to-mute, where closing
+    /// the mic is the deliberate action.
+    #[serde(default)]
+    pub release_delay_ms: u32,
+    /// The keys must be held this long before a press counts, filtering out
+    /// accidental taps and switch bounce.
+    #[serde(default)]
+    pub debounce_ms: u32,
+    /// Play a short tone when the mic opens and closes.
+    #[serde(default)]
+    pub cues: bool,
+}
+
+impl PttConfig {
+    /// Whether enabling this config would run anything: voice activity, or
+    /// at least one binding with keys.
+    pub fn has_trigger(&self) -> bool {
+        self.mode == PttMode::VoiceActivity || self.bindings.iter().any(|b| !b.keys.is_empty())
+    }
+
+    /// Binding names identify the binding in `ptt://` events, so each must be
+    /// non-blank and unique within the config.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut seen = std::collections::HashSet::new();
+        for b in &self.bindings {
+            let name = b.name.trim();
+            if name.is_empty() {
+                return Err("Every push-to-talk binding needs a name".into());
+            }
+            if !seen.insert(name) {
+                return Err(format!("Two push-to-talk bindings are named \"{name}\""));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Key under which the all-servers binding is stored in `PttState::configs`.
+/// It applies to every server that has no enabled binding of its own.
+pub const PTT_ALL_SERVERS: &str = "*";
+
+/// OS-level control of the default capture device.
+///
+/// Every path that mutes or unmutes the system mic goes through this trait,
+/// so a failure is reported instead of leaving PTT looking active over a hot
+/// mic. `SystemMic` is the platform backend; `PttState::with_mic` accepts any
+/// other implementation, e.g. a mock that fails on demand. The one caller is
+/// `ptt_set_system_mic`, which records and reports the outcome.
+pub trait MicControl: Send + Sync {
+    fn set_muted(&self, muted: bool) -> Result<(), String>;
+}
+
+/// The platform microphone backend (Core Audio on Windows, PulseAudio /
+/// PipeWire on Linux).
+#[derive(Default)]
+pub struct SystemMic {
+    /// Sound-server connection shared by every PTT worker, so a key edge costs
+    /// one request instead of a connect and AUTH handshake. Dropped on any
+    /// error and re-established by the next call.
+    #[cfg(target_os = "linux")]
+    pulse: Mutex<Option<pulse::Connection>>,
+}
+
+impl MicControl for SystemMic {
+    #[cfg(target_os = "linux")]
+    fn set_muted(&self, muted: bool) -> Result<(), String> {
+        ptt_set_mic_mute(&mut self.pulse.lock().unwrap(), muted)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn set_muted(&self, muted: bool) -> Result<(), String> {
+        ptt_set_mic_mute(muted)
+    }
+}
+
+/// Answers, per chord, whether all of its key codes are held right now.
+/// `PttKeyReader` is the platform implementation; `PttState::open_keys` lets
+/// a test hand the poll thread a fake instead.
+pub trait ChordReader: Send {
+    fn chords_down(&mut self, chords: &[Vec<u32>]) -> Vec<bool>;
+}
+
+impl ChordReader for PttKeyReader {
+    fn chords_down(&mut self, chords: &[Vec<u32>]) -> Vec<bool> {
+        PttKeyReader::chords_down(self, chords)
+    }
+}
+
+/// How `PttState` opens a `ChordReader` for the given key codes.
+pub type OpenChordReader = fn(&[u32]) -> Result<Box<dyn ChordReader>, String>;
+
+/// A running PTT worker thread (key poll or voice activity).
+pub struct PttWorker {
+    stop_tx: std::sync::mpsc::Sender<()>,
+    thread: std::thread::JoinHandle<()>,
+}
+
+impl PttWorker {
+    /// Signal the thread and wait for it to exit. Once this returns the
+    /// worker touches neither the mic nor the webviews again, so the caller
+    /// can settle their final state without racing it.
+    fn stop(self) {
+        let _ = self.stop_tx.send(());
+        let _ = self.thread.join();
+    }
+}
+
+pub struct PttState {
+    /// PTT bindings keyed by server id (or `PTT_ALL_SERVERS`).
+    pub configs: std::collections::HashMap<String, PttConfig>,
+    /// One worker thread per enabled binding, keyed like `configs`.
+    pub workers: std::collections::HashMap<String, PttWorker>,
+    /// Backend used for the `System` target.
+    pub mic: Arc<dyn MicControl>,
+    /// Opens the key reader a poll thread owns: the platform reader, or a
+    /// fake in tests.
+    pub open_keys: OpenChordReader,
+    /// Last mute failure per scope; cleared by the next successful call.
+    /// Ordered so the all-servers scope ("*") sorts ahead of server ids.
+    pub errors: std::collections::BTreeMap<String, String>,
+}
+
+impl Default for PttState {
+    fn default() -> Self {
+        Self::with_mic(Arc::new(SystemMic::default()))
+    }
+}
+
+/// Returned by `get_ptt_active`.
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PttStatus {
+    /// A worker thread is running.
+    pub active: bool,
+    /// The last mute/unmute failed, so the mic may not be in the state the
+    /// UI shows.
+    pub degraded: bool,
+    /// The failure behind `degraded`.
+    pub error: Option<String>,
+}
+
+impl PttState {
+    pub fn with_mic(mic: Arc<dyn MicControl>) -> Self {
+        Self {
+            configs: Default::default(),
+            workers: Default::default(),
+            mic,
+            open_keys: |codes| Ok(Box::new(PttKeyReader::open(codes)?)),
+            errors: Default::default(),
+        }
+    }
+
+    /// Remember a failed mute call for `scope` (or forget the previous one on
+    /// success) and pass the result through.
+    pub fn record_result(&mut self, scope: &str, result: Result<(), String>) -> Result<(), String> {
+        match &result {
+            Ok(()) => {
+                self.errors.remove(scope);
+            }
+            Err(e) => {
+                self.errors.insert(scope.to_string(), e.clone());
+            }
+        }
+        result
+    }
+
+    /// Status of one scope, or of all of them when `scope` is None. Across
+    /// scopes the reported error is the all-servers one if that failed,
+    /// otherwise the failing server with the lowest id.
+    pub fn status(&self, scope: Option<&str>) -> PttStatus {
+        let (active, error) = match scope {
+            Some(s) => (self.workers.contains_key(s), self.errors.get(s)),
+            None => (!self.workers.is_empty(), self.errors.values().next()),
+        };
+        PttStatus { active, degraded: error.is_some(), error: error.cloned() }
+    }
+
+    /// The scope whose binding governs `server_id`'s webview: its own entry
+    /// when enabled, otherwise the all-servers entry when that is enabled.
+    fn scope_for<'a>(&'a self, server_id: &'a str) -> Option<&'a str> {
+        [server_id, PTT_ALL_SERVERS]
+            .into_iter()
+            .find(|scope| self.configs.get(*scope).is_some_and(|c| c.enabled))
+    }
+
+    /// Whether `server_id`'s audio tracks are subject to a PTT gate at all.
+    fn tracks_gated(&self, server_id: &str) -> bool {
+        self.scope_for(server_id)
+            .and_then(|scope| self.configs.get(scope))
+            .is_some_and(|c| c.target == PttTarget::Tracks)
+    }
+
+    /// Whether a freshly (re)started gate for `server_id` begins open — only
+    /// push-to-mute starts with the mic live.
+    fn tracks_initially_open(&self, server_id: &str) -> bool {
+        self.scope_for(server_id)
+            .and_then(|scope| self.configs.get(scope))
+            .is_some_and(|c| c.mode == PttMode::PushToMute)
+    }
+}
+
+/// The PTT state machine, free of any platform code: it turns one "are the
+/// bound keys held?" sample per poll tick into mic open/close transitions
+/// for the configured mode. Time is passed in rather than read, so the
+/// debounce and release delay can be driven by a fake clock.
+pub struct PttMachine {
+    mode: PttMode,
+    debounce: std::time::Duration,
+    release_delay: std::time::Duration,
+    /// Debounced key state seen on the previous tick, for edge detection.
+    held: bool,
+    /// When the keys were first seen down in the current press.
+    down_since: Option<std::time::Instant>,
+    /// When the mode first asked to close the mic, while the release delay runs.
+    close_requested: Option<std::time::Instant>,
+    /// Keys that were already down when the machine was built (e.g. the user
+    /// switched modes mid-press) don't make a press edge until released once.
+    stale_press: bool,
+    /// Toggle mode: the state the last press asked for. Kept apart from
+    /// `open` so an unlatch is still honoured while the release delay runs.
+    latched: bool,
+    open: bool,
+}
+
+impl PttMachine {
+    pub fn new(config: &PttConfig) -> Self {
+        Self {
+            mode: config.mode,
+            debounce: std::time::Duration::from_millis(config.debounce_ms as u64),
+            release_delay: std::time::Duration::from_millis(config.release_delay_ms as u64),
+            held: false,
+            down_since: None,
+            close_requested: None,
+            stale_press: true,
+            latched: false,
+            open: config.mode == PttMode::PushToMute,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Feed one poll sample taken at `now`. Returns the new mic state on a
+    /// transition.
+    pub fn update(&mut self, raw_held: bool, now: std::time::Instant) -> Option<bool> {
+        // Debounce: a press only counts once it has lasted `debounce`.
+        let held = if raw_held {
+            let since = *self.down_since.get_or_insert(now);
+            now.duration_since(since) >= self.debounce
+        } else {
+            self.down_since = None;
+            self.stale_press = false;
+            false
+        };
+        let pressed = held && !self.held && !self.stale_press;
+        self.held = held;
+
+        let want_open = match self.mode {
+            PttMode::PushToTalk | PttMode::VoiceActivity => held,
+            PttMode::PushToMute => !held,
+            // Latch on the press edge only, so holding the keys doesn't
+            // flip the mic on every tick.
+            PttMode::Toggle => {
+                self.latched ^= pressed;
+                self.latched
+            }
+        };
+
+        if want_open {
+            self.close_requested = None;
+        } else if self.open && self.mode != PttMode::PushToMute {
+            // Hold the mic open until the release delay has elapsed; a new
+            // press in the meantime cancels the pending close above.
+            let since = *self.close_requested.get_or_insert(now);
+            if now.duration_since(since) < self.release_delay {
+                return None;
+            }
+            self.close_requested = None;
+        }
+
+        if want_open == self.open {
+            return None;
+        }
+        self.open = want_open;
+        Some(want_open)
+    }
+}
+
+type SharedPttState = Arc<Mutex<PttState>>;
+
+/// Server webviews are labelled `srv-<serverId>` by webviewManager.ts.
+const SERVER_WEBVIEW_PREFIX: &str = "srv-";
+
+#[tauri::command]
+fn get_ptt_config(
+    state: tauri::State<SharedPttState>,
+) -> std::collections::HashMap<String, PttConfig> {
+    state.lock().unwrap().configs.clone()
+}
+
+/// Convert a frontend key token to a Win32 virtual-key code.
+///
+/// Accepts browser KeyboardEvent.code style ("ShiftLeft", "AltLeft"),
+/// common short names ("shift", "alt", "mouse4"), and single characters.
+/// Case-insensitive.
+///
+/// Mouse buttons map to their real Win32 VK codes:
+///   VK_RBUTTON  = 0x02  (right)
+///   VK_MBUTTON  = 0x04  (middle / mouse3)
+///   VK_XBUTTON1 = 0x05  (mouse4 / side-back)
+///   VK_XBUTTON2 = 0x06  (mouse5 / side-forward)
+#[cfg(windows)]
+fn token_to_vk(token: &str) -> Option<u32> {
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
+    let t = token.to_ascii_lowercase();
+    let t = t.trim();
+    Some(match t {
+        // ── Ctrl ──────────────────────────────────────────────────────────────
+        "ctrl" | "control" | "lctrl" | "lcontrol" | "controlleft" => VK_LCONTROL as u32,
+        "rctrl" | "rcontrol" | "controlright"                      => VK_RCONTROL as u32,
+        // ── Shift ─────────────────────────────────────────────────────────────
+        "shift" | "lshift" | "shiftleft"                           => VK_LSHIFT as u32,
+        "rshift" | "shiftright"                                    => VK_RSHIFT as u32,
+        // ── Alt ───────────────────────────────────────────────────────────────
+        "alt" | "lalt" | "lmenu" | "altleft" | "menu"             => VK_LMENU as u32,
+        "ralt" | "rmenu" | "altright" | "altgr"                    => VK_RMENU as u32,
+        // ── Win / Super ───────────────────────────────────────────────────────
+        "super" | "meta" | "win" | "metaleft" | "osleft"           => VK_LWIN as u32,
+        "metaright" | "osright"                                    => VK_RWIN as u32,
+        // ── Common keys ───────────────────────────────────────────────────────
+        "space"                                                    => VK_SPACE as u32,
+        "enter" | "return" | "numpadenter"                         => VK_RETURN as u32,
+        "escape" | "esc"                                           => VK_ESCAPE as u32,
+        "backspace"                                                => VK_BACK as u32,
+        "tab"                                                      => VK_TAB as u32,
+        "delete" | "del"                                           => VK_DELETE as u32,
+        "insert" | "ins"                                           => VK_INSERT as u32,
+        "home"                                                     => VK_HOME as u32,
+        "end"                                                      => VK_END as u32,
+        "pageup"                                                   => VK_PRIOR as u32,
+        "pagedown"                                                 => VK_NEXT as u32,
+        "arrowup"    | "up"                                        => VK_UP as u32,
+        "arrowdown"  | "down"                                      => VK_DOWN as u32,
+        "arrowleft"  | "left"                                      => VK_LEFT as u32,
+        "arrowright" | "right"                                     => VK_RIGHT as u32,
+        "capslock"                                                 => VK_CAPITAL as u32,
+        // ── Function keys ─────────────────────────────────────────────────────
+        "f1"  => VK_F1  as u32, "f2"  => VK_F2  as u32,
+        "f3"  => VK_F3  as u32, "f4"  => VK_F4  as u32,
+        "f5"  => VK_F5  as u32, "f6"  => VK_F6  as u32,
+        "f7"  => VK_F7  as u32, "f8"  => VK_F8  as u32,
+        "f9"  => VK_F9  as u32, "f10" => VK_F10 as u32,
+        "f11" => VK_F11 as u32, "f12" => VK_F12 as u32,
+        // ── Misc ──────────────────────────────────────────────────────────────
+        "print" | "printscreen"                                    => VK_SNAPSHOT as u32,
+        "scrolllock"                                               => VK_SCROLL as u32,
+        "pause"                                                    => VK_PAUSE as u32,
+        "numlock"                                                  => VK_NUMLOCK as u32,
+        // ── Mouse buttons (real Win32 VK codes) ───────────────────────────────
+        "mouse2" | "mouseright"  | "rightbutton"                   => VK_RBUTTON  as u32,
+        "mouse3" | "mousemiddle" | "middlebutton"                  => VK_MBUTTON  as u32,
+        "mouse4" | "xbutton1"                                      => VK_XBUTTON1 as u32,
+        "mouse5" | "xbutton2"                                      => VK_XBUTTON2 as u32,
+        // ── Single-character fallback via VkKeyScanW ──────────────────────────
+        other => {
+            let chars: Vec<char> = other.chars().collect();
+            if chars.len() == 1 {
+                let scan = unsafe {
+                    windows_sys::Win32::UI::Input::KeyboardAndMouse::VkKeyScanW(chars[0] as u16)
+                };
+                if scan != -1i16 {
+                    (scan & 0xFF) as u32
+                } else {
+                    return None;
+                }
+            } else {
+                return None;
+            }
+        }
+    })
+}
+
+/// Convert a frontend key token to a Linux evdev key code (`KEY_*` / `BTN_*`
+/// from linux/input-event-codes.h).
+///
+/// Accepts exactly the same tokens as the Windows version so the frontend
+/// never has to care which OS it is running on. evdev codes describe physical
+/// key positions, so the single-character fallback assumes a US layout —
+/// unlike VkKeyScanW there is no layout-aware lookup at this level.
+///
+/// Mouse buttons map to the kernel button codes:
+///   BTN_RIGHT  = 0x111  (right)
+///   BTN_MIDDLE = 0x112  (middle / mouse3)
+///   BTN_SIDE   = 0x113  (mouse4 / side-back)
+///   BTN_EXTRA  = 0x114  (mouse5 / side-forward)
+#[cfg(target_os = "linux")]
+fn token_to_vk(token: &str) -> Option<u32> {
+    use evdev::KeyCode as K;
+    let t = token.to_ascii_lowercase();
+    let t = t.trim();
+    let key = match t {
+        // ── Ctrl ──────────────────────────────────────────────────────────────
+        "ctrl" | "control" | "lctrl" | "lcontrol" | "controlleft" => K::KEY_LEFTCTRL,
+        "rctrl" | "rcontrol" | "controlright"                      => K::KEY_RIGHTCTRL,
+        // ── Shift ─────────────────────────────────────────────────────────────
+        "shift" | "lshift" | "shiftleft"                           => K::KEY_LEFTSHIFT,
+        "rshift" | "shiftright"                                    => K::KEY_RIGHTSHIFT,
+        // ── Alt ───────────────────────────────────────────────────────────────
+        "alt" | "lalt" | "lmenu" | "altleft" | "menu"             => K::KEY_LEFTALT,
+        "ralt" | "rmenu" | "altright" | "altgr"                    => K::KEY_RIGHTALT,
+        // ── Win / Super ───────────────────────────────────────────────────────
+        "super" | "meta" | "win" | "metaleft" | "osleft"           => K::KEY_LEFTMETA,
+        "metaright" | "osright"                                    => K::KEY_RIGHTMETA,
+        // ── Common keys ───────────────────────────────────────────────────────
+        "space"                                                    => K::KEY_SPACE,
+        "enter" | "return"                                         => K::KEY_ENTER,
+        "numpadenter"                                              => K::KEY_KPENTER,
+        "escape" | "esc"                                           => K::KEY_ESC,
+        "backspace"                                                => K::KEY_BACKSPACE,
+        "tab"                                                      => K::KEY_TAB,
+        "delete" | "del"                                           => K::KEY_DELETE,
+        "insert" | "ins"                                           => K::KEY_INSERT,
+        "home"                                                     => K::KEY_HOME,
+        "end"                                                      => K::KEY_END,
+        "pageup"                                                   => K::KEY_PAGEUP,
+        "pagedown"                                                 => K::KEY_PAGEDOWN,
+        "arrowup"    | "up"                                        => K::KEY_UP,
+        "arrowdown"  | "down"                                      => K::KEY_DOWN,
+        "arrowleft"  | "left"                                      => K::KEY_LEFT,
+        "arrowright" | "right"                                     => K::KEY_RIGHT,
+        "capslock"                                                 => K::KEY_CAPSLOCK,
+        // ── Function keys ─────────────────────────────────────────────────────
+        "f1"  => K::KEY_F1,  "f2"  => K::KEY_F2,
+        "f3"  => K::KEY_F3,  "f4"  => K::KEY_F4,
+        "f5"  => K::KEY_F5,  "f6"  => K::KEY_F6,
+        "f7"  => K::KEY_F7,  "f8"  => K::KEY_F8,
+        "f9"  => K::KEY_F9,  "f10" => K::KEY_F10,
+        "f11" => K::KEY_F11, "f12" => K::KEY_F12,
+        // ── Misc ──────────────────────────────────────────────────────────────
+        "print" | "printscreen"                                    => K::KEY_SYSRQ,
+        "scrolllock"                                               => K::KEY_SCROLLLOCK,
+        "pause"                                                    => K::KEY_PAUSE,
+        "numlock"                                                  => K::KEY_NUMLOCK,
+        // ── Mouse buttons ─────────────────────────────────────────────────────
+        "mouse2" | "mouseright"  | "rightbutton"                   => K::BTN_RIGHT,
+        "mouse3" | "mousemiddle" | "middlebutton"                  => K::BTN_MIDDLE,
+        "mouse4" | "xbutton1"                                      => K::BTN_SIDE,
+        "mouse5" | "xbutton2"                                      => K::BTN_EXTRA,
+        // ── Single-character fallback (US physical layout) ────────────────────
+        "a" => K::KEY_A, "b" => K::KEY_B, "c" => K::KEY_C, "d" => K::KEY_D,
+        "e" => K::KEY_E, "f" => K::KEY_F, "g" => K::KEY_G, "h" => K::KEY_H,
+        "i" => K::KEY_I, "j" => K::KEY_J, "k" => K::KEY_K, "l" => K::KEY_L,
+        "m" => K::KEY_M, "n" => K::KEY_N, "o" => K::KEY_O, "p" => K::KEY_P,
+        "q" => K::KEY_Q, "r" => K::KEY_R, "s" => K::KEY_S, "t" => K::KEY_T,
+        "u" => K::KEY_U, "v" => K::KEY_V, "w" => K::KEY_W, "x" => K::KEY_X,
+        "y" => K::KEY_Y, "z" => K::KEY_Z,
+        "0" => K::KEY_0, "1" => K::KEY_1, "2" => K::KEY_2, "3" => K::KEY_3,
+        "4" => K::KEY_4, "5" => K::KEY_5, "6" => K::KEY_6, "7" => K::KEY_7,
+        "8" => K::KEY_8, "9" => K::KEY_9,
+        "-" => K::KEY_MINUS,      "=" => K::KEY_EQUAL,
+        "[" => K::KEY_LEFTBRACE,  "]" => K::KEY_RIGHTBRACE,
+        ";" => K::KEY_SEMICOLON,  "'" => K::KEY_APOSTROPHE,
+        "`" => K::KEY_GRAVE,      "\\" => K::KEY_BACKSLASH,
+        "," => K::KEY_COMMA,      "." => K::KEY_DOT,
+        "/" => K::KEY_SLASH,
+        _ => return None,
+    };
+    Some(key.code() as u32)
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+fn token_to_vk(_token: &str) -> Option<u32> { None }
+
+// ── Key-state readers ────────────────────────────────────────────────────────
+//
+// Each platform provides a `PttKeyReader` that answers one question per poll
+// tick: "for each bound chord, are ALL of its codes held right now?". The poll
+// loop itself lives in `start_ptt_hook` and is shared by every platform.
+
+/// Windows: GetAsyncKeyState reads raw hardware key state — no setup needed.
+#[cfg(windows)]
+struct PttKeyReader;
+
+#[cfg(windows)]
+impl PttKeyReader {
+    fn open(_vks: &[u32]) -> Result<Self, String> {
+        Ok(Self)
+    }
+
+    fn chords_down(&mut self, chords: &[Vec<u32>]) -> Vec<bool> {
+        // We call GetAsyncKeyState ONLY for the keys in the chords — the
+        // user-assigned PTT keys — and do nothing with the result except check
+        // whether ALL of a chord's keys are simultaneously held (bit 15 = down).
+        chords
+            .iter()
+            .map(|vks| {
+                vks.iter().all(|&vk| {
+                    (unsafe {
+                        windows_sys::Win32::UI::Input::KeyboardAndMouse::GetAsyncKeyState(vk as i32)
+                    } as u16) & 0x8000 != 0
+                })
+            })
+            .collect()
+    }
+}
+
+/// Linux: read key/button state straight from the kernel via evdev
+/// (EVIOCGKEY), which works under X11, Wayland and on a bare TTY alike.
+///
+/// Reading /dev/input/event* requires membership of the `input` group. When
+/// no device is readable we fall back to X11: the core QueryKeymap request for
+/// keyboard keys and XInput2's XIQueryPointer for mouse buttons (the core
+/// pointer mask has no bits for the side buttons). X11 keycodes are evdev
+/// codes offset by 8, so both paths share the codes from `token_to_vk`.
+#[cfg(target_os = "linux")]
+enum PttKeyReader {
+    Evdev(EvdevDevices),
+    X11 {
+        conn: Box<x11rb::rust_connection::RustConnection>,
+        root: u32,
+        pointer: u16,
+    },
+}
+
+#[cfg(target_os = "linux")]
+impl PttKeyReader {
+    /// Open every input device that can produce at least one of `codes`, so
+    /// keyboards are never opened for a mouse-only bind and vice versa.
+    /// uinput virtual devices show up here like any other device.
+    fn open(codes: &[u32]) -> Result<Self, String> {
+        let devices = EvdevDevices::open(codes);
+        if !devices.devices.is_empty() {
+            return Ok(Self::Evdev(devices));
+        }
+
+        Self::open_x11().map_err(|e| {
+            format!(
+                "No readable input devices in /dev/input (is your user in the \
+                 'input' group?) and the X11 fallback is unavailable: {e}"
+            )
+        })
+    }
+
+    fn open_x11() -> Result<Self, String> {
+        use x11rb::connection::Connection;
+        use x11rb::protocol::xinput::ConnectionExt as _;
+
+        let (conn, screen_num) = x11rb::connect(None).map_err(|e| e.to_string())?;
+        let root = conn.setup().roots[screen_num].root;
+
+        conn.xinput_xi_query_version(2, 0)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| format!("XInput2 not available: {e}"))?;
+
+        let pointer = conn
+            .xinput_xi_get_client_pointer(x11rb::NONE)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| e.to_string())?
+            .deviceid;
+
+        Ok(Self::X11 { conn: Box::new(conn), root, pointer })
+    }
+
+    fn chords_down(&mut self, chords: &[Vec<u32>]) -> Vec<bool> {
+        match self {
+            Self::Evdev(devices) => {
+                devices.refresh();
+                // One EVIOCGKEY per device per tick, shared by every chord. A
+                // device that errors (e.g. unplugged) contributes no pressed
+                // keys until the next refresh drops it.
+                let states: Vec<_> = devices
+                    .devices
+                    .iter()
+                    .filter_map(|d| d.get_key_state().ok())
+                    .collect();
+                let is_down = |c: u32| {
+                    let key = evdev::KeyCode::new(c as u16);
+                    states.iter().any(|s| s.contains(key))
+                };
+                chords.iter().map(|codes| codes.iter().all(|&c| is_down(c))).collect()
+            }
+            Self::X11 { conn, root, pointer } => {
+                use x11rb::protocol::xinput::ConnectionExt as _;
+                use x11rb::protocol::xproto::ConnectionExt as _;
+
+                let Some(keymap) = conn.query_keymap().ok().and_then(|c| c.reply().ok()) else {
+                    return vec![false; chords.len()];
+                };
+                let buttons = conn
+                    .xinput_xi_query_pointer(*root, *pointer)
+                    .ok()
+                    .and_then(|c| c.reply().ok())
+                    .map(|r| r.buttons)
+                    .unwrap_or_default();
+
+                let is_down = |c: u32| match linux_code_to_x11_button(c) {
+                    Some(b) => buttons
+                        .get(b as usize / 32)
+                        .is_some_and(|w| w & (1 << (b % 32)) != 0),
+                    None => {
+                        let kc = c as usize + 8;
+                        kc < 256 && keymap.keys[kc / 8] & (1 << (kc % 8)) != 0
+                    }
+                };
+                chords.iter().map(|codes| codes.iter().all(|&c| is_down(c))).collect()
+            }
+        }
+    }
+}
+
+/// How often `EvdevDevices` looks for devices being plugged in or removed.
+#[cfg(target_os = "linux")]
+const PTT_DEVICE_RESCAN: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// The evdev devices a PTT worker reads. Hotplug is picked up by comparing
+/// the /dev/input event nodes against the last look: listing the directory
+/// is cheap, so it is done every `PTT_DEVICE_RESCAN`, and the devices are
+/// only reopened when a node came, went or changed. A node's ctime is part
+/// of the comparison because udev grants the `input` group access just
+/// after the node appears.
+#[cfg(target_os = "linux")]
+struct EvdevDevices {
+    codes: Vec<u32>,
+    devices: Vec<evdev::Device>,
+    /// Event node names and ctimes that `devices` was opened from.
+    nodes: std::collections::BTreeMap<std::ffi::OsString, (i64, i64)>,
+    checked: std::time::Instant,
+}
+
+#[cfg(target_os = "linux")]
+impl EvdevDevices {
+    fn open(codes: &[u32]) -> Self {
+        let nodes = input_event_nodes();
+        let devices = evdev::enumerate()
+            .map(|(_, dev)| dev)
+            .filter(|dev| {
+                dev.supported_keys().is_some_and(|keys| {
+                    codes.iter().any(|&c| keys.contains(evdev::KeyCode::new(c as u16)))
+                })
+            })
+            .collect();
+        Self { codes: codes.to_vec(), devices, nodes, checked: std::time::Instant::now() }
+    }
+
+    /// Reopen the devices if /dev/input changed since the last look.
+    fn refresh(&mut self) {
+        if self.checked.elapsed() < PTT_DEVICE_RESCAN {
+            return;
+        }
+        self.checked = std::time::Instant::now();
+        if input_event_nodes() != self.nodes {
+            let codes = std::mem::take(&mut self.codes);
+            *self = Self::open(&codes);
+        }
+    }
+}
+
+/// The event* nodes in /dev/input with their ctime (seconds, nanoseconds).
+#[cfg(target_os = "linux")]
+fn input_event_nodes() -> std::collections::BTreeMap<std::ffi::OsString, (i64, i64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    std::fs::read_dir("/dev/input")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("event"))
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok()?;
+            Some((entry.file_name(), (meta.ctime(), meta.ctime_nsec())))
+        })
+        .collect()
+}
+
+/// Map an evdev BTN_* code to its X11 button number, or None for keys.
+#[cfg(target_os = "linux")]
+fn linux_code_to_x11_button(code: u32) -> Option<u32> {
+    match code {
+        0x110 => Some(1), // BTN_LEFT
+        0x111 => Some(3), // BTN_RIGHT
+        0x112 => Some(2), // BTN_MIDDLE
+        0x113 => Some(8), // BTN_SIDE
+        0x114 => Some(9), // BTN_EXTRA
+        _ => None,
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+struct PttKeyReader;
+
+#[cfg(not(any(windows, target_os = "linux")))]
+impl PttKeyReader {
+    fn open(_codes: &[u32]) -> Result<Self, String> {
+        Err("Push-to-talk is not supported on this platform".into())
+    }
+
+    fn chords_down(&mut self, chords: &[Vec<u32>]) -> Vec<bool> {
+        vec![false; chords.len()]
+    }
+}
+
+// ── Voice activity ───────────────────────────────────────────────────────────
+//
+// Voice-activity mode captures the default input device natively (cpal) and
+// runs each buffer through `VadGate`. Transitions are forwarded to the owning
+// thread, which applies them exactly like a key press/release would.
+
+/// Energy-based voice-activity gate with attack and release hangover.
+///
+/// Kept free of any audio API so it can be driven with synthetic buffers.
+pub struct VadGate {
+    /// Linear RMS threshold derived from `VadConfig::threshold_db`.
+    threshold: f32,
+    attack: std::time::Duration,
+    release: std::time::Duration,
+    open: bool,
+    /// How long the signal has continuously disagreed with `open`.
+    pending: std::time::Duration,
+}
+
+impl VadGate {
+    pub fn new(cfg: &VadConfig) -> Self {
+        Self {
+            threshold: 10f32.powf(cfg.threshold_db / 20.0),
+            attack: std::time::Duration::from_millis(cfg.attack_ms as u64),
+            release: std::time::Duration::from_millis(cfg.release_ms as u64),
+            open: false,
+            pending: std::time::Duration::ZERO,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Feed one buffer of interleaved samples in [-1.0, 1.0]. Returns the new
+    /// state when the gate opens or closes, None otherwise.
+    pub fn feed(&mut self, samples: &[f32], channels: u16, sample_rate: u32) -> Option<bool> {
+        if samples.is_empty() || sample_rate == 0 {
+            return None;
+        }
+        let frames = samples.len() / channels.max(1) as usize;
+        let duration = std::time::Duration::from_secs_f64(frames as f64 / sample_rate as f64);
+
+        let above = rms(samples) >= self.threshold;
+        if above == self.open {
+            self.pending = std::time::Duration::ZERO;
+            return None;
+        }
+
+        self.pending += duration;
+        let needed = if self.open { self.release } else { self.attack };
+        if self.pending < needed {
+            return None;
+        }
+        self.open = above;
+        self.pending = std::time::Duration::ZERO;
+        Some(above)
+    }
+}
+
+/// Root-mean-square level of a buffer, linear (1.0 = full scale).
+pub fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum: f32 = samples.iter().map(|s| s * s).sum();
+    (sum / samples.len() as f32).sqrt()
+}
+
+/// Build a cpal input stream that converts any sample format to f32 and
+/// forwards gate transitions to `tx`.
+fn build_vad_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mut gate: VadGate,
+    tx: std::sync::mpsc::Sender<bool>,
+) -> Result<cpal::Stream, String>
+where
+    T: cpal::SizedSample,
+    f32: cpal::FromSample<T>,
+{
+    use cpal::traits::DeviceTrait;
+
+    let channels = config.channels;
+    let rate = config.sample_rate.0;
+    let mut buf: Vec<f32> = Vec::new();
+
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                buf.clear();
+                buf.extend(data.iter().map(|s| s.to_sample::<f32>()));
+                if let Some(open) = gate.feed(&buf, channels, rate) {
+                    let _ = tx.send(open);
+                }
+            },
+            |e| log::error!("VAD capture error: {e}"),
+            None,
+        )
+        .map_err(|e| format!("Failed to open microphone for voice activity: {e}"))
+}
+
+/// Spawn the voice-activity thread for one PTT scope. It owns the capture
+/// stream (cpal streams must stay on the thread that created them) and is
+/// stopped through the returned `PttWorker`, exactly like `start_ptt_hook`.
+///
+/// Audio never leaves this thread: each buffer is reduced to one RMS value
+/// and discarded.
+fn start_vad_gate<R: Runtime>(
+    app: AppHandle<R>,
+    scope: String,
+    target: PttTarget,
+    vad: VadConfig,
+    cues: bool,
+) -> Result<PttWorker, String> {
+    use std::sync::mpsc;
+
+    let (stop_tx, stop_rx) = mpsc::channel::<()>();
+    // Reports whether the stream opened, so failures reach the caller.
+    let (ready_tx, ready_rx) = mpsc::channel::<Result<(), String>>();
+
+    let thread = std::thread::Builder::new()
+        .name(format!("sweetshark-vad-{scope}"))
+        .spawn(move || {
+            use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+            let (gate_tx, gate_rx) = mpsc::channel::<bool>();
+            let stream = (|| {
+                let device = cpal::default_host()
+                    .default_input_device()
+                    .ok_or("No default input device")?;
+                let supported = device.default_input_config().map_err(|e| e.to_string())?;
+                let config = supported.config();
+                let gate = VadGate::new(&vad);
+                let stream = match supported.sample_format() {
+                    cpal::SampleFormat::F32 => build_vad_stream::<f32>(&device, &config, gate, gate_tx)?,
+                    cpal::SampleFormat::I16 => build_vad_stream::<i16>(&device, &config, gate, gate_tx)?,
+                    cpal::SampleFormat::U16 => build_vad_stream::<u16>(&device, &config, gate, gate_tx)?,
+                    cpal::SampleFormat::I32 => build_vad_stream::<i32>(&device, &config, gate, gate_tx)?,
+                    other => return Err(format!("Unsupported sample format: {other:?}")),
+                };
+                stream.play().map_err(|e| e.to_string())?;
+                Ok::<_, String>(stream)
+            })();
+
+            let stream = match stream {
+                Ok(s) => {
+                    let _ = ready_tx.send(Ok(()));
+                    s
+                }
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            };
+
+            let mut was_active = false;
+            loop {
+                if stop_rx.try_recv().is_ok() {
+                    drop(stream);
+                    if was_active {
+                        // Tracks are re-gated by set_ptt_config itself.
+                        let _ = app.emit("ptt://released", PttEvent::new(&scope, None, true));
+                    }
+                    break;
+                }
+                match gate_rx.recv_timeout(std::time::Duration::from_millis(20)) {
+                    Ok(open) if open != was_active => {
+                        was_active = open;
+                        let applied = ptt_apply(&app, &scope, target, open).is_ok();
+                        if cues {
+                            play_cue(open);
+                        }
+                        let event = if open { "ptt://pressed" } else { "ptt://released" };
+                        let _ = app.emit(event, PttEvent::new(&scope, None, applied));
+                    }
+                    _ => {}
+                }
+            }
+        })
+        .expect("failed to spawn VAD thread");
+
+    ready_rx
+        .recv()
+        .map_err(|_| "Voice activity thread exited unexpectedly".to_string())??;
+    Ok(PttWorker { stop_tx, thread })
+}
+
+// ── Audible cues ─────────────────────────────────────────────────────────────
+//
+// Optional short tones played when the mic opens and closes, so users hear
+// that they are live without looking at the sidebar. The WAVs are compiled
+// into the binary and decoded once with hound; each cue plays on a throwaway
+// cpal output stream on its own thread so the poll loop never waits on audio.
+
+const CUE_ON_WAV: &[u8] = include_bytes!("../sounds/ptt-on.wav");
+const CUE_OFF_WAV: &[u8] = include_bytes!("../sounds/ptt-off.wav");
+
+/// A decoded cue, downmixed to mono.
+struct Cue {
+    samples: Vec<f32>,
+    sample_rate: u32,
+}
+
+fn decode_cue(bytes: &[u8]) -> Result<Cue, String> {
+    let mut reader = hound::WavReader::new(std::io::Cursor::new(bytes)).map_err(|e| e.to_string())?;
+    let spec = reader.spec();
+    let interleaved: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>(),
+        hound::SampleFormat::Int => {
+            let scale = 1.0 / (1u64 << (spec.bits_per_sample - 1)) as f32;
+            reader.samples::<i32>().map(|s| s.map(|v| v as f32 * scale)).collect()
+        }
+    }
+    .map_err(|e| e.to_string())?;
+
+    let channels = spec.channels.max(1) as usize;
+    let samples = interleaved
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+    Ok(Cue { samples, sample_rate: spec.sample_rate })
+}
+
+/// The activation (`open`) or deactivation cue, decoded on first use.
+fn cue(open: bool) -> Option<&'static Cue> {
+    static ON: std::sync::OnceLock<Option<Cue>> = std::sync::OnceLock::new();
+    static OFF: std::sync::OnceLock<Option<Cue>> = std::sync::OnceLock::new();
+    let (cell, bytes) = if open { (&ON, CUE_ON_WAV) } else { (&OFF, CUE_OFF_WAV) };
+    cell.get_or_init(|| {
+        decode_cue(bytes)
+            .map_err(|e| log::error!("Failed to decode PTT cue: {e}"))
+            .ok()
+    })
+    .as_ref()
+}
+
+/// Play a cue on the default output device. Fire-and-forget: failures are
+/// logged and never reach the PTT thread.
+fn play_cue(open: bool) {
+    let Some(cue) = cue(open) else { return };
+    let _ = std::thread::Builder::new()
+        .name("sweetshark-ptt-cue".into())
+        .spawn(move || {
+            if let Err(e) = play_cue_blocking(cue) {
+                log::warn!("PTT cue playback failed: {e}");
+            }
+        });
+}
+
+fn play_cue_blocking(cue: &'static Cue) -> Result<(), String> {
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+    let device = cpal::default_host()
+        .default_output_device()
+        .ok_or("No default output device")?;
+    let supported = device.default_output_config().map_err(|e| e.to_string())?;
+    let config = supported.config();
+    let stream = match supported.sample_format() {
+        cpal::SampleFormat::F32 => build_cue_stream::<f32>(&device, &config, cue)?,
+        cpal::SampleFormat::I16 => build_cue_stream::<i16>(&device, &config, cue)?,
+        cpal::SampleFormat::U16 => build_cue_stream::<u16>(&device, &config, cue)?,
+        cpal::SampleFormat::I32 => build_cue_stream::<i32>(&device, &config, cue)?,
+        other => return Err(format!("Unsupported sample format: {other:?}")),
+    };
+    stream.play().map_err(|e| e.to_string())?;
+
+    // The stream stops when dropped; leave a little slack for the device
+    // buffer to drain.
+    let length = std::time::Duration::from_secs_f64(cue.samples.len() as f64 / cue.sample_rate as f64);
+    std::thread::sleep(length + std::time::Duration::from_millis(60));
+    Ok(())
+}
+
+/// Build a cpal output stream that plays `cue` once, resampled to the
+/// device rate and copied to every channel, then outputs silence.
+fn build_cue_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    cue: &'static Cue,
+) -> Result<cpal::Stream, String>
+where
+    T: cpal::SizedSample + cpal::FromSample<f32>,
+{
+    use cpal::traits::DeviceTrait;
+
+    let channels = config.channels.max(1) as usize;
+    let step = cue.sample_rate as f64 / config.sample_rate.0 as f64;
+    let mut pos = 0f64;
+
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+                for frame in data.chunks_mut(channels) {
+                    // Nearest-neighbour resampling is plenty for a 90 ms blip.
+                    let s = cue.samples.get(pos as usize).copied().unwrap_or(0.0);
+                    pos += step;
+                    frame.fill(T::from_sample(s));
+                }
+            },
+            |e| log::warn!("PTT cue playback error: {e}"),
+            None,
+        )
+        .map_err(|e| format!("Failed to open output device for PTT cue: {e}"))
+}
+
+/// Mute or unmute the default Windows microphone at the OS/driver level using
+/// the Core Audio API (IAudioEndpointVolume). This is reliable regardless of
+/// how the web app (mediasoup/WebRTC) manages its audio pipeline internally.
+#[cfg(windows)]
+fn ptt_set_mic_mute(muted: bool) -> Result<(), String> {
+    use windows::{
+        Win32::Media::Audio::{eCapture, eConsole, IMMDeviceEnumerator, MMDeviceEnumerator, Endpoints::IAudioEndpointVolume},
+        Win32::System::Com::{CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_ALL, COINIT_APARTMENTTHREADED},
+        Win32::Foundation::BOOL,
+    };
+
+    unsafe {
+        let hr_init = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+        let com_inited = hr_init.is_ok() || hr_init.0 == 1; // S_OK or S_FALSE
+
+        // Every step can fail (COM unavailable, no capture device, driver
+        // gone); each one is reported so PTT never silently leaves the mic hot.
+        let result = (|| -> Result<(), String> {
+            let enumerator: IMMDeviceEnumerator =
+                CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)
+                    .map_err(|e| format!("Core Audio: device enumerator unavailable: {e}"))?;
+            let device = enumerator
+                .GetDefaultAudioEndpoint(eCapture, eConsole)
+                .map_err(|e| format!("Core Audio: no default capture device: {e}"))?;
+            let vol = device
+                .Activate::<IAudioEndpointVolume>(CLSCTX_ALL, None)
+                .map_err(|e| format!("Core Audio: endpoint volume unavailable: {e}"))?;
+            vol.SetMute(BOOL::from(muted), std::ptr::null())
+                .map_err(|e| format!("Core Audio: SetMute failed: {e}"))
+        })();
+
+        if com_inited { CoUninitialize(); }
+        result
+    }
+}
+
+/// Mute or unmute the default Linux capture source through the PulseAudio
+/// native protocol. PipeWire desktops accept the same commands via
+/// pipewire-pulse, so this covers both sound servers. Like the Windows version
+/// it acts on the source itself, so every app's view of the mic is gated.
+///
+/// `conn` is reused between calls. A request that fails on it (the sound
+/// server restarted, the socket timed out) is retried once on a fresh
+/// connection; a failure there is reported and leaves `conn` empty.
+#[cfg(target_os = "linux")]
+fn ptt_set_mic_mute(conn: &mut Option<pulse::Connection>, muted: bool) -> Result<(), String> {
+    if let Some(c) = conn.as_mut() {
+        if c.set_source_mute(pulse::DEFAULT_SOURCE, muted).is_ok() {
+            return Ok(());
+        }
+        *conn = None;
+    }
+    let mut fresh = pulse::Connection::connect()?;
+    fresh.set_source_mute(pulse::DEFAULT_SOURCE, muted)?;
+    *conn = Some(fresh);
+    Ok(())
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+fn ptt_set_mic_mute(_muted: bool) -> Result<(), String> {
+    Err("Muting the system microphone is not supported on this platform".into())
+}
+
+/// Open or close the microphone for one PTT scope.
+///
+/// `System` flips the OS default capture device. `Tracks` only touches the
+/// server webviews governed by `scope`: the server itself, or — for the
+/// all-servers binding — every server without an enabled binding of its own.
+///
+/// The outcome is recorded against `scope` and a failure is emitted as
+/// `ptt://error`, so callers only need the result for the event payload.
+fn ptt_apply<R: Runtime>(app: &AppHandle<R>, scope: &str, target: PttTarget, open: bool) -> Result<(), String> {
+    let result = match target {
+        PttTarget::System => return ptt_set_system_mic(app, scope, !open),
+        PttTarget::Tracks => {
+            let state = app.state::<SharedPttState>();
+            let locked = state.lock().unwrap();
+            let mut failed = Vec::new();
+            for (label, wv) in app.webviews() {
+                let Some(server_id) = label.strip_prefix(SERVER_WEBVIEW_PREFIX) else {
+                    continue;
+                };
+                if locked.scope_for(server_id) == Some(scope)
+                    && wv
+                        .eval(format!("window.__ssPtt && window.__ssPtt.setOpen({open})"))
+                        .is_err()
+                {
+                    failed.push(server_id.to_string());
+                }
+            }
+            if failed.is_empty() {
+                Ok(())
+            } else {
+                Err(format!("Failed to gate tracks for server(s): {}", failed.join(", ")))
+            }
+        }
+    };
+    ptt_report(app, scope, result)
+}
+
+/// Mute or unmute the OS mic through the configured `MicControl` backend on
+/// behalf of `scope`. The backend runs without the state lock held, so a slow
+/// sound server can't stall `set_ptt_config`.
+fn ptt_set_system_mic<R: Runtime>(app: &AppHandle<R>, scope: &str, muted: bool) -> Result<(), String> {
+    let mic = app.state::<SharedPttState>().lock().unwrap().mic.clone();
+    let result = mic.set_muted(muted);
+    ptt_report(app, scope, result)
+}
+
+/// Record a mute outcome for `scope` and emit `ptt://error` if it failed.
+fn ptt_report<R: Runtime>(app: &AppHandle<R>, scope: &str, result: Result<(), String>) -> Result<(), String> {
+    let result = app
+        .state::<SharedPttState>()
+        .lock()
+        .unwrap()
+        .record_result(scope, result);
+    if let Err(message) = &result {
+        log::error!("PTT [{scope}]: {message}");
+        let _ = app.emit(
+            "ptt://error",
+            PttErrorEvent { scope: scope.to_string(), message: message.clone(), timestamp_ms: monotonic_ms() },
+        );
+    }
+    result
+}
+
+/// Push the current gate state into every live server webview. Called after
+/// any config change so tracks are re-gated (or released) immediately rather
+/// than on the next page load.
+fn sync_webview_ptt_gates<R: Runtime>(app: &AppHandle<R>, state: &PttState) {
+    for (label, wv) in app.webviews() {
+        let Some(server_id) = label.strip_prefix(SERVER_WEBVIEW_PREFIX) else {
+            continue;
+        };
+        let gated = state.tracks_gated(server_id);
+        let open = state.tracks_initially_open(server_id);
+        let _ = wv.eval(format!(
+            "window.__ssPtt && (window.__ssPtt.setOpen({open}), window.__ssPtt.setGated({gated}))"
+        ));
+    }
+}
+
+/// Payload of the `ptt://pressed` / `ptt://released` events.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PttEvent {
+    /// Server id, or `PTT_ALL_SERVERS`.
+    pub scope: String,
+    /// Name of the binding that opened the mic; None for voice activity.
+    pub binding: Option<String>,
+    /// Milliseconds since process start, from a monotonic clock — safe to
+    /// diff between events, meaningless as a wall-clock time.
+    pub timestamp_ms: u64,
+    /// Whether the mute/unmute actually took effect (OS call or webview eval).
+    pub mute_applied: bool,
+}
+
+impl PttEvent {
+    pub fn new(scope: &str, binding: Option<String>, mute_applied: bool) -> Self {
+        Self { scope: scope.to_string(), binding, timestamp_ms: monotonic_ms(), mute_applied }
+    }
+}
+
+/// Payload of the `ptt://error` event, emitted whenever muting or unmuting
+/// fails — the mic may be live while the UI says otherwise.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PttErrorEvent {
+    /// Server id, or `PTT_ALL_SERVERS`.
+    pub scope: String,
+    pub message: String,
+    /// Same clock as `PttEvent::timestamp_ms`.
+    pub timestamp_ms: u64,
+}
+
+/// Monotonic milliseconds since the first call in this process.
+fn monotonic_ms() -> u64 {
+    static START: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+    START.get_or_init(std::time::Instant::now).elapsed().as_millis() as u64
+}
+
+/// Spawn a dedicated poll thread that watches ONLY the exact key codes in
+/// `chords` — nothing else is ever read.
+///
+/// Privacy guarantee:
+///   • This thread is started ONLY when PTT is enabled by the user.
+///   • It reads the state of the assigned key(s) only — no other keys, no
+///     text, no clipboard, no window titles.
+///   • `PttWorker::stop` on the returned worker stops the thread within one
+///     poll tick. The thread is also the only thing that owns the key list;
+///     when it exits the list is dropped and no key state is ever read again.
+///   • No data leaves the process: the only output is a
+///     "ptt://pressed" / "ptt://released" Tauri event naming the binding.
+fn start_ptt_hook<R: Runtime>(
+    app: AppHandle<R>,
+    scope: String,
+    config: PttConfig,
+    chords: Vec<(String, Vec<u32>)>,
+) -> Result<PttWorker, String> {
+    use std::sync::mpsc;
+
+    let (names, chords): (Vec<String>, Vec<Vec<u32>>) = chords.into_iter().unzip();
+
+    // Open the platform reader up front so a missing permission (e.g. no
+    // access to /dev/input on Linux) is reported to the caller instead of
+    // producing a thread that silently never fires.
+    let all_codes: Vec<u32> = chords.iter().flatten().copied().collect();
+    let open_keys = app.state::<SharedPttState>().lock().unwrap().open_keys;
+    let mut reader = open_keys(&all_codes)?;
+
+    let (stop_tx, stop_rx) = mpsc::channel::<()>();
+
+    let thread = std::thread::Builder::new()
+        // Named so it's identifiable in debuggers / task managers.
+        .name(format!("sweetshark-ptt-poll-{scope}"))
+        // Move the key lists into the thread — nothing outside the thread can
+        // access them once we hand them over.
+        .spawn(move || {
+            let target = config.target;
+            let mut machine = PttMachine::new(&config);
+            // The binding most recently seen held, reported on release too.
+            let mut binding: Option<String> = None;
+            let emit = |event: &str, binding: &Option<String>, applied: bool| {
+                let _ = app.emit(event, PttEvent::new(&scope, binding.clone(), applied));
+            };
+
+            // Push-to-mute starts live; open the mic and tell the sidebar
+            // straight away.
+            if machine.is_open() {
+                let applied = ptt_apply(&app, &scope, target, true).is_ok();
+                emit("ptt://pressed", &binding, applied);
+            }
+
+            loop {
+                // Exit as soon as the stop signal arrives.
+                if stop_rx.try_recv().is_ok() {
+                    if machine.is_open() {
+                        // The mic itself is left alone: whoever stopped us
+                        // settles it once we have exited (see
+                        // `apply_ptt_config`), so nothing can land after that.
+                        emit("ptt://released", &binding, true);
+                    }
+                    // chords are dropped here — no key state is read after this point.
+                    break;
+                }
+
+                // No keystroke data is stored, logged, or transmitted — the
+                // reader only reports, per binding, whether ALL its keys are held.
+                let down = reader.chords_down(&chords);
+                if let Some(i) = down.iter().position(|&d| d) {
+                    binding = Some(names[i].clone());
+                }
+
+                if let Some(open) = machine.update(down.contains(&true), std::time::Instant::now()) {
+                    // open → allow microphone audio through; closed → silence it.
+                    let applied = ptt_apply(&app, &scope, target, open).is_ok();
+                    if config.cues {
+                        play_cue(open);
+                    }
+                    emit(if open { "ptt://pressed" } else { "ptt://released" }, &binding, applied);
+                }
+
+                std::thread::sleep(std::time::Duration::from_millis(5));
+            }
+        })
+        .expect("failed to spawn PTT poll thread");
+
+    Ok(PttWorker { stop_tx, thread })
+}
+
+/// Start the worker thread that drives one PTT scope: a key poll thread for
+/// the key-driven modes, or a capture thread for voice activity. Returns None
+/// when the config has nothing to run (e.g. push-to-talk with no bindings).
+fn start_ptt_worker<R: Runtime>(
+    app: &AppHandle<R>,
+    scope: &str,
+    config: &PttConfig,
+) -> Option<Result<PttWorker, String>> {
+    match config.mode {
+        PttMode::VoiceActivity => {
+            // Voice activity measures the same mic it gates — muting it at the
+            // OS level would silence the very signal that should reopen it.
+            if config.target == PttTarget::System {
+                return Some(Err("Voice activity requires the \"tracks\" target".into()));
+            }
+            Some(start_vad_gate(app.clone(), scope.to_string(), config.target, config.vad, config.cues))
+        }
+        PttMode::PushToTalk | PttMode::Toggle | PttMode::PushToMute => {
+            let bindings: Vec<&PttBinding> =
+                config.bindings.iter().filter(|b| !b.keys.is_empty()).collect();
+            if bindings.is_empty() {
+                return None;
+            }
+            let mut chords = Vec::with_capacity(bindings.len());
+            for b in bindings {
+                let vks: Vec<u32> = b.keys.iter()
+                    .filter_map(|k| token_to_vk(k))
+                    .collect();
+                if vks.is_empty() {
+                    return Some(Err(format!(
+                        "No recognisable keys in binding \"{}\": {:?}", b.name, b.keys
+                    )));
+                }
+                chords.push((b.name.clone(), vks));
+            }
+            if config.target == PttTarget::System && config.mode != PttMode::PushToMute {
+                // PTT starting: the OS mic begins muted until a key is held.
+                // Push-to-mute starts live; its thread unmutes on startup.
+                // A failure is surfaced as ptt://error and a degraded status;
+                // the thread still starts so a recovered sound server works.
+                let _ = ptt_set_system_mic(app, scope, true);
+            }
+            Some(start_ptt_hook(app.clone(), scope.to_string(), config.clone(), chords))
+        }
+    }
+}
+
+/// Enable or disable PTT for one server, or for all servers when `server_id`
+/// is omitted.
+///
+/// A config with a blank or repeated binding name is refused and leaves the
+/// running one in place.
+///
+/// When `config.enabled` is false (or nothing is bound) that scope's worker
+/// thread is stopped immediately and no key state is read for it until the
+/// user explicitly re-enables PTT. This is the sole entry-point that controls
+/// whether worker threads exist at all.
+#[tauri::command]
+async fn set_ptt_config(app: AppHandle, server_id: Option<ServerId>, config: PttConfig) -> Result<(), String> {
+    config.validate()?;
+    let scope = server_id.map_or_else(|| PTT_ALL_SERVERS.to_string(), |id| id.to_string());
+    let result = apply_ptt_config(&app, scope, config);
+    recheck_shortcuts(&app);
+    refresh_tray_menu(&app);
+    result
+}
+
+/// `set_ptt_config` for callers in Rust (the tray and shortcuts). Waits for
+/// the scope's old worker to exit, so call it off the main thread.
+fn apply_ptt_config<R: Runtime>(app: &AppHandle<R>, scope: String, config: PttConfig) -> Result<(), String> {
+    // One change at a time, so two callers can't both start a worker for a
+    // scope. The state lock itself is released while the old worker exits:
+    // it takes that lock to apply its last transition.
+    static SERIAL: Mutex<()> = Mutex::new(());
+    let _serial = SERIAL.lock().unwrap();
+
+    let state = app.state::<SharedPttState>();
+
+    // Always stop this scope's thread first — whether we're disabling, changing
+    // keys, or re-enabling. This guarantees at most one thread per scope, and
+    // waiting for it to exit means it can't touch the mic after the final
+    // state is settled below.
+    let previous_worker = state.lock().unwrap().workers.remove(&scope);
+    if let Some(worker) = previous_worker {
+        worker.stop();
+    }
+    let previous_target = {
+        let mut locked = state.lock().unwrap();
+        let previous = locked
+            .configs
+            .get(&scope)
+            .filter(|c| c.enabled)
+            .map(|c| c.target);
+        locked.configs.insert(scope.clone(), config.clone());
+        // A new config starts from a clean slate; the worker re-reports
+        // anything that still fails.
+        locked.errors.remove(&scope);
+        previous
+    };
+
+    // Only start a thread when PTT is explicitly enabled with something to
+    // watch. If disabled, nothing runs — no key state is ever polled.
+    let started = if config.enabled {
+        start_ptt_worker(app, &scope, &config)
+    } else {
+        None
+    };
+
+    let mut locked = state.lock().unwrap();
+    let result = match started {
+        Some(Ok(worker)) => {
+            locked.workers.insert(scope.clone(), worker);
+            Ok(())
+        }
+        Some(Err(e)) => {
+            // Keep the stored config honest: nothing is running for it.
+            if let Some(c) = locked.configs.get_mut(&scope) {
+                c.enabled = false;
+            }
+            Err(e)
+        }
+        None => Ok(()),
+    };
+
+    // Restore open-mic behaviour if this scope used to hold the OS mic and no
+    // running scope does any more.
+    let holds_system_mic = locked
+        .configs
+        .iter()
+        .any(|(k, c)| c.enabled && c.target == PttTarget::System && locked.workers.contains_key(k));
+    sync_webview_ptt_gates(app, &locked);
+    drop(locked);
+    if previous_target == Some(PttTarget::System) && !holds_system_mic {
+        // Failure is reported through ptt://error; the config change itself
+        // has already taken effect.
+        let _ = ptt_set_system_mic(app, &scope, false);
+    }
+
+    result
+}
+
+/// Stop and forget a server's own PTT binding once the server is removed.
+fn remove_ptt_scope(app: &AppHandle, scope: &str) {
+    let _ = apply_ptt_config(app, scope.to_string(), PttConfig::default());
+    app.state::<SharedPttState>().lock().unwrap().configs.remove(scope);
+    recheck_shortcuts(app);
+    refresh_tray_menu(app);
+}
+
+/// Store key of the all-servers PTT settings (per-server ones are under
+/// "pttServerConfigs"). The frontend owns that entry (see SettingsPage.tsx);
+/// Rust only ever flips its `enabled` flag.
+const PTT_CONFIG_KEY: &str = "pttConfig";
+
+/// Payload of `ptt://enabled`, emitted when PTT is switched on or off from
+/// outside the settings page.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PttEnabledEvent {
+    /// Server id, or `PTT_ALL_SERVERS`.
+    pub scope: String,
+    pub enabled: bool,
+}
+
+/// Switch the all-servers PTT binding on or off, keeping its keys and mode,
+/// and save that like the settings page would. Returns the new state.
+fn toggle_ptt_enabled(app: &AppHandle) -> Result<bool, String> {
+    use tauri_plugin_store::StoreExt;
+
+    let state = app.state::<SharedPttState>();
+    let config = state
+        .lock()
+        .unwrap()
+        .configs
+        .get(PTT_ALL_SERVERS)
+        .filter(|c| c.has_trigger())
+        .cloned()
+        .ok_or("Push-to-talk has no binding yet; set one up in Settings")?;
+    let result = apply_ptt_config(app, PTT_ALL_SERVERS.to_string(), PttConfig { enabled: !config.enabled, ..config });
+    // A failed start leaves it disabled, and that is what gets saved.
+    let enabled = state.lock().unwrap().configs.get(PTT_ALL_SERVERS).is_some_and(|c| c.enabled);
+
+    let store = app.store(CONFIG_STORE).map_err(|e| e.to_string())?;
+    if let Some(mut saved) = store.get(PTT_CONFIG_KEY).filter(serde_json::Value::is_object) {
+        saved["enabled"] = serde_json::Value::Bool(enabled);
+        store.set(PTT_CONFIG_KEY, saved);
+        store.save().map_err(|e| e.to_string())?;
+    }
+    let _ = app.emit("ptt://enabled", PttEnabledEvent { scope: PTT_ALL_SERVERS.to_string(), enabled });
+    recheck_shortcuts(app);
+    refresh_tray_menu(app);
+    result.map(|()| enabled)
+}
+
+/// `toggle_ptt_enabled` off the calling thread, for the tray and shortcuts:
+/// switching may wait on the sound server.
+fn spawn_ptt_toggle(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = toggle_ptt_enabled(&app) {
+            log::error!("Failed to toggle push-to-talk: {e}");
+            // Undo the check mark the tray click toggled.
+            refresh_tray_menu(&app);
+        }
+    });
+}
+
+/// Reports whether a PTT worker thread is running — for `server_id` when
+/// given, otherwise for any scope — and whether the last mute call failed.
+/// The frontend can expose this to users as proof that no key polling
+/// is happening when PTT is disabled, and warn when PTT is degraded.
+#[tauri::command]
+fn get_ptt_active(state: tauri::State<'_, SharedPttState>, server_id: Option<ServerId>) -> PttStatus {
+    state.lock().unwrap().status(server_id.as_ref().map(ServerId::as_str))
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let ptt_state: SharedPttState = Arc::new(Mutex::new(PttState {
-        config: PttConfig::default(),
-        stop_tx: None,
-    }));
+    let ptt_state: SharedPttState = Arc::new(Mutex::new(PttState::default()));
 
     tauri::Builder::default()
         .manage(ptt_state)
+        .manage(DeletionJobs::default())
+        .manage(SharedBadgeState::default())
+        .manage(SharedVoiceState::default())
+        .manage(SharedShortcuts::default())
+        // First, so the other plugins and `setup` can already log. Our own
+        // records down to info (debug in dev builds); dependencies only warn.
+        .plugin(
+            tauri_plugin_log::Builder::new()
+                .targets([
+                    tauri_plugin_log::Target::new(tauri_plugin_log::TargetKind::Stderr),
+                    tauri_plugin_log::Target::new(tauri_plugin_log::TargetKind::LogDir { file_name: None }),
+                ])
+                .level(log::LevelFilter::Warn)
+                .level_for(
+                    module_path!(),
+                    if cfg!(debug_assertions) { log::LevelFilter::Debug } else { log::LevelFilter::Info },
+                )
+                .build(),
+        )
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_store::Builder::new().build())
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(server_bridge_plugin())
+        .register_uri_scheme_protocol("server-icon", |ctx, request| {
+            server_icon_protocol(ctx.app_handle(), &request)
+        })
+        .setup(|app| {
+            // Plugins are initialised by now, so the store is available.
+            let registry = ServerRegistry::load(app.handle()).unwrap_or_else(|e| {
+                log::error!("Failed to load server list; not saving changes to it: {e}");
+                ServerRegistry::unavailable()
+            });
+            app.manage::<SharedServerRegistry>(Arc::new(Mutex::new(registry)));
+            let notifications = NotificationSettings::load(app.handle());
+            app.manage::<SharedNotificationSettings>(Arc::new(Mutex::new(notifications)));
+            app.manage::<SharedTraySettings>(Arc::new(Mutex::new(TraySettings::load(app.handle()))));
+            if let Err(e) = resume_tombstones(app.handle()) {
+                log::warn!("Failed to resume server data deletion: {e}");
+            }
+            if let Err(e) = build_tray(app.handle()) {
+                log::error!("Failed to create tray icon: {e}");
+            }
+            let shortcuts = load_shortcuts(app.handle());
+            for status in register_shortcuts(app.handle(), shortcuts) {
+                if let Some(e) = status.error {
+                    log::warn!("Shortcut for {} not active: {e}", status.binding.action);
+                }
+            }
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            if window.label() != "main" {
+                return;
+            }
+            match event {
+                tauri::WindowEvent::Focused(focused) => {
+                    update_badges(window.app_handle(), |state| state.set_window_focused(*focused));
+                }
+                // Hiding keeps the window — and the server webviews inside
+                // it — alive; Quit in the tray menu ends the app.
+                tauri::WindowEvent::CloseRequested { api, .. } if hides_on_close(window.app_handle()) => {
+                    api.prevent_close();
+                    let _ = window.hide();
+                }
+                _ => {}
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             greet,
             get_app_version,
+            list_servers,
+            add_server,
+            update_server,
+            remove_server,
+            get_storage_usage,
+            find_orphaned_server_data,
+            resolve_orphaned_server_data,
+            list_audio_devices,
             check_for_update,
-            open_url,
+            navigation::open_url,
             create_server_webview,
             reload_server_webview,
             delete_server_data,
+            cancel_server_data_deletion,
+            clear_server_data,
+            probe::probe_server,
+            fetch_server_metadata,
+            pinning::trust_server_certificate,
+            proxy::set_server_proxy,
+            navigation::set_server_in_app_domains,
+            notifications::get_notification_settings,
+            notifications::set_notification_settings,
+            notifications::set_server_notifications_muted,
+            badges::get_badges,
+            badges::set_viewed_server,
+            get_voice_state,
+            set_voice_state,
+            tray::get_tray_settings,
+            tray::set_tray_settings,
+            shortcuts::get_shortcuts,
+            shortcuts::set_shortcuts,
             get_ptt_config,
             set_ptt_config,
             get_ptt_active,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application")
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, _event| {
+            // Clicking the dock icon of a window hidden to the tray.
+            #[cfg(target_os = "macos")]
+            if let tauri::RunEvent::Reopen { has_visible_windows: false, .. } = _event {
+                tray::show_main_window(_app);
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── Server ids ───────────────────────────────────────────────────────────
+
+    pub(crate) const ID: &str = "3f2b8c1e-9d4a-4e6b-8a7c-0123456789ab";
+
+    #[test]
+    fn server_id_accepts_lowercase_uuids() {
+        let id = ServerId::parse(ID).unwrap();
+        assert_eq!(id.as_str(), ID);
+        assert_eq!(id.webview_label(), format!("srv-{ID}"));
+        assert_eq!(ServerId::from_webview_label(&id.webview_label()).unwrap(), id);
+        assert_eq!(id.to_bytes()[..2], [0x3f, 0x2b]);
+        assert_eq!(id.to_bytes()[15], 0xab);
+    }
+
+    #[test]
+    fn server_id_rejects_anything_else() {
+        for bad in [
+            "",
+            "../..",
+            "../../../etc/passwd",
+            "3f2b8c1e-9d4a-4e6b-8a7c-0123456789AB",
+            "3F2B8C1E-9D4A-4E6B-8A7C-0123456789AB",
+            "not-a-uuid",
+            "3f2b8c1e9d4a4e6b8a7c0123456789ab",
+            "3f2b8c1e-9d4a-4e6b-8a7c-0123456789a",
+            "3f2b8c1e-9d4a-4e6b-8a7c-0123456789abc",
+            "3f2b8c1e-9d4a-4e6b-8a7c_0123456789ab",
+            "/tmp/3f2b8c1e-9d4a-4e6b-8a7c-01234567",
+            "C:\\3f2b8c1e-9d4a-4e6b-8a7c-012345678",
+            "3f2b8c1e-9d4a-4e6b-8a7c-0123456789a\0",
+            "3f2b8c1e-9d4a-4e6b-8a7c-01234567/9ab",
+            "3f2b8c1e-9d4a-4e6b-8a7c-0123456789ä",
+        ] {
+            assert!(ServerId::parse(bad).is_err(), "accepted {bad:?}");
+        }
+        assert!(ServerId::from_webview_label(ID).is_err());
+        assert!(ServerId::from_webview_label("main").is_err());
+        // The IPC boundary goes through the same check.
+        assert!(serde_json::from_str::<ServerId>("\"../..\"").is_err());
+        assert!(serde_json::from_str::<ServerId>(&format!("\"{ID}\"")).is_ok());
+    }
+
+    #[test]
+    fn contained_server_dir_stays_under_root() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let id = ServerId::parse(ID).unwrap();
+
+        // Not created yet, then created: both resolve to <root>/<id>.
+        assert_eq!(contained_server_dir(&root, &id).unwrap(), root.join(ID));
+        std::fs::create_dir(root.join(ID)).unwrap();
+        assert_eq!(contained_server_dir(&root, &id).unwrap(), root.join(ID));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn contained_server_dir_refuses_a_symlink_escape() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("servers");
+        std::fs::create_dir(&root).unwrap();
+        let root = root.canonicalize().unwrap();
+        let outside = tmp.path().join("elsewhere");
+        std::fs::create_dir(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join(ID)).unwrap();
+
+        let err = contained_server_dir(&root, &ServerId::parse(ID).unwrap()).unwrap_err();
+        assert!(err.contains("escapes"), "{err}");
+
+        // A link that stays inside the root but isn't a direct child of it
+        // is refused too.
+        let other = "0a1b2c3d-0000-4000-8000-000000000000";
+        std::fs::create_dir_all(root.join("nested").join(other)).unwrap();
+        std::os::unix::fs::symlink(root.join("nested").join(other), root.join(other)).unwrap();
+        assert!(contained_server_dir(&root, &ServerId::parse(other).unwrap()).is_err());
+    }
+
+    // ── Server registry ──────────────────────────────────────────────────────
+
+    fn stored_server(id: &str) -> serde_json::Value {
+        serde_json::json!({ "id": id, "name": id, "url": "https://example.com", "iconLetter": "S", "iconColor": "#000" })
+    }
+
+    #[test]
+    fn registry_migrates_unversioned_and_v1_lists() {
+        let mut inline = stored_server(ID);
+        inline["iconUrl"] = "data:image/png;base64,AAAA".into();
+        for version in [None, Some(0), Some(1)] {
+            let registry = ServerRegistry::from_stored(version, Some(serde_json::json!([inline, stored_server("0a1b2c3d-0000-4000-8000-000000000000")])));
+            assert!(!registry.read_only && registry.is_complete(), "{version:?}");
+            assert_eq!(registry.list().len(), 2);
+            // Left inline for `load` to move into the icon file.
+            assert_eq!(registry.list()[0].icon_url.as_deref(), Some("data:image/png;base64,AAAA"));
+            assert_eq!(registry.stored_value().unwrap()[0]["id"], ID);
+        }
+        // Nothing stored yet is an empty list, not a broken one.
+        for servers in [None, Some(serde_json::Value::Null)] {
+            let registry = ServerRegistry::from_stored(None, servers);
+            assert!(registry.list().is_empty() && !registry.read_only && registry.is_complete());
+        }
+    }
+
+    #[test]
+    fn registry_from_a_newer_schema_is_read_only() {
+        let mut entry = stored_server(ID);
+        entry["addedInV9"] = true.into();
+        let registry = ServerRegistry::from_stored(Some(SERVERS_SCHEMA_VERSION + 1), Some(serde_json::json!([entry])));
+        assert!(registry.read_only);
+        assert!(registry.is_complete());
+        assert_eq!(registry.list().len(), 1);
+
+        assert!(!ServerRegistry::from_stored(Some(SERVERS_SCHEMA_VERSION), Some(serde_json::json!([]))).read_only);
+    }
+
+    #[test]
+    fn registry_drops_duplicates_and_keeps_unreadable_entries() {
+        let other = "0a1b2c3d-0000-4000-8000-000000000000";
+        let mut renamed = stored_server(ID);
+        renamed["name"] = "second copy".into();
+        let bad_id = stored_server("../..");
+        let no_url = serde_json::json!({ "id": other, "name": "no url" });
+        let registry = ServerRegistry::from_stored(
+            Some(SERVERS_SCHEMA_VERSION),
+            Some(serde_json::json!([stored_server(ID), bad_id, renamed, no_url, "junk"])),
+        );
+
+        // The first copy of a duplicate wins.
+        assert_eq!(registry.list().len(), 1);
+        assert_eq!(registry.list()[0].name, ID);
+        // Unreadable entries are written back verbatim, after the servers,
+        // and make the list too incomplete for the orphan sweep.
+        assert!(!registry.is_complete());
+        assert!(!registry.read_only);
+        assert_eq!(
+            registry.stored_value().unwrap(),
+            serde_json::json!([stored_server(ID), bad_id, no_url, "junk"])
+        );
+    }
+
+    #[test]
+    fn registry_that_is_not_a_list_is_never_saved() {
+        let registry = ServerRegistry::from_stored(Some(SERVERS_SCHEMA_VERSION), Some(serde_json::json!({ "id": ID })));
+        assert!(registry.read_only);
+        assert!(!registry.is_complete());
+        assert!(registry.list().is_empty());
+
+        let unavailable = ServerRegistry::unavailable();
+        assert!(unavailable.read_only && !unavailable.is_complete());
+    }
+
+    #[test]
+    fn registry_keeps_sidebar_order() {
+        let entries: Vec<serde_json::Value> = ["0a1b2c3d-0000-4000-8000-000000000000", ID, "0a1b2c3d-0000-4000-8000-000000000002"]
+            .iter()
+            .map(|id| stored_server(id))
+            .collect();
+        let mut registry = ServerRegistry::from_stored(Some(SERVERS_SCHEMA_VERSION), Some(entries.into()));
+        let order = |r: &ServerRegistry| r.list().iter().map(|s| s.id.to_string()).collect::<Vec<_>>();
+
+        let middle = registry.remove(&ServerId::parse(ID).unwrap()).unwrap();
+        registry.modify(&ServerId::parse("0a1b2c3d-0000-4000-8000-000000000002").unwrap(), |s| s.name = "renamed".into()).unwrap();
+        registry.add(middle).unwrap();
+        assert_eq!(
+            order(&registry),
+            ["0a1b2c3d-0000-4000-8000-000000000000", "0a1b2c3d-0000-4000-8000-000000000002", ID]
+        );
+    }
+
+    // ── Server metadata ──────────────────────────────────────────────────────
+
+    #[test]
+    fn html_tags_finds_whole_tags_only() {
+        let html = "<LINK rel=icon href=a.png><linkfoo rel=icon><link\nrel='manifest' href=\"m.json\"/><meta name=x>";
+        assert_eq!(html_tags(html, "link"), [" rel=icon href=a.png", "\nrel='manifest' href=\"m.json\"/"]);
+        assert_eq!(html_tags(html, "meta"), [" name=x"]);
+        assert!(html_tags("<link rel=icon", "link").is_empty());
+    }
+
+    #[test]
+    fn html_attr_reads_quoted_unquoted_and_escaped_values() {
+        let tag = " REL='Icon' data-href=no hreflang=en HREF = \"a.png?x=1&amp;y=2\" sizes=32x32";
+        assert_eq!(html_attr(tag, "rel").as_deref(), Some("Icon"));
+        assert_eq!(html_attr(tag, "href").as_deref(), Some("a.png?x=1&y=2"));
+        assert_eq!(html_attr(tag, "sizes").as_deref(), Some("32x32"));
+        assert_eq!(html_attr(tag, "type"), None);
+        // Unquoted paths keep their slashes; a trailing one closes the tag.
+        assert_eq!(html_attr(" href=/icons/a.png", "href").as_deref(), Some("/icons/a.png"));
+        assert_eq!(html_attr(" href=/icons/a.png/", "href").as_deref(), Some("/icons/a.png"));
+        assert_eq!(html_attr(" href=a.png type=image/png", "type").as_deref(), Some("image/png"));
+    }
+
+    #[test]
+    fn html_title_and_meta() {
+        let html = "<head><TITLE> Shark &amp; Co </TITLE>\
+            <meta property=\"og:site_name\" content=\"Shark Site\">\
+            <meta name=Application-Name content='Shark App'></head>";
+        assert_eq!(html_title(html).as_deref(), Some("Shark & Co"));
+        assert_eq!(html_title("<title>  </title>"), None);
+        assert_eq!(html_title("<title>never closed"), None);
+        assert_eq!(html_meta(html, "application-name").as_deref(), Some("Shark App"));
+        assert_eq!(html_meta(html, "og:site_name").as_deref(), Some("Shark Site"));
+        assert_eq!(html_meta(html, "description"), None);
+    }
+
+    #[test]
+    fn icon_size_hint_takes_the_largest_edge() {
+        assert_eq!(icon_size_hint(None), 0);
+        assert_eq!(icon_size_hint(Some("16x16 192X192 32x32")), 192);
+        assert_eq!(icon_size_hint(Some("32x32 any")), u32::MAX);
+        assert_eq!(icon_size_hint(Some("big")), 0);
+    }
+
+    #[test]
+    fn icon_candidates_rank_by_size_and_skip_what_cannot_be_used() {
+        let page = url::Url::parse("https://chat.example.com/app/").unwrap();
+        let manifest_url = url::Url::parse("https://chat.example.com/static/manifest.json").unwrap();
+        let manifest = serde_json::json!({ "icons": [
+            { "src": "icons/180.png", "sizes": "180x180" },
+            { "src": "icons/512.png", "sizes": "512x512", "type": "image/png" },
+            { "src": "icons/logo.svg", "sizes": "any" },
+        ]});
+        let html = r#"
+            <link rel="icon" href="favicon-32.png" sizes="32x32">
+            <link rel="apple-touch-icon" href="/apple.png" sizes="180x180">
+            <link rel="icon" type="image/svg+xml" href="logo">
+            <link rel="stylesheet" href="site.css">
+            <link rel="shortcut icon" href="javascript:alert(1)">
+            <link rel="icon" href="/favicon.ico">
+        "#;
+        let urls: Vec<String> = icon_candidates(&page, html, Some((&manifest_url, &manifest)))
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(urls, [
+            "https://chat.example.com/static/icons/512.png",
+            // Equal sizes: the manifest's comes first.
+            "https://chat.example.com/static/icons/180.png",
+            "https://chat.example.com/apple.png",
+            "https://chat.example.com/app/favicon-32.png",
+            "https://chat.example.com/favicon.ico",
+        ]);
+
+        // Nothing advertised: just the conventional favicon.
+        let urls = icon_candidates(&page, "<title>x</title>", None);
+        assert_eq!(urls, [url::Url::parse("https://chat.example.com/favicon.ico").unwrap()]);
+    }
+
+    // ── Storage ──────────────────────────────────────────────────────────────
+
+    #[test]
+    fn storage_usage_sorts_both_engines_layouts() {
+        for (rel, expected) in [
+            // WebView2
+            ("EBWebView/Default/Cache/Cache_Data/data_0", "cache"),
+            ("EBWebView/Default/Code Cache/js/index", "cache"),
+            ("EBWebView/Default/GPUCache/data_1", "cache"),
+            ("EBWebView/Default/Service Worker/CacheStorage/abc/index", "cache"),
+            ("EBWebView/Default/IndexedDB/https_example.com_0.indexeddb.leveldb/000003.log", "indexed_db"),
+            ("EBWebView/Default/Local Storage/leveldb/000003.log", "local_storage"),
+            ("EBWebView/Default/Network/Cookies", "other"),
+            ("EBWebView/Default/Preferences", "other"),
+            // WebKitGTK
+            ("WebKitCache/Version 16/Records/abc/Resource", "cache"),
+            ("indexeddb/v1/https_example.com_0/IndexedDB.sqlite3", "indexed_db"),
+            ("storage/abc/def/IndexedDB/v1/db.sqlite3", "indexed_db"),
+            ("localstorage/https_example.com_0.localstorage", "local_storage"),
+            ("cookies.sqlite", "other"),
+            ("icon.png", "other"),
+        ] {
+            let mut usage = StorageUsage::default();
+            usage.add(std::path::Path::new(rel), 10);
+            let bucket = [
+                ("cache", usage.cache),
+                ("indexed_db", usage.indexed_db),
+                ("local_storage", usage.local_storage),
+                ("other", usage.other),
+            ];
+            assert_eq!(bucket, bucket.map(|(name, _)| (name, if name == expected { 10 } else { 0 })), "{rel}");
+            assert_eq!(usage.total, 10, "{rel}");
+        }
+    }
+
+    #[test]
+    fn measure_dir_totals_files_by_bucket() {
+        let tmp = tempfile::tempdir().unwrap();
+        for (rel, len) in [("EBWebView/Default/Cache/a", 3), ("EBWebView/Default/IndexedDB/b", 5), ("icon.png", 7)] {
+            let path = tmp.path().join(rel);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, vec![0; len]).unwrap();
+        }
+        let usage = measure_dir(tmp.path());
+        assert_eq!((usage.cache, usage.indexed_db, usage.local_storage, usage.other, usage.total), (3, 5, 0, 7, 15));
+    }
+
+    /// Whether `rel` is `pattern` or inside it, `*` matching any one name.
+    fn under_layout_path(rel: &str, pattern: &str) -> bool {
+        let rel: Vec<&str> = rel.split('/').collect();
+        let pattern: Vec<&str> = pattern.split('/').collect();
+        rel.len() >= pattern.len() && pattern.iter().zip(&rel).all(|(p, r)| *p == "*" || p == r)
+    }
+
+    #[test]
+    fn clear_scope_removes_only_its_own_profile_paths() {
+        for (layout, unrelated) in [
+            (
+                &WEBVIEW2_LAYOUT,
+                &[
+                    "icon.png",
+                    "EBWebView/Local State",
+                    "EBWebView/Default/Preferences",
+                    "EBWebView/Default/Network/Network Persistent State",
+                    "EBWebView/Default/Network/TransportSecurity",
+                    "EBWebView/Default/Extension Cookies",
+                    "EBWebView/Default/Cache Notes/keep",
+                    "EBWebView/Crashpad/reports/keep",
+                ][..],
+            ),
+            (
+                &WEBKITGTK_LAYOUT,
+                &[
+                    "icon.png",
+                    "hsts-storage.sqlite",
+                    "deviceidhashsalts/1/abc",
+                    "mediakeys/v1/keep",
+                    "itp/observations.db",
+                    "cookies-backup",
+                    "webkitcache-notes",
+                ][..],
+            ),
+        ] {
+            // Something inside every listed path, plus paths a name heuristic
+            // would have caught.
+            let mut files: Vec<String> = [layout.cache, layout.cookies, layout.storage]
+                .concat()
+                .iter()
+                .map(|p| format!("{}/data", p.replace('*', "a1b2")))
+                .collect();
+            files.extend(unrelated.iter().map(|f| f.to_string()));
+            files.push("storage/a1b2/c3d4/LocalStorage/localstorage.sqlite3".into());
+
+            for scope in [ClearScope::Cache, ClearScope::Cookies, ClearScope::Storage] {
+                let tmp = tempfile::tempdir().unwrap();
+                for file in &files {
+                    let path = tmp.path().join(file);
+                    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                    std::fs::write(path, b"x").unwrap();
+                }
+                clear_scope(tmp.path(), layout, scope).unwrap();
+
+                for file in &files {
+                    let removed = scope.paths(layout).iter().any(|p| under_layout_path(file, p));
+                    assert_eq!(!tmp.path().join(file).exists(), removed, "{scope:?} {file}");
+                }
+                // Clearing again finds nothing left to do.
+                clear_scope(tmp.path(), layout, scope).unwrap();
+            }
+        }
+        // A missing data dir has nothing to clear.
+        clear_scope(std::path::Path::new("/nonexistent/sweetshark"), &WEBKITGTK_LAYOUT, ClearScope::Cache).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn clear_scope_removes_symlinks_without_following_them() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("data");
+        let outside = tmp.path().join("outside");
+        std::fs::create_dir_all(outside.join("inner")).unwrap();
+        std::fs::write(outside.join("inner/keep"), b"x").unwrap();
+        std::fs::create_dir_all(dir.join("storage")).unwrap();
+        // A linked cache dir, and a link standing in for a per-origin dir.
+        std::os::unix::fs::symlink(&outside, dir.join("WebKitCache")).unwrap();
+        std::os::unix::fs::symlink(&outside, dir.join("storage/a1b2")).unwrap();
+
+        clear_scope(&dir, &WEBKITGTK_LAYOUT, ClearScope::Cache).unwrap();
+        assert!(dir.join("WebKitCache").symlink_metadata().is_err());
+        assert!(dir.join("storage/a1b2").symlink_metadata().is_ok());
+        assert!(outside.join("inner/keep").exists());
+    }
+
+    #[test]
+    fn orphaned_server_ids_lists_only_unowned_server_dirs() {
+        let orphan = "0a1b2c3d-0000-4000-8000-000000000000";
+        let missing = "0a1b2c3d-0000-4000-8000-000000000002";
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("servers");
+        for dir in [ID, orphan, "not-a-server", "3F2B8C1E-9D4A-4E6B-8A7C-0123456789AB"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        // A file named like a server is not a data dir.
+        std::fs::write(root.join(missing), b"").unwrap();
+        #[cfg(unix)]
+        {
+            let elsewhere = "0a1b2c3d-0000-4000-8000-000000000003";
+            std::fs::create_dir(tmp.path().join("elsewhere")).unwrap();
+            std::os::unix::fs::symlink(tmp.path().join("elsewhere"), root.join(elsewhere)).unwrap();
+        }
+        let stored = |ids: &[&str]| serde_json::Value::Array(ids.iter().map(|id| stored_server(id)).collect());
+
+        for (servers, expected) in [
+            (stored(&[ID]), vec![orphan]),
+            (stored(&[ID, orphan]), vec![]),
+            // A registered server whose dir is gone is not an orphan.
+            (stored(&[ID, missing]), vec![orphan]),
+            (stored(&[]), vec![orphan, ID]),
+            // Unreadable entries may own any of the dirs.
+            (serde_json::json!([stored_server(ID), { "id": orphan }]), vec![]),
+            (serde_json::json!("not a list"), vec![]),
+        ] {
+            let registry = ServerRegistry::from_stored(Some(SERVERS_SCHEMA_VERSION), Some(servers.clone()));
+            let mut found: Vec<String> = orphaned_server_ids(&root, &registry).iter().map(|id| id.to_string()).collect();
+            found.sort();
+            assert_eq!(found, expected, "{servers}");
+        }
+        assert!(orphaned_server_ids(&root, &ServerRegistry::unavailable()).is_empty());
+    }
+
+    // ── Server data deletion ─────────────────────────────────────────────────
+
+    #[test]
+    fn tombstone_owner_accepts_only_our_names() {
+        assert_eq!(tombstone_owner(&format!("{ID}-1700000000000")), Some(ServerId::parse(ID).unwrap()));
+        assert_eq!(tombstone_owner(&format!("{ID}-0")), Some(ServerId::parse(ID).unwrap()));
+        for name in [
+            ID.to_string(),
+            format!("{ID}-"),
+            format!("{ID}_1700000000000"),
+            format!("{ID}-17000x"),
+            format!("{ID}--1"),
+            format!("{ID}-1-2"),
+            "3F2B8C1E-9D4A-4E6B-8A7C-0123456789AB-1".into(),
+            "../../../../../../../../../../../../-1".into(),
+            "3f2b8c1e-9d4a-4e6b-8a7c-0123456789äb-1".into(),
+            "short-1".into(),
+            String::new(),
+        ] {
+            assert_eq!(tombstone_owner(&name), None, "{name}");
+        }
+    }
+
+    #[test]
+    fn cancel_flag_wait_times_out_or_wakes_on_cancel() {
+        let flag = Arc::new(CancelFlag::default());
+        let started = std::time::Instant::now();
+        assert!(!flag.wait(std::time::Duration::from_millis(20)));
+        assert!(started.elapsed() >= std::time::Duration::from_millis(20));
+
+        let canceller = {
+            let flag = flag.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(std::time::Duration::from_millis(20));
+                flag.cancel();
+            })
+        };
+        let started = std::time::Instant::now();
+        assert!(flag.wait(std::time::Duration::from_secs(30)));
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+        canceller.join().unwrap();
+
+        // Stays cancelled.
+        assert!(flag.wait(std::time::Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn with_backoff_retries_then_fails_or_stops_on_cancel() {
+        let flag = CancelFlag::default();
+        let mut retries = Vec::new();
+        let mut attempts = 0;
+        let result = with_backoff(
+            &flag,
+            std::time::Duration::ZERO,
+            || {
+                attempts += 1;
+                if attempts < 3 { Err(format!("locked {attempts}")) } else { Ok(attempts) }
+            },
+            |status| retries.push(serde_json::to_value(status).unwrap()),
+        );
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(
+            retries,
+            [
+                serde_json::json!({ "status": "retrying", "attempt": 1, "retryInMs": 0, "error": "locked 1" }),
+                serde_json::json!({ "status": "retrying", "attempt": 2, "retryInMs": 0, "error": "locked 2" }),
+            ]
+        );
+
+        let mut attempts = 0;
+        let result = with_backoff(&flag, std::time::Duration::ZERO, || -> Result<(), _> { attempts += 1; Err("locked".into()) }, |_| {});
+        assert!(matches!(result, Err(DeletionStatus::Failed { error }) if error == "locked"));
+        assert_eq!(attempts, DELETE_MAX_ATTEMPTS);
+
+        flag.cancel();
+        let mut attempts = 0;
+        let result = with_backoff(&flag, std::time::Duration::from_secs(30), || -> Result<(), _> { attempts += 1; Err("locked".into()) }, |_| {});
+        assert!(matches!(result, Err(DeletionStatus::Cancelled)));
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn tombstone_attempt_moves_the_data_dir_once() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source = tmp.path().join("servers").join(ID);
+        std::fs::create_dir_all(source.join("EBWebView")).unwrap();
+        let tombstone = tmp.path().join(format!("{ID}-1"));
+
+        assert_eq!(tombstone_attempt(&source, &tombstone), Ok(true));
+        assert!(!source.exists() && tombstone.join("EBWebView").is_dir());
+        // Already moved (or never created): nothing to do.
+        assert_eq!(tombstone_attempt(&source, &tombstone), Ok(false));
+
+        assert_eq!(delete_attempt(&tombstone), Ok(()));
+        assert!(!tombstone.exists());
+        assert_eq!(delete_attempt(&tombstone), Ok(()));
+    }
+
+    #[test]
+    fn resume_tombstones_skips_foreign_entries_and_survives_failures() {
+        let other = "0a1b2c3d-0000-4000-8000-000000000000";
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("tombstones");
+        for dir in [format!("{ID}-1"), format!("{other}-2"), "not-ours".into(), ID.into(), format!("{ID}-x")] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        std::fs::write(root.join(format!("{ID}-3")), b"").unwrap();
+        #[cfg(unix)]
+        {
+            std::fs::create_dir(tmp.path().join("elsewhere")).unwrap();
+            std::os::unix::fs::symlink(tmp.path().join("elsewhere"), root.join(format!("{other}-4"))).unwrap();
+        }
+
+        let mut resumed = Vec::new();
+        resume_tombstones_in(&root, |id, tombstone| {
+            resumed.push((id.to_string(), tombstone.file_name().unwrap().to_string_lossy().into_owned()));
+            // The first one fails; the other is still resumed.
+            if resumed.len() == 1 { Err("no thread".into()) } else { Ok(()) }
+        })
+        .unwrap();
+        resumed.sort();
+        assert_eq!(
+            resumed,
+            [(other.to_string(), format!("{other}-2")), (ID.to_string(), format!("{ID}-1"))]
+        );
+
+        assert!(resume_tombstones_in(&tmp.path().join("missing"), |_, _| Ok(())).is_err());
+    }
+
+    // ── Audio devices ────────────────────────────────────────────────────────
+
+    #[test]
+    fn device_selection_resolves_ids_and_labels_or_falls_back_to_default() {
+        let device = |id: &str, name: &str, kind| AudioDevice { id: id.into(), name: name.into(), kind, is_default: false };
+        let devices = [
+            device("alsa_input.usb-headset", "USB Headset Mic", AudioDeviceKind::Input),
+            device("alsa_input.pci-analog", "Built-in Audio", AudioDeviceKind::Input),
+            device("alsa_output.usb-headset", "USB Headset", AudioDeviceKind::Output),
+        ];
+        let validate = |kind, selected: Option<&str>| validate_device_selection(&devices, kind, selected.map(String::from));
+
+        // A known id resolves to the label the page reports.
+        assert_eq!(validate(AudioDeviceKind::Input, Some("alsa_input.usb-headset")).as_deref(), Some("USB Headset Mic"));
+        assert_eq!(validate(AudioDeviceKind::Output, Some("alsa_output.usb-headset")).as_deref(), Some("USB Headset"));
+        // Selections saved before ids existed are labels.
+        assert_eq!(validate(AudioDeviceKind::Input, Some("Built-in Audio")).as_deref(), Some("Built-in Audio"));
+
+        // Unknown, unplugged or of the other kind: the system default.
+        assert_eq!(validate(AudioDeviceKind::Input, Some("alsa_input.unplugged")), None);
+        assert_eq!(validate(AudioDeviceKind::Output, Some("alsa_input.usb-headset")), None);
+        // Nothing selected: the system default, without a lookup.
+        assert_eq!(validate(AudioDeviceKind::Input, Some("")), None);
+        assert_eq!(validate(AudioDeviceKind::Input, None), None);
+    }
+
+    // ── Push-to-talk state machine ──────────────────────────────────────────
+
+    fn machine(mode: PttMode, debounce_ms: u32, release_delay_ms: u32) -> PttMachine {
+        PttMachine::new(&PttConfig {
+            enabled: true,
+            mode,
+            debounce_ms,
+            release_delay_ms,
+            ..Default::default()
+        })
+    }
+
+    /// Drives a machine with one sample per 5 ms poll tick, like the worker.
+    struct Clock {
+        t0: std::time::Instant,
+        ms: u64,
+    }
+
+    impl Clock {
+        fn new() -> Self {
+            Self { t0: std::time::Instant::now(), ms: 0 }
+        }
+
+        /// Feed `held` for `ms` milliseconds of ticks; returns each
+        /// transition with the time it happened at.
+        fn hold(&mut self, m: &mut PttMachine, held: bool, ms: u64) -> Vec<(u64, bool)> {
+            let mut out = Vec::new();
+            for _ in 0..ms / 5 {
+                if let Some(open) = m.update(held, self.t0 + std::time::Duration::from_millis(self.ms)) {
+                    out.push((self.ms, open));
+                }
+                self.ms += 5;
+            }
+            out
+        }
+    }
+
+    #[test]
+    fn ptt_hold_opens_only_while_held() {
+        let mut m = machine(PttMode::PushToTalk, 0, 0);
+        let mut c = Clock::new();
+        assert!(!m.is_open());
+        assert!(c.hold(&mut m, false, 50).is_empty());
+        assert_eq!(c.hold(&mut m, true, 100), vec![(50, true)]);
+        assert_eq!(c.hold(&mut m, false, 50), vec![(150, false)]);
+        assert!(!m.is_open());
+    }
+
+    #[test]
+    fn ptt_toggle_latches_and_unlatches_on_press_edges() {
+        let mut m = machine(PttMode::Toggle, 0, 0);
+        let mut c = Clock::new();
+        c.hold(&mut m, false, 10);
+        // Press latches open; holding and releasing change nothing.
+        assert_eq!(c.hold(&mut m, true, 200), vec![(10, true)]);
+        assert!(c.hold(&mut m, false, 200).is_empty());
+        assert!(m.is_open());
+        // The next press unlatches.
+        assert_eq!(c.hold(&mut m, true, 50), vec![(410, false)]);
+        assert!(c.hold(&mut m, false, 50).is_empty());
+        assert!(!m.is_open());
+    }
+
+    #[test]
+    fn ptt_push_to_mute_starts_open_and_closes_while_held() {
+        let mut m = machine(PttMode::PushToMute, 0, 0);
+        let mut c = Clock::new();
+        assert!(m.is_open());
+        assert!(c.hold(&mut m, false, 50).is_empty());
+        assert_eq!(c.hold(&mut m, true, 50), vec![(50, false)]);
+        assert_eq!(c.hold(&mut m, false, 50), vec![(100, true)]);
+        assert!(m.is_open());
+    }
+
+    #[test]
+    fn ptt_keys_held_across_a_mode_change() {
+        // Switching modes rebuilds the machine while the keys may still be
+        // down. Hold-style modes follow the keys straight away…
+        let mut c = Clock::new();
+        let mut m = machine(PttMode::PushToTalk, 0, 0);
+        assert_eq!(c.hold(&mut m, true, 10), vec![(0, true)]);
+
+        let mut m = machine(PttMode::PushToMute, 0, 0);
+        assert_eq!(c.hold(&mut m, true, 10), vec![(10, false)]);
+
+        // …but toggle waits for a fresh press rather than latching on the
+        // one that was already in progress.
+        let mut m = machine(PttMode::Toggle, 0, 0);
+        assert!(c.hold(&mut m, true, 100).is_empty());
+        assert!(c.hold(&mut m, false, 10).is_empty());
+        assert_eq!(c.hold(&mut m, true, 10), vec![(130, true)]);
+    }
+
+    #[test]
+    fn ptt_debounce_rejects_a_short_press() {
+        let mut m = machine(PttMode::PushToTalk, 30, 0);
+        let mut c = Clock::new();
+        // 20 ms taps never last the 30 ms debounce.
+        for _ in 0..5 {
+            assert!(c.hold(&mut m, true, 20).is_empty());
+            assert!(c.hold(&mut m, false, 20).is_empty());
+        }
+        // A real press opens 30 ms after it started.
+        assert_eq!(c.hold(&mut m, true, 100), vec![(230, true)]);
+        assert_eq!(c.hold(&mut m, false, 10), vec![(300, false)]);
+    }
+
+    #[test]
+    fn ptt_release_delay_holds_the_mic_open() {
+        let mut m = machine(PttMode::PushToTalk, 0, 200);
+        let mut c = Clock::new();
+        assert_eq!(c.hold(&mut m, true, 100), vec![(0, true)]);
+        assert_eq!(c.hold(&mut m, false, 300), vec![(300, false)]);
+    }
+
+    #[test]
+    fn ptt_re_press_during_release_delay_cancels_the_close() {
+        let mut m = machine(PttMode::PushToTalk, 0, 200);
+        let mut c = Clock::new();
+        c.hold(&mut m, true, 100);
+        // Released at 100, pressed again at 200: the pending close is dropped.
+        assert!(c.hold(&mut m, false, 100).is_empty());
+        assert!(c.hold(&mut m, true, 100).is_empty());
+        assert!(m.is_open());
+        // The delay starts over from the second release at 300.
+        assert_eq!(c.hold(&mut m, false, 300), vec![(500, false)]);
+    }
+
+    #[test]
+    fn ptt_release_delay_does_not_apply_to_push_to_mute() {
+        let mut m = machine(PttMode::PushToMute, 0, 200);
+        let mut c = Clock::new();
+        assert_eq!(c.hold(&mut m, true, 50), vec![(0, false)]);
+        assert_eq!(c.hold(&mut m, false, 50), vec![(50, true)]);
+    }
+
+    #[test]
+    fn ptt_toggle_close_waits_for_release_delay() {
+        let mut m = machine(PttMode::Toggle, 0, 100);
+        let mut c = Clock::new();
+        c.hold(&mut m, false, 5);
+        assert_eq!(c.hold(&mut m, true, 10), vec![(5, true)]);
+        assert!(c.hold(&mut m, false, 50).is_empty());
+        // Unlatching press at 65; the mic closes 100 ms later.
+        assert_eq!(c.hold(&mut m, true, 200), vec![(165, false)]);
+    }
+
+    pub(crate) fn binding(name: &str, keys: &[&str]) -> PttBinding {
+        PttBinding { name: name.into(), keys: keys.iter().map(|k| k.to_string()).collect() }
+    }
+
+    #[test]
+    fn ptt_config_rejects_blank_and_duplicate_binding_names() {
+        let config = |bindings| PttConfig { bindings, ..Default::default() };
+        assert!(config(vec![]).validate().is_ok());
+        assert!(config(vec![binding("main", &["ctrl"]), binding("priority", &["mouse4"])]).validate().is_ok());
+        // A binding without keys is fine; it just never fires.
+        assert!(config(vec![binding("main", &[])]).validate().is_ok());
+
+        assert!(config(vec![binding("", &["ctrl"])]).validate().is_err());
+        assert!(config(vec![binding("  ", &["ctrl"])]).validate().is_err());
+        let dup = config(vec![binding("main", &["ctrl"]), binding(" main ", &["alt"])]).validate();
+        assert_eq!(dup.unwrap_err(), "Two push-to-talk bindings are named \"main\"");
+    }
+
+    /// A `MicControl` that fails until told otherwise.
+    #[derive(Default)]
+    struct MockMic {
+        fail: std::sync::atomic::AtomicBool,
+        /// Every successful call, in order.
+        calls: Mutex<Vec<bool>>,
+    }
+
+    impl MicControl for MockMic {
+        fn set_muted(&self, muted: bool) -> Result<(), String> {
+            if self.fail.load(std::sync::atomic::Ordering::SeqCst) {
+                Err("capture device unplugged".into())
+            } else {
+                self.calls.lock().unwrap().push(muted);
+                Ok(())
+            }
+        }
+    }
+
+    /// A key reader on which nothing is ever held.
+    struct NoKeysHeld;
+
+    impl ChordReader for NoKeysHeld {
+        fn chords_down(&mut self, chords: &[Vec<u32>]) -> Vec<bool> {
+            vec![false; chords.len()]
+        }
+    }
+
+    // Push-to-mute is open while no key is held, so a stopping worker used
+    // to re-mute the OS mic after the config change had unmuted it. Restart
+    // and disable it repeatedly to give that race every chance.
+    #[cfg(any(windows, target_os = "linux"))]
+    #[test]
+    fn disabling_push_to_mute_leaves_the_system_mic_live() {
+        let mic = Arc::new(MockMic::default());
+        let mut state = PttState::with_mic(mic.clone());
+        state.open_keys = |_| Ok(Box::new(NoKeysHeld));
+        let app = tauri::test::mock_app();
+        app.manage::<SharedPttState>(Arc::new(Mutex::new(state)));
+        let config = PttConfig {
+            enabled: true,
+            mode: PttMode::PushToMute,
+            target: PttTarget::System,
+            bindings: vec![binding("main", &["f9"])],
+            ..Default::default()
+        };
+
+        for _ in 0..20 {
+            apply_ptt_config(app.handle(), PTT_ALL_SERVERS.into(), config.clone()).unwrap();
+            apply_ptt_config(app.handle(), PTT_ALL_SERVERS.into(), config.clone()).unwrap();
+            apply_ptt_config(app.handle(), PTT_ALL_SERVERS.into(), PttConfig { enabled: false, ..config.clone() })
+                .unwrap();
+            assert_eq!(mic.calls.lock().unwrap().last(), Some(&false));
+        }
+        // Nothing is left running to change it afterwards.
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert_eq!(mic.calls.lock().unwrap().last(), Some(&false));
+        assert!(!app.state::<SharedPttState>().lock().unwrap().status(None).active);
+    }
+
+    #[test]
+    fn ptt_mute_failure_emits_error_and_degrades_status() {
+        use tauri::Listener;
+
+        let mic = Arc::new(MockMic { fail: true.into(), ..Default::default() });
+        let state: SharedPttState = Arc::new(Mutex::new(PttState::with_mic(mic.clone())));
+        let app = tauri::test::mock_app();
+        app.manage(state.clone());
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.listen("ptt://error", move |event| {
+            let _ = tx.send(event.payload().to_string());
+        });
+
+        let err = ptt_set_system_mic(app.handle(), PTT_ALL_SERVERS, true).unwrap_err();
+        assert_eq!(err, "capture device unplugged");
+        let payload: serde_json::Value =
+            serde_json::from_str(&rx.recv_timeout(std::time::Duration::from_secs(1)).unwrap()).unwrap();
+        assert_eq!(payload["scope"], PTT_ALL_SERVERS);
+        assert_eq!(payload["message"], "capture device unplugged");
+
+        let status = state.lock().unwrap().status(Some(PTT_ALL_SERVERS));
+        assert!(status.degraded);
+        assert_eq!(status.error.as_deref(), Some("capture device unplugged"));
+        assert!(state.lock().unwrap().status(None).degraded);
+
+        // The next successful call clears it, without another event.
+        mic.fail.store(false, std::sync::atomic::Ordering::SeqCst);
+        ptt_set_system_mic(app.handle(), PTT_ALL_SERVERS, false).unwrap();
+        assert_eq!(
+            state.lock().unwrap().status(None),
+            PttStatus { active: false, degraded: false, error: None }
+        );
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn ptt_status_across_scopes_is_deterministic() {
+        let mut state = PttState::with_mic(Arc::new(MockMic::default()));
+        let ids = ["f0e1d2c3-0000-4000-8000-000000000000", "0a1b2c3d-0000-4000-8000-000000000000"];
+        for id in ids {
+            let _ = state.record_result(id, Err(format!("failed for {id}")));
+        }
+        // Lowest server id wins while the all-servers scope is healthy…
+        assert_eq!(state.status(None).error, Some(format!("failed for {}", ids[1])));
+        // …and the all-servers scope wins once it fails too.
+        let _ = state.record_result(PTT_ALL_SERVERS, Err("all servers".into()));
+        assert_eq!(state.status(None).error.as_deref(), Some("all servers"));
+        assert_eq!(state.status(Some(ids[0])).error, Some(format!("failed for {}", ids[0])));
+    }
+
+    #[test]
+    fn ptt_event_payloads_match_the_frontend_types() {
+        let event = PttEvent { scope: PTT_ALL_SERVERS.into(), binding: Some("main".into()), timestamp_ms: 1234, mute_applied: true };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({ "scope": "*", "binding": "main", "timestampMs": 1234, "muteApplied": true })
+        );
+        // Voice activity has no binding; the frontend expects null, not a missing key.
+        let event = PttEvent { binding: None, mute_applied: false, ..event };
+        assert_eq!(serde_json::to_value(&event).unwrap()["binding"], serde_json::Value::Null);
+
+        let error = PttErrorEvent { scope: ID.into(), message: "capture device unplugged".into(), timestamp_ms: 5 };
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({ "scope": ID, "message": "capture device unplugged", "timestampMs": 5 })
+        );
+
+        // Timestamps come from one monotonic clock.
+        let first = PttEvent::new(PTT_ALL_SERVERS, None, true).timestamp_ms;
+        assert!(PttEvent::new(PTT_ALL_SERVERS, None, true).timestamp_ms >= first);
+    }
+
+    // ── Key-state readers ────────────────────────────────────────────────────
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn token_to_vk_maps_frontend_tokens_to_evdev_codes() {
+        use evdev::KeyCode as K;
+
+        let cases = [
+            ("alt", Some(K::KEY_LEFTALT)),
+            ("AltRight", Some(K::KEY_RIGHTALT)),
+            ("ShiftLeft", Some(K::KEY_LEFTSHIFT)),
+            ("ctrl", Some(K::KEY_LEFTCTRL)),
+            ("meta", Some(K::KEY_LEFTMETA)),
+            (" Space ", Some(K::KEY_SPACE)),
+            ("numpadenter", Some(K::KEY_KPENTER)),
+            ("print", Some(K::KEY_SYSRQ)),
+            ("f12", Some(K::KEY_F12)),
+            ("M", Some(K::KEY_M)),
+            ("7", Some(K::KEY_7)),
+            ("/", Some(K::KEY_SLASH)),
+            ("mouse3", Some(K::BTN_MIDDLE)),
+            ("mouse4", Some(K::BTN_SIDE)),
+            ("xbutton2", Some(K::BTN_EXTRA)),
+            ("f13", None),
+            ("é", None),
+            ("", None),
+        ];
+        for (token, code) in cases {
+            assert_eq!(token_to_vk(token), code.map(|k| k.code() as u32), "{token:?}");
+        }
+    }
+
+    /// Press and release keys on uinput devices, one of them created after
+    /// the reader, and watch `chords_down` follow. Run with
+    /// `cargo test -- --ignored` as a user in the `input` group with write
+    /// access to /dev/uinput.
+    #[cfg(target_os = "linux")]
+    #[test]
+    #[ignore = "needs /dev/uinput and read access to /dev/input"]
+    fn ptt_key_reader_follows_uinput_devices() {
+        use evdev::{uinput::VirtualDevice, AttributeSet, EventType, InputEvent, KeyCode as K};
+
+        fn device(name: &str, keys: &[K]) -> VirtualDevice {
+            let keys: AttributeSet<K> = keys.iter().copied().collect();
+            VirtualDevice::builder().unwrap().name(name).with_keys(&keys).unwrap().build().unwrap()
+        }
+        fn set(device: &mut VirtualDevice, keys: &[K], down: bool) {
+            let events: Vec<InputEvent> =
+                keys.iter().map(|k| InputEvent::new(EventType::KEY.0, k.code(), i32::from(down))).collect();
+            device.emit(&events).unwrap();
+        }
+        // Poll like the worker until `want` shows up or a few rescans pass.
+        fn wait_for(reader: &mut PttKeyReader, chords: &[Vec<u32>], want: &[bool]) -> Vec<bool> {
+            let deadline = std::time::Instant::now() + PTT_DEVICE_RESCAN * 3;
+            loop {
+                let down = reader.chords_down(chords);
+                if down == want || std::time::Instant::now() > deadline {
+                    return down;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(5));
+            }
+        }
+
+        let chords = vec![
+            vec![K::KEY_LEFTALT.code() as u32, K::KEY_M.code() as u32],
+            vec![K::BTN_SIDE.code() as u32],
+        ];
+        let mut keyboard = device("sweetshark test keyboard", &[K::KEY_LEFTALT, K::KEY_M]);
+        // udev needs a moment to create the node and set its permissions.
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        let mut reader = PttKeyReader::open(&chords.concat()).unwrap();
+        assert!(matches!(reader, PttKeyReader::Evdev(_)));
+        assert_eq!(reader.chords_down(&chords), [false, false]);
+
+        set(&mut keyboard, &[K::KEY_LEFTALT], true);
+        assert_eq!(wait_for(&mut reader, &chords, &[false, false]), [false, false]);
+        set(&mut keyboard, &[K::KEY_M], true);
+        assert_eq!(wait_for(&mut reader, &chords, &[true, false]), [true, false]);
+        set(&mut keyboard, &[K::KEY_LEFTALT, K::KEY_M], false);
+        assert_eq!(wait_for(&mut reader, &chords, &[false, false]), [false, false]);
+
+        // A mouse plugged in later is picked up by the rescan.
+        let mut mouse = device("sweetshark test mouse", &[K::BTN_SIDE]);
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        set(&mut mouse, &[K::BTN_SIDE], true);
+        assert_eq!(wait_for(&mut reader, &chords, &[false, true]), [false, true]);
+
+        // Unplugging it mid-press releases the chord instead of sticking.
+        drop(mouse);
+        assert_eq!(wait_for(&mut reader, &chords, &[false, false]), [false, false]);
+    }
+
+    // ── Voice activity ───────────────────────────────────────────────────────
+
+    const RATE: u32 = 48_000;
+    /// One 10 ms mono buffer.
+    const FRAMES_10MS: usize = 480;
+
+    fn tone(amplitude: f32, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / RATE as f32).sin())
+            .collect()
+    }
+
+    fn silence(frames: usize) -> Vec<f32> {
+        vec![0.0; frames]
+    }
+
+    /// -40 dBFS threshold, 20 ms attack, 300 ms hangover.
+    fn gate() -> VadGate {
+        VadGate::new(&VadConfig { threshold_db: -40.0, attack_ms: 20, release_ms: 300 })
+    }
+
+    /// Feed `buffers` copies of `buf` and return the index (1-based) of each
+    /// transition with its new state.
+    fn run(gate: &mut VadGate, buf: &[f32], buffers: usize) -> Vec<(usize, bool)> {
+        (1..=buffers)
+            .filter_map(|i| gate.feed(buf, 1, RATE).map(|open| (i, open)))
+            .collect()
+    }
+
+    #[test]
+    fn rms_of_synthetic_buffers() {
+        assert_eq!(rms(&[]), 0.0);
+        assert_eq!(rms(&silence(FRAMES_10MS)), 0.0);
+        assert!((rms(&[0.5; 64]) - 0.5).abs() < 1e-6);
+        // A full-scale sine over whole periods is 1/sqrt(2).
+        assert!((rms(&tone(1.0, RATE as usize / 10)) - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-3);
+    }
+
+    #[test]
+    fn vad_stays_closed_on_silence_and_quiet_noise() {
+        let mut g = gate();
+        assert!(run(&mut g, &silence(FRAMES_10MS), 100).is_empty());
+        // -46 dBFS peak, well under the threshold in RMS terms.
+        assert!(run(&mut g, &tone(0.005, FRAMES_10MS), 100).is_empty());
+        assert!(!g.is_open());
+    }
+
+    #[test]
+    fn vad_opens_after_attack_and_closes_after_hangover() {
+        let mut g = gate();
+        // Two 10 ms buffers make up the 20 ms attack.
+        assert_eq!(run(&mut g, &tone(0.5, FRAMES_10MS), 5), vec![(2, true)]);
+        // Thirty 10 ms buffers make up the 300 ms hangover.
+        assert_eq!(run(&mut g, &silence(FRAMES_10MS), 40), vec![(30, false)]);
+        assert!(!g.is_open());
+    }
+
+    #[test]
+    fn vad_ignores_bursts_shorter_than_attack() {
+        let mut g = gate();
+        for _ in 0..10 {
+            assert_eq!(g.feed(&tone(0.5, FRAMES_10MS), 1, RATE), None);
+            assert_eq!(g.feed(&silence(FRAMES_10MS), 1, RATE), None);
+        }
+        assert!(!g.is_open());
+    }
+
+    #[test]
+    fn vad_pauses_shorter_than_hangover_keep_it_open() {
+        let mut g = gate();
+        run(&mut g, &tone(0.5, FRAMES_10MS), 2);
+        assert!(g.is_open());
+        // 290 ms pause, a word, another 290 ms pause: never closes.
+        assert!(run(&mut g, &silence(FRAMES_10MS), 29).is_empty());
+        assert!(run(&mut g, &tone(0.5, FRAMES_10MS), 1).is_empty());
+        assert!(run(&mut g, &silence(FRAMES_10MS), 29).is_empty());
+        assert!(g.is_open());
+        // The hangover restarted at the word, so one more buffer closes it.
+        assert_eq!(run(&mut g, &silence(FRAMES_10MS), 1), vec![(1, false)]);
+    }
+
+    #[test]
+    fn vad_times_by_frames_not_samples() {
+        let mut g = gate();
+        // 960 interleaved stereo samples are 10 ms, not 20 ms.
+        let stereo = tone(0.5, FRAMES_10MS * 2);
+        assert_eq!(g.feed(&stereo, 2, RATE), None);
+        assert_eq!(g.feed(&stereo, 2, RATE), Some(true));
+        // Empty buffers and a zero rate are ignored rather than dividing by 0.
+        assert_eq!(g.feed(&[], 1, RATE), None);
+        assert_eq!(g.feed(&silence(FRAMES_10MS), 1, 0), None);
+        assert!(g.is_open());
+    }
+
+    // ── Audible cues ─────────────────────────────────────────────────────────
+
+    /// A WAV with `frames`, each holding one sample per channel.
+    fn wav<S: hound::Sample + Copy>(spec: hound::WavSpec, frames: &[&[S]]) -> Vec<u8> {
+        let mut out = std::io::Cursor::new(Vec::new());
+        let mut writer = hound::WavWriter::new(&mut out, spec).unwrap();
+        for sample in frames.iter().flat_map(|f| f.iter()) {
+            writer.write_sample(*sample).unwrap();
+        }
+        writer.finalize().unwrap();
+        out.into_inner()
+    }
+
+    #[test]
+    fn bundled_cues_decode() {
+        for open in [true, false] {
+            let cue = cue(open).expect("bundled cue decodes");
+            assert!(cue.sample_rate > 0);
+            assert!(!cue.samples.is_empty());
+            assert!(cue.samples.iter().all(|s| (-1.0..=1.0).contains(s)));
+        }
+    }
+
+    #[test]
+    fn decode_cue_scales_and_downmixes_to_mono() {
+        let spec = |channels, bits_per_sample, sample_format| hound::WavSpec {
+            channels,
+            sample_rate: 8000,
+            bits_per_sample,
+            sample_format,
+        };
+
+        let stereo16 = wav::<i16>(spec(2, 16, hound::SampleFormat::Int), &[&[16384, 16384], &[16384, -16384], &[-32768, 0]]);
+        let cue = decode_cue(&stereo16).unwrap();
+        assert_eq!(cue.sample_rate, 8000);
+        assert_eq!(cue.samples, [0.5, 0.0, -0.5]);
+
+        let mono24 = wav::<i32>(spec(1, 24, hound::SampleFormat::Int), &[&[1 << 22], &[-(1 << 23)]]);
+        assert_eq!(decode_cue(&mono24).unwrap().samples, [0.5, -1.0]);
+
+        let stereo_float = wav::<f32>(spec(2, 32, hound::SampleFormat::Float), &[&[1.0, 0.0], &[-0.25, -0.75]]);
+        assert_eq!(decode_cue(&stereo_float).unwrap().samples, [0.5, -0.5]);
+
+        assert!(decode_cue(b"RIFF but not a wave file").is_err());
+    }
 }
@@ -0,0 +1,378 @@
+// ── External links ───────────────────────────────────────────────────────────
+//
+// Links leave the app through the opener plugin — never a shell — and only
+// after passing `url_policy`: web and mail links open directly, a short list
+// of well-known app handlers needs the user's confirmation, and everything
+// else (file:, javascript:, OS protocol handlers, …) is refused.
+
+use tauri::{AppHandle, Manager};
+use crate::{ServerChange, ServerId, SharedServerRegistry, commit_server_change};
+
+/// Schemes opened without asking.
+const URL_ALLOWED_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Schemes handed to another application only once the user confirms.
+const URL_CONFIRM_SCHEMES: &[&str] = &[
+    "tel", "sms", "magnet", "steam", "spotify", "zoommtg", "msteams", "slack",
+];
+
+/// Why `open_url` did not open a link. Reaches the frontend as
+/// `{ kind, detail }`.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "kind", content = "detail", rename_all = "camelCase")]
+pub enum OpenUrlError {
+    /// Not an absolute URL.
+    Invalid(String),
+    /// The scheme is on neither list.
+    SchemeNotAllowed(String),
+    /// The user declined the confirmation prompt.
+    Declined,
+    /// The OS could not open it.
+    OpenFailed(String),
+}
+
+impl std::fmt::Display for OpenUrlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Invalid(e) => write!(f, "Invalid URL: {e}"),
+            Self::SchemeNotAllowed(s) => write!(f, "Links with the \"{s}:\" scheme are not allowed"),
+            Self::Declined => f.write_str("Opening the link was cancelled"),
+            Self::OpenFailed(e) => write!(f, "Failed to open link: {e}"),
+        }
+    }
+}
+
+/// What `url_policy` lets `open_url` do with an acceptable link.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UrlDecision {
+    Open,
+    Confirm,
+}
+
+/// Classify a link. The returned URL is the parsed, re-serialised form, so
+/// what gets opened is exactly what was checked.
+pub fn url_policy(raw: &str) -> Result<(url::Url, UrlDecision), OpenUrlError> {
+    let url = url::Url::parse(raw.trim()).map_err(|e| OpenUrlError::Invalid(e.to_string()))?;
+    // The url crate lowercases schemes, so "HTTPS:" and "File:" are caught too.
+    let decision = if URL_ALLOWED_SCHEMES.contains(&url.scheme()) {
+        UrlDecision::Open
+    } else if URL_CONFIRM_SCHEMES.contains(&url.scheme()) {
+        UrlDecision::Confirm
+    } else {
+        return Err(OpenUrlError::SchemeNotAllowed(url.scheme().to_string()));
+    };
+    Ok((url, decision))
+}
+
+/// Open a link in the user's default handler, subject to `url_policy`.
+#[tauri::command]
+pub(crate) async fn open_url(app: AppHandle, url: String) -> Result<(), OpenUrlError> {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+    use tauri_plugin_opener::OpenerExt;
+
+    let (url, decision) = url_policy(&url)?;
+
+    if decision == UrlDecision::Confirm {
+        let dialog = app
+            .dialog()
+            .message(format!("This link will open another application:\n\n{url}"))
+            .title(format!("Open {}: link?", url.scheme()))
+            .kind(MessageDialogKind::Warning)
+            .buttons(MessageDialogButtons::OkCancelCustom("Open".into(), "Cancel".into()));
+        // The dialog blocks until answered; keep it off the async runtime.
+        let confirmed = tauri::async_runtime::spawn_blocking(move || dialog.blocking_show())
+            .await
+            .unwrap_or(false);
+        if !confirmed {
+            return Err(OpenUrlError::Declined);
+        }
+    }
+
+    app.opener()
+        .open_url(url.as_str(), None::<&str>)
+        .map_err(|e| OpenUrlError::OpenFailed(e.to_string()))
+}
+
+// ── Navigation policy ────────────────────────────────────────────────────────
+//
+// Server webviews show one Sharkord server and nothing else. Navigations and
+// new-window requests are classified by `classify_navigation`: the server's
+// own pages stay in the webview, as do the sign-in hosts the user allowlisted
+// for that server (SSO / OAuth round trips that must come back with the
+// session). Everything else is refused in the webview and handed to
+// `open_url`, which applies the external-link policy above.
+//
+// Only navigations the user started are handed to `open_url`; an off-server
+// load the page starts by itself is refused silently. Tauri's `on_navigation`
+// says neither which frame navigates nor whether it was a user gesture, and
+// under WebKitGTK it fires for every iframe, so there the decision is made
+// in WebKit's own policy callback (`watch_navigations`). WebView2 only asks
+// about the main frame. WKWebView asks about frames as well but wry passes on
+// just the URL, so on macOS an embedded third-party frame still ends up in
+// the browser.
+
+/// Schemes the engine uses for its own documents (blank frames, generated
+/// downloads); they never leave the webview.
+const NAVIGATION_INTERNAL_SCHEMES: &[&str] = &["about", "blob", "data"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NavigationTarget {
+    /// Load it in the server webview.
+    InApp,
+    /// Refuse it in the webview and pass it to `open_url`.
+    External,
+}
+
+/// Whether `host` is `domain` or one of its subdomains.
+fn host_matches_domain(host: &str, domain: &str) -> bool {
+    host == domain || host.strip_suffix(domain).is_some_and(|rest| rest.ends_with('.'))
+}
+
+/// Whether `target` is on the server at `server`: its origin, or the same
+/// host upgraded from http to https (the usual redirect), whether onto 443
+/// or the server's own port.
+pub(crate) fn is_server_origin(server: &url::Url, target: &url::Url) -> bool {
+    let same_host = matches!((server.host_str(), target.host_str()), (Some(a), Some(b)) if a.eq_ignore_ascii_case(b));
+    let upgraded = same_host
+        && server.scheme() == "http"
+        && target.scheme() == "https"
+        && (target.port_or_known_default() == Some(443) || target.port() == server.port());
+    target.origin() == server.origin() || upgraded
+}
+
+/// Decide where `target` opens for the server at `server`. `in_app_domains`
+/// are the server's allowlisted sign-in domains, as `normalize_in_app_domain`
+/// leaves them.
+pub fn classify_navigation(server: &url::Url, target: &url::Url, in_app_domains: &[String]) -> NavigationTarget {
+    if NAVIGATION_INTERNAL_SCHEMES.contains(&target.scheme()) {
+        return NavigationTarget::InApp;
+    }
+    if !matches!(target.scheme(), "http" | "https") {
+        return NavigationTarget::External;
+    }
+    let Some(host) = target.host_str() else { return NavigationTarget::External };
+    let host = host.to_ascii_lowercase();
+
+    if is_server_origin(server, target) {
+        return NavigationTarget::InApp;
+    }
+
+    // Sign-in providers, over https only.
+    if target.scheme() == "https" && in_app_domains.iter().any(|d| host_matches_domain(&host, d)) {
+        return NavigationTarget::InApp;
+    }
+    NavigationTarget::External
+}
+
+/// Reduce user input to a bare lowercase domain: `https://Login.Example.com/x`,
+/// `*.example.com` and `.example.com` become `login.example.com`,
+/// `example.com` and `example.com`.
+pub fn normalize_in_app_domain(raw: &str) -> Result<String, String> {
+    let trimmed = raw.trim();
+    let without_wildcard = trimmed.trim_start_matches("*.").trim_start_matches('.');
+    let with_scheme = if without_wildcard.contains("://") {
+        without_wildcard.to_string()
+    } else {
+        format!("https://{without_wildcard}")
+    };
+    let url = url::Url::parse(&with_scheme).map_err(|e| format!("Invalid domain {trimmed:?}: {e}"))?;
+    match url.host() {
+        Some(url::Host::Domain(domain)) if domain.contains('.') => Ok(domain.to_ascii_lowercase()),
+        Some(url::Host::Domain(_)) => Err(format!("{trimmed:?} is not a full domain name")),
+        Some(_) => Err(format!("{trimmed:?} is an IP address; enter a domain name")),
+        None => Err(format!("Invalid domain {trimmed:?}")),
+    }
+}
+
+/// The allowlisted sign-in domains of a server, read when each navigation
+/// happens so edits apply to live webviews.
+pub(crate) fn server_in_app_domains(app: &AppHandle, id: &ServerId) -> Vec<String> {
+    app.state::<SharedServerRegistry>()
+        .lock()
+        .unwrap()
+        .get(id)
+        .map(|s| s.in_app_domains.clone())
+        .unwrap_or_default()
+}
+
+/// Hand a refused navigation to `open_url`. Runs off the webview's callback,
+/// which must return without waiting on a confirmation dialog.
+pub(crate) fn open_navigation_externally(app: &AppHandle, url: &url::Url) {
+    let app = app.clone();
+    let url = url.to_string();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = open_url(app, url.clone()).await {
+            if e != OpenUrlError::Declined {
+                log::warn!("Not opening {url}: {e}");
+            }
+        }
+    });
+}
+
+/// WebKitGTK's navigation policy for a server webview, in place of Tauri's
+/// `on_navigation`: WebKit knows whether the user started a navigation, so
+/// an off-server page the user clicked through to opens in the browser, and
+/// one the page loads by itself — an embedded frame, a script redirect — is
+/// refused without a word.
+#[cfg(target_os = "linux")]
+pub(crate) fn watch_navigations(webview: &webkit2gtk::WebView, app: AppHandle, server_id: ServerId, server_url: url::Url) {
+    use webkit2gtk::glib::Cast;
+    use webkit2gtk::{NavigationPolicyDecisionExt, PolicyDecisionExt, PolicyDecisionType, URIRequestExt, WebViewExt};
+
+    webview.connect_decide_policy(move |_, decision, kind| {
+        if !matches!(kind, PolicyDecisionType::NavigationAction) {
+            return false;
+        }
+        let Some(action) = decision
+            .downcast_ref::<webkit2gtk::NavigationPolicyDecision>()
+            .and_then(|d| d.navigation_action())
+        else {
+            return false;
+        };
+        let Some(target) = action.request().and_then(|r| r.uri()).and_then(|u| url::Url::parse(&u).ok()) else {
+            return false;
+        };
+        let domains = server_in_app_domains(&app, &server_id);
+        if classify_navigation(&server_url, &target, &domains) == NavigationTarget::InApp {
+            // WebKit's default: load it.
+            return false;
+        }
+        if action.is_user_gesture() {
+            open_navigation_externally(&app, &target);
+        }
+        decision.ignore();
+        true
+    });
+}
+
+/// Replace the sign-in domains that stay inside a server's webview.
+#[tauri::command]
+pub(crate) fn set_server_in_app_domains(
+    app: AppHandle,
+    registry: tauri::State<'_, SharedServerRegistry>,
+    server_id: ServerId,
+    domains: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let mut normalized = Vec::new();
+    for domain in domains.iter().filter(|d| !d.trim().is_empty()) {
+        let domain = normalize_in_app_domain(domain)?;
+        if !normalized.contains(&domain) {
+            normalized.push(domain);
+        }
+    }
+    commit_server_change(&app, &registry, ServerChange::Updated { id: server_id.clone() }, |next| {
+        next.modify(&server_id, |s| s.in_app_domains = normalized.clone())
+    })?;
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_policy_opens_web_and_mail_links() {
+        for raw in ["https://example.com/a?b=c", "http://example.com", "mailto:someone@example.com", "  HTTPS://Example.com  "] {
+            let (_, decision) = url_policy(raw).unwrap();
+            assert_eq!(decision, UrlDecision::Open, "{raw}");
+        }
+        // What gets opened is the re-serialised URL that was checked.
+        assert_eq!(url_policy(" HTTPS://Example.com ").unwrap().0.as_str(), "https://example.com/");
+    }
+
+    #[test]
+    fn url_policy_confirms_app_handlers() {
+        for raw in ["tel:+15551234567", "magnet:?xt=urn:btih:abc", "steam://run/440", "Spotify:track:1", "zoommtg://zoom.us/join"] {
+            let (_, decision) = url_policy(raw).unwrap();
+            assert_eq!(decision, UrlDecision::Confirm, "{raw}");
+        }
+    }
+
+    #[test]
+    fn url_policy_refuses_other_schemes() {
+        for (raw, scheme) in [
+            ("file:///etc/passwd", "file"),
+            ("FILE:///C:/Windows/System32/calc.exe", "file"),
+            ("javascript:alert(1)", "javascript"),
+            ("data:text/html,<script>alert(1)</script>", "data"),
+            ("ms-settings:privacy", "ms-settings"),
+            ("smb://host/share", "smb"),
+        ] {
+            assert_eq!(url_policy(raw).unwrap_err(), OpenUrlError::SchemeNotAllowed(scheme.into()), "{raw}");
+        }
+    }
+
+    #[test]
+    fn url_policy_reports_malformed_input_as_invalid() {
+        for raw in ["", "   ", "example.com", "/relative/path", "https://", "http://exa mple.com"] {
+            assert!(matches!(url_policy(raw), Err(OpenUrlError::Invalid(_))), "{raw:?}");
+        }
+        // The error reaches the frontend as { kind, detail }.
+        let json = serde_json::to_value(OpenUrlError::SchemeNotAllowed("file".into())).unwrap();
+        assert_eq!(json, serde_json::json!({ "kind": "schemeNotAllowed", "detail": "file" }));
+    }
+
+    #[test]
+    fn classify_navigation_table() {
+        use NavigationTarget::{External, InApp};
+        let domains = ["example.com".to_string(), "login.corp.io".to_string()];
+        for (server, target, expected) in [
+            // Same origin, whatever the path or case of the host.
+            ("https://chat.example.org", "https://chat.example.org/channels/1", InApp),
+            ("https://chat.example.org:4991", "https://CHAT.example.org:4991/x", InApp),
+            ("https://chat.example.org:4991", "https://chat.example.org/", External),
+            ("https://chat.example.org", "http://chat.example.org/", External),
+            // http → https upgrade, onto 443 or the server's own port.
+            ("http://chat.example.org", "https://chat.example.org/", InApp),
+            ("http://chat.example.org:4991", "https://chat.example.org/", InApp),
+            ("http://chat.example.org:4991", "https://chat.example.org:4991/", InApp),
+            ("http://chat.example.org:4991", "https://chat.example.org:8443/", External),
+            ("http://chat.example.org", "https://evil.example.net/", External),
+            // Allowlisted sign-in domains and their subdomains, https only.
+            ("https://chat.example.org", "https://example.com/oauth", InApp),
+            ("https://chat.example.org", "https://accounts.example.com/oauth", InApp),
+            ("https://chat.example.org", "https://login.corp.io/", InApp),
+            ("https://chat.example.org", "https://corp.io/", External),
+            ("https://chat.example.org", "https://badexample.com/", External),
+            ("https://chat.example.org", "https://example.com.evil.net/", External),
+            ("https://chat.example.org", "http://example.com/oauth", External),
+            // The engine's own documents stay; scripts and other schemes go.
+            ("https://chat.example.org", "about:blank", InApp),
+            ("https://chat.example.org", "blob:https://chat.example.org/3b1c", InApp),
+            ("https://chat.example.org", "data:text/html,hi", InApp),
+            ("https://chat.example.org", "javascript:alert(1)", External),
+            ("https://chat.example.org", "mailto:a@example.com", External),
+            ("https://chat.example.org", "file:///etc/passwd", External),
+        ] {
+            let server_url = url::Url::parse(server).unwrap();
+            let target_url = url::Url::parse(target).unwrap();
+            assert_eq!(classify_navigation(&server_url, &target_url, &domains), expected, "{server} → {target}");
+        }
+    }
+
+    #[test]
+    fn host_matches_domain_needs_a_label_boundary() {
+        assert!(host_matches_domain("example.com", "example.com"));
+        assert!(host_matches_domain("a.b.example.com", "example.com"));
+        assert!(!host_matches_domain("badexample.com", "example.com"));
+        assert!(!host_matches_domain("example.com.evil.net", "example.com"));
+        assert!(!host_matches_domain("com", "example.com"));
+    }
+
+    #[test]
+    fn normalize_in_app_domain_reduces_input_to_a_domain() {
+        for (raw, expected) in [
+            ("example.com", "example.com"),
+            ("  Login.Example.COM ", "login.example.com"),
+            ("https://Login.Example.com/oauth?x=1", "login.example.com"),
+            ("*.example.com", "example.com"),
+            (".example.com", "example.com"),
+            ("example.com:8443", "example.com"),
+        ] {
+            assert_eq!(normalize_in_app_domain(raw).unwrap(), expected, "{raw}");
+        }
+        for raw in ["", "localhost", "intranet", "127.0.0.1", "[::1]", "https://10.0.0.1/", "exa mple.com"] {
+            assert!(normalize_in_app_domain(raw).is_err(), "accepted {raw:?}");
+        }
+    }
+}
@@ -0,0 +1,361 @@
+// ── Notifications ────────────────────────────────────────────────────────────
+//
+// Sharkord raises notifications through the web Notification API, which
+// inside a server webview either goes nowhere or shows up with no hint of
+// which server sent it. NOTIFICATION_SCRIPT replaces `window.Notification`
+// with a shim that hands each one to `notify`, and we raise it natively
+// under the server's name and icon — unless the server is muted or
+// do-not-disturb is on. Clicking it brings the main window forward, switches
+// to the server and fires the page's own `click` handler.
+//
+// Server pages are remote content. Tauri only lets those call commands that
+// a plugin declares and a `remote` capability grants, so `notify` lives in
+// the inlined `server-bridge` plugin (see build.rs and
+// capabilities/server-bridge.json) rather than in the app's handler, which
+// stays out of their reach. The capability can only name the webviews, so
+// each bridge command also checks that the calling page is on the server.
+
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+use crate::{
+    CONFIG_STORE, SERVER_ICON_FILE, Server, ServerChange, ServerId, SharedServerRegistry,
+    commit_server_change, server_data_dir,
+};
+use crate::badges::update_badges;
+use crate::navigation::is_server_origin;
+use crate::tray::show_main_window;
+
+pub(crate) const NOTIFICATION_SCRIPT: &str = r#"
+(function () {
+  var _internals = window.__TAURI_INTERNALS__;
+  if (!_internals) return;
+
+  /* Notifications the page may still get a click for, by id. Bounded so a
+     chatty page can't grow it forever; the oldest simply stop being
+     clickable. */
+  var _live = {};
+  var _order = [];
+  var _nextId = 1;
+  var MAX_LIVE = 100;
+
+  class SsNotification extends EventTarget {
+    constructor(title, options) {
+      super();
+      if (arguments.length === 0) {
+        throw new TypeError("Failed to construct 'Notification': 1 argument required, but only 0 present.");
+      }
+      options = options || {};
+      this.title = String(title);
+      this.body = options.body == null ? '' : String(options.body);
+      this.tag = options.tag == null ? '' : String(options.tag);
+      this.icon = options.icon == null ? '' : String(options.icon);
+      this.data = options.data === undefined ? null : options.data;
+      this.silent = !!options.silent;
+      this.onclick = this.onshow = this.onclose = this.onerror = null;
+
+      var self = this, id = _nextId++;
+      this.__ssId = id;
+      _live[id] = this;
+      _order.push(id);
+      if (_order.length > MAX_LIVE) delete _live[_order.shift()];
+
+      _internals.invoke('plugin:server-bridge|notify', {
+        notification: { id: id, title: this.title, body: this.body, focused: document.hasFocus() }
+      }).then(function () {
+        self.__ssFire('show');
+      }, function () {
+        delete _live[id];
+        self.__ssFire('error');
+      });
+    }
+
+    close() {
+      if (!_live[this.__ssId]) return;
+      delete _live[this.__ssId];
+      this.__ssFire('close');
+    }
+
+    __ssFire(type) {
+      var ev = new Event(type, { cancelable: type === 'click' });
+      var handler = this['on' + type];
+      if (typeof handler === 'function') handler.call(this, ev);
+      this.dispatchEvent(ev);
+    }
+
+    static get permission() { return 'granted'; }
+    static get maxActions() { return 0; }
+    static requestPermission(callback) {
+      if (typeof callback === 'function') callback('granted');
+      return Promise.resolve('granted');
+    }
+  }
+
+  /* Called by Rust when the native notification is clicked. */
+  window.__ssNotify = {
+    clicked: function (id) {
+      var n = _live[id];
+      if (!n) return;
+      delete _live[id];
+      n.__ssFire('click');
+    }
+  };
+
+  window.Notification = SsNotification;
+
+  /* Pages often ask the Permissions API before trying. */
+  if (navigator.permissions && navigator.permissions.query) {
+    var _query = navigator.permissions.query.bind(navigator.permissions);
+    navigator.permissions.query = function (desc) {
+      if (desc && desc.name === 'notifications') {
+        var status = new EventTarget();
+        status.name = 'notifications';
+        status.state = 'granted';
+        status.onchange = null;
+        return Promise.resolve(status);
+      }
+      return _query(desc);
+    };
+  }
+})();
+"#;
+
+/// Name of the inlined plugin that carries the commands server pages may
+/// call. Must match build.rs and capabilities/server-bridge.json.
+const SERVER_BRIDGE_PLUGIN: &str = "server-bridge";
+
+/// Key under which `NotificationSettings` is kept in `CONFIG_STORE`.
+const NOTIFICATIONS_KEY: &str = "notifications";
+
+/// Emitted when the user clicks a notification, so the UI can switch to the
+/// server that raised it.
+const NOTIFICATION_CLICKED_EVENT: &str = "notification://clicked";
+
+/// Page-supplied text is cut to this many characters before it reaches the
+/// OS; notification centres truncate long text anyway.
+const NOTIFICATION_TITLE_LIMIT: usize = 200;
+const NOTIFICATION_BODY_LIMIT: usize = 1000;
+
+/// Each shown notification parks a thread waiting for its click. Past this
+/// many, new notifications are still shown but can't be clicked through —
+/// some notification daemons keep entries around until dismissed. Not on
+/// macOS, where the wait is what delivers the banner.
+#[cfg(not(target_os = "macos"))]
+const MAX_CLICK_WAITERS: usize = 32;
+
+#[cfg(not(target_os = "macos"))]
+static CLICK_WAITERS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+/// App-wide notification policy. Per-server mutes live on `Server`.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NotificationSettings {
+    /// Drop every notification.
+    pub do_not_disturb: bool,
+    /// When set, do-not-disturb ends by itself at this time (Unix ms);
+    /// otherwise it stays on until switched off.
+    pub do_not_disturb_until: Option<u64>,
+}
+
+impl NotificationSettings {
+    /// Whether do-not-disturb is in effect at `now_ms`.
+    pub fn quiet_at(&self, now_ms: u64) -> bool {
+        self.do_not_disturb && self.do_not_disturb_until.is_none_or(|until| now_ms < until)
+    }
+
+    pub(crate) fn load(app: &AppHandle) -> Self {
+        use tauri_plugin_store::StoreExt;
+
+        app.store(CONFIG_STORE)
+            .ok()
+            .and_then(|store| store.get(NOTIFICATIONS_KEY))
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, app: &AppHandle) -> Result<(), String> {
+        use tauri_plugin_store::StoreExt;
+
+        let store = app.store(CONFIG_STORE).map_err(|e| e.to_string())?;
+        store.set(NOTIFICATIONS_KEY, serde_json::to_value(self).map_err(|e| e.to_string())?);
+        store.save().map_err(|e| e.to_string())
+    }
+}
+
+pub type SharedNotificationSettings = Arc<Mutex<NotificationSettings>>;
+
+/// A notification as the shim forwards it.
+#[derive(Clone, Debug, serde::Deserialize)]
+pub struct PageNotification {
+    /// The shim's handle for it, passed back on click.
+    pub id: u64,
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    /// The page had focus when it raised it, so the user has seen it.
+    #[serde(default)]
+    pub focused: bool,
+}
+
+/// Payload of `notification://clicked`.
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationClickedEvent {
+    pub server_id: ServerId,
+}
+
+fn unix_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// At most `limit` characters of `s`, with an ellipsis when cut.
+fn truncate_chars(s: &str, limit: usize) -> String {
+    match s.char_indices().nth(limit) {
+        Some((end, _)) => format!("{}…", &s[..end]),
+        None => s.to_string(),
+    }
+}
+
+/// Raise `notification` for `server` and wait for a click on a background
+/// thread. Blocking: on Linux, showing it is a D-Bus round trip.
+fn show_server_notification(app: &AppHandle, server: &Server, notification: &PageNotification) -> Result<(), String> {
+    let mut native = notify_rust::Notification::new();
+    native
+        .summary(&truncate_chars(&notification.title, NOTIFICATION_TITLE_LIMIT))
+        .body(&truncate_chars(&notification.body, NOTIFICATION_BODY_LIMIT))
+        // Linux notification daemons show the app name; Windows and macOS
+        // show the subtitle.
+        .appname(&server.name)
+        .subtitle(&server.name);
+    if server.icon_rev.is_some() {
+        if let Ok(dir) = server_data_dir(app, &server.id) {
+            native.image_path(&dir.join(SERVER_ICON_FILE).to_string_lossy());
+        }
+    }
+    // XDG daemons only report a click on the body when a "default" action
+    // exists; elsewhere actions become buttons, and a body click is reported
+    // anyway.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    native.action("default", "Open");
+    // An AppUserModelID only resolves for the installed app; toasts from a
+    // dev build go out under PowerShell's instead.
+    #[cfg(windows)]
+    if !tauri::is_dev() {
+        native.app_id(&app.config().identifier);
+    }
+
+    let handle = native.show().map_err(|e| format!("Failed to show notification: {e}"))?;
+    #[cfg(not(target_os = "macos"))]
+    if CLICK_WAITERS.fetch_add(1, std::sync::atomic::Ordering::SeqCst) >= MAX_CLICK_WAITERS {
+        CLICK_WAITERS.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+        return Ok(());
+    }
+    let app = app.clone();
+    let server_id = server.id.clone();
+    let id = notification.id;
+    std::thread::spawn(move || {
+        let result = handle.wait_for_response(|response: &notify_rust::NotificationResponse| {
+            if *response == notify_rust::NotificationResponse::Default {
+                open_server_from_notification(&app, &server_id, id);
+            }
+        });
+        if let Err(e) = result {
+            log::warn!("Lost track of a notification for {server_id}: {e}");
+        }
+        #[cfg(not(target_os = "macos"))]
+        CLICK_WAITERS.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+    });
+    Ok(())
+}
+
+/// Bring the main window forward on `server_id` and let the page handle the
+/// click on its notification `id`.
+fn open_server_from_notification(app: &AppHandle, server_id: &ServerId, id: u64) {
+    show_main_window(app);
+    let _ = app.emit(NOTIFICATION_CLICKED_EVENT, NotificationClickedEvent { server_id: server_id.clone() });
+    if let Some(webview) = app.get_webview(&server_id.webview_label()) {
+        let _ = webview.eval(format!("window.__ssNotify && window.__ssNotify.clicked({id})"));
+    }
+}
+
+/// The server a `server-bridge` command comes from. It is named by the
+/// calling webview's label, never by the page, and only answers while that
+/// webview shows the server's own origin: the capability admits any page a
+/// server webview may be on, sign-in providers included.
+pub(crate) fn bridge_caller(webview: &tauri::Webview, registry: &SharedServerRegistry) -> Result<Server, String> {
+    let server_id = ServerId::from_webview_label(webview.label())?;
+    let server = registry
+        .lock()
+        .unwrap()
+        .get(&server_id)
+        .cloned()
+        .ok_or_else(|| format!("Unknown server {server_id}"))?;
+    let page = webview.url().map_err(|e: tauri::Error| e.to_string())?;
+    match url::Url::parse(&server.url) {
+        Ok(server_url) if is_server_origin(&server_url, &page) => Ok(server),
+        _ => Err(format!("{} is not {}", page.origin().ascii_serialization(), server.name)),
+    }
+}
+
+/// Called by NOTIFICATION_SCRIPT in a server webview, from the server's own
+/// pages only (`bridge_caller`). A muted server or do-not-disturb drops the
+/// notification without telling the page; under do-not-disturb it still
+/// counts towards the unread badge.
+#[tauri::command]
+pub(crate) async fn notify(
+    app: AppHandle,
+    webview: tauri::Webview,
+    registry: tauri::State<'_, SharedServerRegistry>,
+    settings: tauri::State<'_, SharedNotificationSettings>,
+    notification: PageNotification,
+) -> Result<(), String> {
+    let server = bridge_caller(&webview, &registry)?;
+    if server.notifications_muted {
+        return Ok(());
+    }
+    update_badges(&app, |state| state.add_mention(&server.id, notification.focused));
+    if settings.lock().unwrap().quiet_at(unix_ms()) {
+        return Ok(());
+    }
+    tauri::async_runtime::spawn_blocking(move || show_server_notification(&app, &server, &notification))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// The `server-bridge` plugin: the commands server pages may call.
+pub(crate) fn server_bridge_plugin() -> tauri::plugin::TauriPlugin<tauri::Wry> {
+    tauri::plugin::Builder::new(SERVER_BRIDGE_PLUGIN)
+        .invoke_handler(tauri::generate_handler![notify, crate::badges::report_title])
+        .build()
+}
+
+#[tauri::command]
+pub(crate) fn get_notification_settings(settings: tauri::State<'_, SharedNotificationSettings>) -> NotificationSettings {
+    settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub(crate) fn set_notification_settings(
+    app: AppHandle,
+    state: tauri::State<'_, SharedNotificationSettings>,
+    settings: NotificationSettings,
+) -> Result<(), String> {
+    let mut locked = state.lock().unwrap();
+    settings.save(&app)?;
+    *locked = settings;
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn set_server_notifications_muted(
+    app: AppHandle,
+    registry: tauri::State<'_, SharedServerRegistry>,
+    server_id: ServerId,
+    muted: bool,
+) -> Result<(), String> {
+    commit_server_change(&app, &registry, ServerChange::Updated { id: server_id.clone() }, |next| {
+        next.modify(&server_id, |s| s.notifications_muted = muted)
+    })
+}
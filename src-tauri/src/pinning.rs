@@ -0,0 +1,300 @@
+// ── Certificate pinning ──────────────────────────────────────────────────────
+//
+// Self-hosted servers often run on self-signed certificates. The first time a
+// server's certificate fails normal verification the user is shown its SHA-256
+// fingerprint and may trust it; from then on that exact certificate is
+// accepted for that server, by our own HTTP calls and — where the platform
+// offers a hook — by its webview. Certificates that chain to a public root
+// are accepted as usual; a pin only ever adds the one certificate.
+//
+// Webview hooks: WebView2's ServerCertificateErrorDetected and WebKitGTK's
+// load-failed-with-tls-errors. WKWebView exposes none through Tauri, so on
+// macOS a self-signed server must still be trusted in Keychain Access.
+
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use crate::{ServerChange, ServerId, SharedServerRegistry, commit_server_change};
+
+/// SHA-256 of a DER certificate as uppercase colon-separated hex, the form
+/// browsers and `openssl x509 -fingerprint` show.
+pub fn cert_fingerprint(der: &[u8]) -> String {
+    use sha2::Digest;
+    sha2::Sha256::digest(der).iter().map(|b| format!("{b:02X}")).collect::<Vec<_>>().join(":")
+}
+
+/// Accept a fingerprint with or without colons, in either case.
+fn normalize_fingerprint(raw: &str) -> Result<String, String> {
+    let hex: Vec<char> = raw.chars().filter(|c| *c != ':' && !c.is_whitespace()).collect();
+    if hex.len() != 64 || !hex.iter().all(char::is_ascii_hexdigit) {
+        return Err(format!("Not a SHA-256 fingerprint: {raw}"));
+    }
+    Ok(hex
+        .chunks(2)
+        .map(|pair| pair.iter().collect::<String>().to_ascii_uppercase())
+        .collect::<Vec<_>>()
+        .join(":"))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CertTrust {
+    /// Chains to a public root.
+    Trusted,
+    /// Fails normal verification but is the server's pinned certificate.
+    Pinned,
+    /// Fails normal verification; the server has no pin.
+    Untrusted,
+    /// Fails normal verification and is not the pinned certificate.
+    PinMismatch,
+}
+
+/// The leaf certificate a server presented and how it was judged.
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerCertificate {
+    pub sha256: String,
+    pub trust: CertTrust,
+    /// Why normal verification rejected it, when it did.
+    pub error: Option<String>,
+}
+
+impl PeerCertificate {
+    /// The server can't be loaded until the user trusts this certificate.
+    pub fn needs_trust(&self) -> bool {
+        matches!(self.trust, CertTrust::Untrusted | CertTrust::PinMismatch)
+    }
+}
+
+pub(crate) fn crypto_provider() -> Arc<tokio_rustls::rustls::crypto::CryptoProvider> {
+    static PROVIDER: std::sync::OnceLock<Arc<tokio_rustls::rustls::crypto::CryptoProvider>> =
+        std::sync::OnceLock::new();
+    PROVIDER
+        .get_or_init(|| Arc::new(tokio_rustls::rustls::crypto::ring::default_provider()))
+        .clone()
+}
+
+/// Normal verification, against the same webpki roots reqwest uses.
+fn root_verifier() -> Arc<tokio_rustls::rustls::client::WebPkiServerVerifier> {
+    use tokio_rustls::rustls;
+    static VERIFIER: std::sync::OnceLock<Arc<rustls::client::WebPkiServerVerifier>> = std::sync::OnceLock::new();
+    VERIFIER
+        .get_or_init(|| {
+            let roots = rustls::RootCertStore { roots: webpki_roots::TLS_SERVER_ROOTS.to_vec() };
+            rustls::client::WebPkiServerVerifier::builder_with_provider(Arc::new(roots), crypto_provider())
+                .build()
+                .expect("webpki roots are valid trust anchors")
+        })
+        .clone()
+}
+
+/// rustls verifier for one server: public roots first, then the pin. Keeps
+/// the last certificate it judged so probes can report it.
+#[derive(Debug)]
+pub struct PinningVerifier {
+    pin: Option<String>,
+    seen: Mutex<Option<PeerCertificate>>,
+}
+
+impl PinningVerifier {
+    pub fn new(pin: Option<String>) -> Arc<Self> {
+        Arc::new(Self { pin, seen: Mutex::new(None) })
+    }
+
+    pub fn seen(&self) -> Option<PeerCertificate> {
+        self.seen.lock().unwrap().clone()
+    }
+
+    pub fn client_config(self: &Arc<Self>) -> tokio_rustls::rustls::ClientConfig {
+        tokio_rustls::rustls::ClientConfig::builder_with_provider(crypto_provider())
+            .with_safe_default_protocol_versions()
+            .expect("ring supports the default protocol versions")
+            .dangerous()
+            .with_custom_certificate_verifier(self.clone())
+            .with_no_client_auth()
+    }
+}
+
+impl tokio_rustls::rustls::client::danger::ServerCertVerifier for PinningVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &tokio_rustls::rustls::pki_types::CertificateDer<'_>,
+        intermediates: &[tokio_rustls::rustls::pki_types::CertificateDer<'_>],
+        server_name: &tokio_rustls::rustls::pki_types::ServerName<'_>,
+        ocsp_response: &[u8],
+        now: tokio_rustls::rustls::pki_types::UnixTime,
+    ) -> Result<tokio_rustls::rustls::client::danger::ServerCertVerified, tokio_rustls::rustls::Error> {
+        use tokio_rustls::rustls::{self, client::danger::ServerCertVerified};
+
+        let sha256 = cert_fingerprint(end_entity);
+        let verdict =
+            root_verifier().verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now);
+        let (trust, error, result) = match verdict {
+            Ok(verified) => (CertTrust::Trusted, None, Ok(verified)),
+            Err(e) => match &self.pin {
+                Some(pin) if *pin == sha256 => (CertTrust::Pinned, Some(e.to_string()), Ok(ServerCertVerified::assertion())),
+                Some(_) => (
+                    CertTrust::PinMismatch,
+                    Some(e.to_string()),
+                    Err(rustls::Error::InvalidCertificate(rustls::CertificateError::ApplicationVerificationFailure)),
+                ),
+                None => (CertTrust::Untrusted, Some(e.to_string()), Err(e)),
+            },
+        };
+        *self.seen.lock().unwrap() = Some(PeerCertificate { sha256, trust, error });
+        result
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &tokio_rustls::rustls::pki_types::CertificateDer<'_>,
+        dss: &tokio_rustls::rustls::DigitallySignedStruct,
+    ) -> Result<tokio_rustls::rustls::client::danger::HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+        let algorithms = &crypto_provider().signature_verification_algorithms;
+        tokio_rustls::rustls::crypto::verify_tls12_signature(message, cert, dss, algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &tokio_rustls::rustls::pki_types::CertificateDer<'_>,
+        dss: &tokio_rustls::rustls::DigitallySignedStruct,
+    ) -> Result<tokio_rustls::rustls::client::danger::HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+        let algorithms = &crypto_provider().signature_verification_algorithms;
+        tokio_rustls::rustls::crypto::verify_tls13_signature(message, cert, dss, algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<tokio_rustls::rustls::SignatureScheme> {
+        crypto_provider().signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// The certificate fingerprint pinned for a server, if any.
+fn server_cert_pin(app: &AppHandle, id: &ServerId) -> Option<String> {
+    app.state::<SharedServerRegistry>().lock().unwrap().get(id)?.cert_sha256.clone()
+}
+
+/// Pin the certificate with fingerprint `sha256` for a server, or with None
+/// forget its pin. The fingerprint is one the user has just been shown by
+/// `probe_server`; a server webview picks the change up on its next load.
+#[tauri::command]
+pub(crate) fn trust_server_certificate(
+    app: AppHandle,
+    registry: tauri::State<'_, SharedServerRegistry>,
+    server_id: ServerId,
+    sha256: Option<String>,
+) -> Result<(), String> {
+    let sha256 = sha256.as_deref().map(normalize_fingerprint).transpose()?;
+    commit_server_change(&app, &registry, ServerChange::Updated { id: server_id.clone() }, |next| {
+        next.modify(&server_id, |s| s.cert_sha256 = sha256)
+    })
+}
+
+/// WebView2 raises ServerCertificateErrorDetected before failing a load over
+/// a bad certificate; allow it only for the server's pinned certificate.
+/// Needs WebView2 runtime 1.0.1245 or later — older runtimes just fail the
+/// load as before.
+#[cfg(windows)]
+pub(crate) fn watch_certificate_errors(
+    core: &webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2,
+    app: AppHandle,
+    server_id: ServerId,
+) -> windows_core::Result<()> {
+    use webview2_com::Microsoft::Web::WebView2::Win32::{
+        ICoreWebView2_14, COREWEBVIEW2_SERVER_CERTIFICATE_ERROR_ACTION_ALWAYS_ALLOW,
+        COREWEBVIEW2_SERVER_CERTIFICATE_ERROR_ACTION_CANCEL,
+    };
+    use windows_core::Interface;
+
+    unsafe {
+        let core: ICoreWebView2_14 = core.cast()?;
+        let handler = webview2_com::ServerCertificateErrorDetectedEventHandler::create(Box::new(move |_, args| {
+            let Some(args) = args else { return Ok(()) };
+            let mut pem = windows_core::PWSTR::null();
+            args.ServerCertificate()?.ToPemEncoding(&mut pem)?;
+            let presented = pem_to_der(&webview2_com::take_pwstr(pem)).map(|der| cert_fingerprint(&der));
+            let allowed = presented.is_some() && presented == server_cert_pin(&app, &server_id);
+            args.SetAction(if allowed {
+                COREWEBVIEW2_SERVER_CERTIFICATE_ERROR_ACTION_ALWAYS_ALLOW
+            } else {
+                COREWEBVIEW2_SERVER_CERTIFICATE_ERROR_ACTION_CANCEL
+            })
+        }));
+        let mut token = 0;
+        core.add_ServerCertificateErrorDetected(&handler, &mut token)
+    }
+}
+
+/// WebView2 hands certificates over PEM-encoded; pins are over the DER.
+#[cfg(windows)]
+fn pem_to_der(pem: &str) -> Option<Vec<u8>> {
+    use base64::Engine;
+    let body: String = pem.lines().map(str::trim).filter(|l| !l.starts_with("-----")).collect();
+    base64::engine::general_purpose::STANDARD.decode(body).ok()
+}
+
+/// WebKitGTK fails a load with a TLS error and tells us which certificate it
+/// rejected. If it is the server's pinned one, allow it for that host and
+/// retry; otherwise let the normal error page show.
+#[cfg(target_os = "linux")]
+pub(crate) fn watch_certificate_errors(webview: &webkit2gtk::WebView, app: AppHandle, server_id: ServerId) {
+    use webkit2gtk::gio::prelude::TlsCertificateExt;
+    use webkit2gtk::{WebContextExt, WebViewExt};
+
+    webview.connect_load_failed_with_tls_errors(move |webview, failing_uri, certificate, _errors| {
+        let presented = certificate.certificate().map(|der| cert_fingerprint(&der));
+        if presented.is_none() || presented != server_cert_pin(&app, &server_id) {
+            return false;
+        }
+        let host = url::Url::parse(failing_uri).ok().and_then(|u| u.host_str().map(str::to_string));
+        let (Some(context), Some(host)) = (webview.context(), host) else { return false };
+        context.allow_tls_certificate_for_host(certificate, &host);
+        webview.load_uri(failing_uri);
+        true
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_fingerprint_accepts_colon_and_plain_hex() {
+        let upper = "AB:CD:EF:01:23:45:67:89:AB:CD:EF:01:23:45:67:89:AB:CD:EF:01:23:45:67:89:AB:CD:EF:01:23:45:67:89";
+        assert_eq!(normalize_fingerprint(upper).unwrap(), upper);
+        assert_eq!(normalize_fingerprint(&upper.to_ascii_lowercase()).unwrap(), upper);
+        assert_eq!(normalize_fingerprint(&upper.replace(':', "")).unwrap(), upper);
+        assert_eq!(normalize_fingerprint("abCD ef01:2345:6789 ABCDEF0123456789abcdef0123456789ABCDEF0123456789").unwrap(), upper);
+    }
+
+    #[test]
+    fn normalize_fingerprint_rejects_wrong_length_or_digits() {
+        let plain = "ABCDEF0123456789ABCDEF0123456789ABCDEF0123456789ABCDEF0123456789";
+        for bad in ["", &plain[..62], &format!("{plain}00"), &plain.replace('A', "G"), "sha256:ABCD"] {
+            assert!(normalize_fingerprint(bad).is_err(), "accepted {bad:?}");
+        }
+    }
+
+    #[test]
+    fn pinning_verifier_judges_self_signed_certificates() {
+        use tokio_rustls::rustls::client::danger::ServerCertVerifier;
+        use tokio_rustls::rustls::pki_types::{ServerName, UnixTime};
+
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap().cert;
+        let der = cert.der();
+        let sha256 = cert_fingerprint(der);
+        let name = ServerName::try_from("localhost").unwrap();
+        let verify = |pin: Option<String>| {
+            let verifier = PinningVerifier::new(pin);
+            let result = verifier.verify_server_cert(der, &[], &name, &[], UnixTime::now());
+            let seen = verifier.seen().unwrap();
+            assert_eq!(seen.sha256, sha256);
+            (result.is_ok(), seen.trust, seen.needs_trust())
+        };
+
+        assert_eq!(verify(None), (false, CertTrust::Untrusted, true));
+        assert_eq!(verify(Some(sha256.clone())), (true, CertTrust::Pinned, false));
+        let other = normalize_fingerprint(&"00".repeat(32)).unwrap();
+        assert_eq!(verify(Some(other)), (false, CertTrust::PinMismatch, true));
+    }
+}
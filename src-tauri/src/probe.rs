@@ -0,0 +1,347 @@
+// ── Server probe ─────────────────────────────────────────────────────────────
+//
+// Staged reachability check for a server URL, so a dead server produces a
+// diagnosis instead of a blank webview. Stages run in order — DNS, TCP, TLS
+// (https only), HTTP — and the first failure ends the probe.
+
+use std::sync::Arc;
+use tauri::AppHandle;
+use crate::{ServerId, validate_server_url};
+use crate::pinning::{CertTrust, PeerCertificate, PinningVerifier};
+use crate::proxy::{ServerConnection, server_connection_async};
+
+const PROBE_STAGE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+/// Enough of the landing page to find Sharkord's markers without pulling a
+/// whole bundle.
+const PROBE_BODY_LIMIT: usize = 64 * 1024;
+
+/// Client for requests to Sharkord servers — probes, metadata, icons — with
+/// the server's pinned certificate and proxy.
+pub(crate) fn server_http_client(connection: &ServerConnection) -> reqwest::Result<reqwest::Client> {
+    verified_http_client(connection, &PinningVerifier::new(connection.pin.clone()))
+}
+
+/// `server_http_client` with a verifier the caller can inspect afterwards.
+fn verified_http_client(connection: &ServerConnection, verifier: &Arc<PinningVerifier>) -> reqwest::Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .use_preconfigured_tls(verifier.client_config())
+        .user_agent("Sweetshark-Client-v2")
+        .timeout(std::time::Duration::from_secs(10));
+    if let Some(proxy) = &connection.proxy {
+        builder = builder.proxy(proxy.reqwest_proxy()?);
+    }
+    builder.build()
+}
+
+/// Read at most `limit` bytes of a response body. The bool is true when the
+/// body was cut short.
+pub(crate) async fn read_body_limited(mut response: reqwest::Response, limit: usize) -> reqwest::Result<(Vec<u8>, bool)> {
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        body.extend_from_slice(&chunk);
+        if body.len() >= limit {
+            body.truncate(limit);
+            return Ok((body, true));
+        }
+    }
+    Ok((body, false))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProbeStage {
+    Dns,
+    Tcp,
+    Tls,
+    Http,
+}
+
+impl std::fmt::Display for ProbeStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Dns => "DNS lookup",
+            Self::Tcp => "TCP connect",
+            Self::Tls => "TLS handshake",
+            Self::Http => "HTTP request",
+        })
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProbeStep {
+    pub stage: ProbeStage,
+    pub elapsed_ms: u64,
+    /// None when the stage passed.
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProbeReport {
+    pub url: String,
+    pub steps: Vec<ProbeStep>,
+    /// Final status after redirects, when the HTTP stage got a response.
+    pub http_status: Option<u16>,
+    /// The landing page or its headers identify a Sharkord instance.
+    pub sharkord: bool,
+    /// The certificate the server presented, for https URLs that got that far.
+    pub certificate: Option<PeerCertificate>,
+    /// The server's proxy, when it has one. The DNS and TCP stages then
+    /// check the proxy, and the server is only reached by the HTTP stage.
+    pub proxy: Option<String>,
+}
+
+impl ProbeReport {
+    pub fn failure(&self) -> Option<&ProbeStep> {
+        self.steps.iter().find(|s| s.error.is_some())
+    }
+
+    /// Every stage passed. Says nothing about whether it is Sharkord.
+    pub fn reachable(&self) -> bool {
+        self.failure().is_none() && self.http_status.is_some()
+    }
+
+    /// One line for error messages.
+    pub fn summary(&self) -> String {
+        let untrusted = self.certificate.as_ref().filter(|c| c.needs_trust());
+        match self.failure() {
+            Some(step) if matches!(step.stage, ProbeStage::Tls | ProbeStage::Http) && untrusted.is_some() => {
+                let cert = untrusted.unwrap();
+                let what = match cert.trust {
+                    CertTrust::PinMismatch => "a different certificate than the one you trusted",
+                    _ => "an untrusted certificate",
+                };
+                format!("{} presented {what} (SHA-256 {})", self.url, cert.sha256)
+            }
+            Some(step) => format!(
+                "{} failed for {}: {}",
+                step.stage,
+                match (&self.proxy, step.stage) {
+                    (Some(proxy), ProbeStage::Dns | ProbeStage::Tcp) => format!("proxy {proxy}"),
+                    _ => self.url.clone(),
+                },
+                step.error.as_deref().unwrap_or_default()
+            ),
+            None if !self.sharkord => format!("{} is reachable but does not look like a Sharkord server", self.url),
+            None => format!("{} is a reachable Sharkord server", self.url),
+        }
+    }
+}
+
+/// Run `fut` with the stage timeout, recording the outcome as a step.
+async fn probe_stage<T, E: std::fmt::Display>(
+    steps: &mut Vec<ProbeStep>,
+    stage: ProbeStage,
+    fut: impl std::future::Future<Output = Result<T, E>>,
+) -> Option<T> {
+    let started = std::time::Instant::now();
+    let (value, error) = match tokio::time::timeout(PROBE_STAGE_TIMEOUT, fut).await {
+        Ok(Ok(v)) => (Some(v), None),
+        Ok(Err(e)) => (None, Some(e.to_string())),
+        Err(_) => (None, Some(format!("timed out after {} s", PROBE_STAGE_TIMEOUT.as_secs()))),
+    };
+    steps.push(ProbeStep { stage, elapsed_ms: started.elapsed().as_millis() as u64, error });
+    value
+}
+
+/// Sharkord serves its web client from the server root; the page title and
+/// bundle references name it. Also accept a Server / X-Powered-By header
+/// naming it, for reverse proxies that rewrite the page.
+fn looks_like_sharkord(headers: &reqwest::header::HeaderMap, body: &str) -> bool {
+    let header_says = [reqwest::header::SERVER, reqwest::header::HeaderName::from_static("x-powered-by")]
+        .iter()
+        .filter_map(|h| headers.get(h)?.to_str().ok())
+        .any(|v| v.to_ascii_lowercase().contains("sharkord"));
+    header_says || body.to_ascii_lowercase().contains("sharkord")
+}
+
+/// Probe `url` stage by stage, the way `connection` says to reach it.
+/// Never fails — problems are in the report.
+pub async fn probe(url: &url::Url, connection: &ServerConnection) -> ProbeReport {
+    let proxy = connection.proxy.as_ref().map(|p| &p.url);
+    let mut report = ProbeReport {
+        url: url.to_string(),
+        steps: Vec::new(),
+        http_status: None,
+        sharkord: false,
+        certificate: None,
+        proxy: proxy.map(url::Url::to_string),
+    };
+    let verifier = PinningVerifier::new(connection.pin.clone());
+    let steps = &mut report.steps;
+    // Through a proxy, the first hop is the proxy itself.
+    let first_hop = proxy.unwrap_or(url);
+    let (Some(host), Some(port)) = (first_hop.host(), first_hop.port_or_known_default()) else {
+        steps.push(ProbeStep { stage: ProbeStage::Dns, elapsed_ms: 0, error: Some("URL has no host".into()) });
+        return report;
+    };
+
+    // DNS — IP literals resolve to themselves.
+    let addrs = probe_stage(steps, ProbeStage::Dns, async {
+        let addrs: Vec<std::net::SocketAddr> = match &host {
+            url::Host::Domain(domain) => tokio::net::lookup_host((*domain, port)).await?.collect(),
+            url::Host::Ipv4(ip) => vec![(*ip, port).into()],
+            url::Host::Ipv6(ip) => vec![(*ip, port).into()],
+        };
+        if addrs.is_empty() {
+            return Err(std::io::Error::other("no addresses found"));
+        }
+        Ok(addrs)
+    })
+    .await;
+    let Some(addrs) = addrs else { return report };
+
+    // TCP — first address that accepts.
+    let stream = probe_stage(steps, ProbeStage::Tcp, async {
+        let mut last = std::io::Error::other("no addresses to try");
+        for addr in &addrs {
+            match tokio::net::TcpStream::connect(addr).await {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last = std::io::Error::new(e.kind(), format!("{addr}: {e}")),
+            }
+        }
+        Err(last)
+    })
+    .await;
+    let Some(stream) = stream else { return report };
+
+    // TLS — verified the same way as every other request to this server.
+    // Behind a proxy the handshake happens inside the HTTP stage's tunnel.
+    if url.scheme() == "https" && proxy.is_none() {
+        let server_name = url.host_str().unwrap_or_default().trim_matches(|c| c == '[' || c == ']').to_string();
+        let tls = probe_stage(steps, ProbeStage::Tls, async {
+            let name = tokio_rustls::rustls::pki_types::ServerName::try_from(server_name)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+            tokio_rustls::TlsConnector::from(Arc::new(verifier.client_config())).connect(name, stream).await
+        })
+        .await;
+        report.certificate = verifier.seen();
+        if tls.is_none() {
+            return report;
+        }
+    } else {
+        drop(stream);
+    }
+
+    // HTTP — a real request through reqwest, following redirects.
+    let response = probe_stage(steps, ProbeStage::Http, async {
+        let response = verified_http_client(connection, &verifier)?.get(url.clone()).send().await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let (body, _) = read_body_limited(response, PROBE_BODY_LIMIT).await?;
+        Ok::<_, reqwest::Error>((status, headers, body))
+    })
+    .await;
+    if report.certificate.is_none() {
+        report.certificate = verifier.seen();
+    }
+    if let Some((status, headers, body)) = response {
+        report.http_status = Some(status.as_u16());
+        report.sharkord = looks_like_sharkord(&headers, &String::from_utf8_lossy(&body));
+        // The server answered, but with an error page — as good as down.
+        if status.is_server_error() {
+            if let Some(step) = report.steps.last_mut() {
+                step.error = Some(format!("server responded {status}"));
+            }
+        }
+    }
+    report
+}
+
+/// Check whether a server URL is reachable and is a Sharkord instance,
+/// returning a stage-by-stage diagnosis. With `server_id`, that server's
+/// pinned certificate and proxy are used.
+#[tauri::command]
+pub(crate) async fn probe_server(app: AppHandle, url: String, server_id: Option<ServerId>) -> Result<ProbeReport, String> {
+    let url = validate_server_url(&url)?;
+    let connection = server_connection_async(&app, server_id).await?;
+    Ok(probe(&url, &connection).await)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A loopback HTTP server answering every request with `response`.
+    pub(crate) async fn serve(response: &'static str) -> url::Url {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    // The TCP stage connects and hangs up without a request.
+                    let mut request = Vec::new();
+                    let mut buf = [0; 1024];
+                    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                        match stream.read(&mut buf).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => request.extend_from_slice(&buf[..n]),
+                        }
+                    }
+                    let _ = stream.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        url::Url::parse(&format!("http://{addr}/")).unwrap()
+    }
+
+    #[tokio::test]
+    async fn probe_stops_at_tcp_for_a_closed_port() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = url::Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        drop(listener);
+
+        let report = probe(&url, &ServerConnection::default()).await;
+        let stages: Vec<_> = report.steps.iter().map(|s| s.stage).collect();
+        assert_eq!(stages, [ProbeStage::Dns, ProbeStage::Tcp]);
+        assert_eq!(report.failure().unwrap().stage, ProbeStage::Tcp);
+        assert!(!report.reachable());
+        assert!(report.summary().starts_with("TCP connect failed"), "{}", report.summary());
+    }
+
+    #[tokio::test]
+    async fn probe_finds_a_plain_page_reachable_but_not_sharkord() {
+        let url = serve("HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello").await;
+
+        let report = probe(&url, &ServerConnection::default()).await;
+        let stages: Vec<_> = report.steps.iter().map(|s| s.stage).collect();
+        assert_eq!(stages, [ProbeStage::Dns, ProbeStage::Tcp, ProbeStage::Http]);
+        assert!(report.reachable(), "{}", report.summary());
+        assert_eq!(report.http_status, Some(200));
+        assert!(!report.sharkord);
+        assert!(report.certificate.is_none());
+    }
+
+    #[tokio::test]
+    async fn probe_detects_sharkord() {
+        let page = "HTTP/1.1 200 OK\r\nContent-Length: 23\r\nConnection: close\r\n\r\n<title>Sharkord</title>";
+        let report = probe(&serve(page).await, &ServerConnection::default()).await;
+        assert!(report.reachable() && report.sharkord, "{}", report.summary());
+
+        // A 5xx answer is as good as down, whatever it says.
+        let down = "HTTP/1.1 502 Bad Gateway\r\nContent-Length: 8\r\nConnection: close\r\n\r\nsharkord";
+        let report = probe(&serve(down).await, &ServerConnection::default()).await;
+        assert_eq!(report.failure().unwrap().stage, ProbeStage::Http);
+        assert_eq!(report.http_status, Some(502));
+    }
+
+    #[test]
+    fn looks_like_sharkord_checks_headers_and_body() {
+        use reqwest::header::{HeaderMap, HeaderValue};
+        let none = HeaderMap::new();
+        assert!(looks_like_sharkord(&none, "<script src=\"/assets/SHARKORD-abc.js\">"));
+        assert!(!looks_like_sharkord(&none, "<title>Welcome to nginx!</title>"));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(reqwest::header::SERVER, HeaderValue::from_static("Sharkord/1.2"));
+        assert!(looks_like_sharkord(&headers, ""));
+        let mut headers = HeaderMap::new();
+        headers.insert("x-powered-by", HeaderValue::from_static("sharkord"));
+        assert!(looks_like_sharkord(&headers, ""));
+        let mut headers = HeaderMap::new();
+        headers.insert(reqwest::header::SERVER, HeaderValue::from_static("nginx"));
+        assert!(!looks_like_sharkord(&headers, "hello"));
+    }
+}
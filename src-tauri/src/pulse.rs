@@ -0,0 +1,716 @@
+// ── PulseAudio native protocol ───────────────────────────────────────────────
+//
+// A deliberately tiny client for the PulseAudio native protocol, which
+// PipeWire also speaks through pipewire-pulse. We only need a handful of
+// control commands, so instead of linking libpulse we talk to the socket
+// directly: every packet is a 20-byte descriptor followed by a "tagstruct",
+// a self-describing sequence of typed values.
+//
+// Only the control channel is used — no audio data ever flows through here.
+
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Protocol version we announce. The server answers with its own and both
+/// sides speak min(ours, theirs); replies are parsed for whichever version
+/// was negotiated.
+const PROTOCOL_VERSION: u32 = 32;
+/// The AUTH reply carries feature flags in the upper bits of the version.
+const PROTOCOL_VERSION_MASK: u32 = 0x0000_FFFF;
+
+/// Special source name resolved by the server to the current default input.
+pub const DEFAULT_SOURCE: &str = "@DEFAULT_SOURCE@";
+
+const CHANNEL_CONTROL: u32 = 0xFFFF_FFFF;
+const INVALID_INDEX: u32 = 0xFFFF_FFFF;
+const COOKIE_LEN: usize = 256;
+/// Largest packet we accept, matching the server's own FRAME_SIZE_MAX_ALLOW.
+/// The length comes straight off the socket, so it is checked before any
+/// buffer is allocated for it.
+const MAX_PACKET_LEN: usize = 16 * 1024 * 1024;
+
+// Command ids from pulsecore/native-common.h
+const COMMAND_ERROR: u32 = 0;
+const COMMAND_REPLY: u32 = 2;
+const COMMAND_AUTH: u32 = 8;
+const COMMAND_SET_CLIENT_NAME: u32 = 9;
+const COMMAND_GET_SERVER_INFO: u32 = 20;
+const COMMAND_GET_SINK_INFO_LIST: u32 = 22;
+const COMMAND_GET_SOURCE_INFO_LIST: u32 = 24;
+const COMMAND_SET_SOURCE_MUTE: u32 = 40;
+
+// Tag bytes from pulsecore/tagstruct.h
+const TAG_STRING: u8 = b't';
+const TAG_STRING_NULL: u8 = b'N';
+const TAG_U32: u8 = b'L';
+const TAG_U8: u8 = b'B';
+const TAG_USEC: u8 = b'U';
+const TAG_SAMPLE_SPEC: u8 = b'a';
+const TAG_CHANNEL_MAP: u8 = b'm';
+const TAG_CVOLUME: u8 = b'v';
+const TAG_VOLUME: u8 = b'V';
+const TAG_FORMAT_INFO: u8 = b'f';
+const TAG_ARBITRARY: u8 = b'x';
+const TAG_BOOLEAN_TRUE: u8 = b'1';
+const TAG_BOOLEAN_FALSE: u8 = b'0';
+const TAG_PROPLIST: u8 = b'P';
+
+// ── Tagstruct encoding ───────────────────────────────────────────────────────
+
+#[derive(Default)]
+pub struct TagWriter {
+    buf: Vec<u8>,
+}
+
+impl TagWriter {
+    pub fn u32(&mut self, v: u32) -> &mut Self {
+        self.buf.push(TAG_U32);
+        self.buf.extend_from_slice(&v.to_be_bytes());
+        self
+    }
+
+    pub fn string(&mut self, v: Option<&str>) -> &mut Self {
+        match v {
+            Some(s) => {
+                self.buf.push(TAG_STRING);
+                self.buf.extend_from_slice(s.as_bytes());
+                self.buf.push(0);
+            }
+            None => self.buf.push(TAG_STRING_NULL),
+        }
+        self
+    }
+
+    pub fn bool(&mut self, v: bool) -> &mut Self {
+        self.buf.push(if v { TAG_BOOLEAN_TRUE } else { TAG_BOOLEAN_FALSE });
+        self
+    }
+
+    pub fn arbitrary(&mut self, v: &[u8]) -> &mut Self {
+        self.buf.push(TAG_ARBITRARY);
+        self.buf.extend_from_slice(&(v.len() as u32).to_be_bytes());
+        self.buf.extend_from_slice(v);
+        self
+    }
+
+    /// Proplist values are NUL-terminated strings stored as arbitrary blobs.
+    pub fn proplist(&mut self, props: &[(&str, &str)]) -> &mut Self {
+        self.buf.push(TAG_PROPLIST);
+        for (key, value) in props {
+            let mut v = value.as_bytes().to_vec();
+            v.push(0);
+            self.string(Some(key));
+            self.u32(v.len() as u32);
+            self.arbitrary(&v);
+        }
+        self.string(None)
+    }
+}
+
+pub struct TagReader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> TagReader<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf }
+    }
+
+    fn tag(&mut self, expected: u8) -> Result<(), String> {
+        match self.buf.split_first() {
+            Some((&t, rest)) if t == expected => {
+                self.buf = rest;
+                Ok(())
+            }
+            Some((&t, _)) => Err(format!(
+                "PulseAudio: expected tag '{}', got '{}'",
+                expected as char, t as char
+            )),
+            None => Err("PulseAudio: truncated reply".into()),
+        }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        if self.buf.len() < n {
+            return Err("PulseAudio: truncated reply".into());
+        }
+        let (head, rest) = self.buf.split_at(n);
+        self.buf = rest;
+        Ok(head)
+    }
+
+    fn raw_u32(&mut self) -> Result<u32, String> {
+        let b = self.take(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    pub fn u32(&mut self) -> Result<u32, String> {
+        self.tag(TAG_U32)?;
+        self.raw_u32()
+    }
+
+    pub fn u8(&mut self) -> Result<u8, String> {
+        self.tag(TAG_U8)?;
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn string(&mut self) -> Result<Option<String>, String> {
+        if self.buf.first() == Some(&TAG_STRING_NULL) {
+            self.buf = &self.buf[1..];
+            return Ok(None);
+        }
+        self.tag(TAG_STRING)?;
+        let end = self
+            .buf
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or("PulseAudio: unterminated string")?;
+        let s = String::from_utf8_lossy(self.take(end)?).into_owned();
+        self.take(1)?;
+        Ok(Some(s))
+    }
+
+    pub fn bool(&mut self) -> Result<bool, String> {
+        match self.buf.first() {
+            Some(&TAG_BOOLEAN_TRUE) => {
+                self.buf = &self.buf[1..];
+                Ok(true)
+            }
+            _ => {
+                self.tag(TAG_BOOLEAN_FALSE)?;
+                Ok(false)
+            }
+        }
+    }
+
+    // The remaining types are only ever skipped: we need to walk past them to
+    // reach the next field, but never look at their contents.
+
+    fn skip_usec(&mut self) -> Result<(), String> {
+        self.tag(TAG_USEC)?;
+        self.take(8).map(drop)
+    }
+
+    fn skip_volume(&mut self) -> Result<(), String> {
+        self.tag(TAG_VOLUME)?;
+        self.take(4).map(drop)
+    }
+
+    /// Sample format (u8), channels (u8), rate (u32).
+    fn skip_sample_spec(&mut self) -> Result<(), String> {
+        self.tag(TAG_SAMPLE_SPEC)?;
+        self.take(6).map(drop)
+    }
+
+    fn skip_channel_map(&mut self) -> Result<(), String> {
+        self.tag(TAG_CHANNEL_MAP)?;
+        let n = self.take(1)?[0] as usize;
+        self.take(n).map(drop)
+    }
+
+    fn skip_cvolume(&mut self) -> Result<(), String> {
+        self.tag(TAG_CVOLUME)?;
+        let n = self.take(1)?[0] as usize;
+        self.take(n * 4).map(drop)
+    }
+
+    fn skip_arbitrary(&mut self) -> Result<(), String> {
+        self.tag(TAG_ARBITRARY)?;
+        let len = self.raw_u32()? as usize;
+        self.take(len).map(drop)
+    }
+
+    fn skip_proplist(&mut self) -> Result<(), String> {
+        self.tag(TAG_PROPLIST)?;
+        while self.string()?.is_some() {
+            self.u32()?;
+            self.skip_arbitrary()?;
+        }
+        Ok(())
+    }
+
+    fn skip_format_info(&mut self) -> Result<(), String> {
+        self.tag(TAG_FORMAT_INFO)?;
+        self.u8()?;
+        self.skip_proplist()
+    }
+}
+
+// ── Introspection ────────────────────────────────────────────────────────────
+
+/// One sink or source as reported by GET_*_INFO_LIST.
+pub struct DeviceInfo {
+    /// Stable identifier, e.g. "alsa_input.pci-0000_00_1f.3.analog-stereo".
+    pub name: String,
+    /// Human-readable name (the device.description property).
+    pub description: String,
+    /// Sources: the sink this is a monitor of. Sinks: their monitor source.
+    /// `INVALID_INDEX` when there is none.
+    linked_index: u32,
+}
+
+/// Default device names from GET_SERVER_INFO.
+pub struct ServerDefaults {
+    pub sink: Option<String>,
+    pub source: Option<String>,
+}
+
+/// Parse one entry of a sink or source info list. Both share a layout apart
+/// from the protocol version that introduced the trailing format list.
+fn read_device_info(r: &mut TagReader, version: u32, formats_since: u32) -> Result<DeviceInfo, String> {
+    r.u32()?; // index
+    let name = r.string()?.unwrap_or_default();
+    let description = r.string()?;
+    r.skip_sample_spec()?;
+    r.skip_channel_map()?;
+    r.u32()?; // owner module
+    r.skip_cvolume()?;
+    r.bool()?; // mute
+    let linked_index = r.u32()?;
+    r.string()?; // linked sink/source name
+    r.skip_usec()?; // latency
+    r.string()?; // driver
+    r.u32()?; // flags
+    if version >= 13 {
+        r.skip_proplist()?;
+        r.skip_usec()?; // configured latency
+    }
+    if version >= 15 {
+        r.skip_volume()?; // base volume
+        r.u32()?; // state
+        r.u32()?; // volume steps
+        r.u32()?; // card
+    }
+    if version >= 16 {
+        let ports = r.u32()?;
+        for _ in 0..ports {
+            r.string()?; // name
+            r.string()?; // description
+            r.u32()?; // priority
+            if version >= 24 {
+                r.u32()?; // available
+            }
+            if version >= 34 {
+                r.string()?; // availability group
+                r.u32()?; // type
+            }
+        }
+        r.string()?; // active port
+    }
+    if version >= formats_since {
+        let formats = r.u8()?;
+        for _ in 0..formats {
+            r.skip_format_info()?;
+        }
+    }
+
+    Ok(DeviceInfo {
+        description: description.unwrap_or_else(|| name.clone()),
+        name,
+        linked_index,
+    })
+}
+
+// ── Connection ───────────────────────────────────────────────────────────────
+
+pub struct Connection {
+    stream: UnixStream,
+    next_tag: u32,
+    /// Negotiated protocol version; decides which reply fields are present.
+    version: u32,
+}
+
+impl Connection {
+    /// Connect to the user's sound server and complete the AUTH and
+    /// SET_CLIENT_NAME handshake.
+    pub fn connect() -> Result<Self, String> {
+        let path = socket_path().ok_or("PulseAudio: no server socket found")?;
+        let stream = UnixStream::connect(&path)
+            .map_err(|e| format!("PulseAudio: connect {}: {e}", path.display()))?;
+        // The server answers control commands immediately; a stuck daemon must
+        // not be able to hang the PTT poll thread, whether it stops reading
+        // or stops answering.
+        stream
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .map_err(|e| e.to_string())?;
+        stream
+            .set_write_timeout(Some(Duration::from_secs(2)))
+            .map_err(|e| e.to_string())?;
+
+        let mut conn = Self { stream, next_tag: 0, version: PROTOCOL_VERSION };
+
+        let cookie = read_cookie();
+        let reply = conn.request(COMMAND_AUTH, |t| {
+            t.u32(PROTOCOL_VERSION).arbitrary(&cookie);
+        })?;
+        let server_version = TagReader::new(&reply).u32()? & PROTOCOL_VERSION_MASK;
+        conn.version = conn.version.min(server_version);
+
+        conn.request(COMMAND_SET_CLIENT_NAME, |t| {
+            t.proplist(&[("application.name", "Sweetshark Client")]);
+        })?;
+
+        Ok(conn)
+    }
+
+    /// Mute or unmute a capture source by name (or `DEFAULT_SOURCE`).
+    pub fn set_source_mute(&mut self, name: &str, muted: bool) -> Result<(), String> {
+        self.request(COMMAND_SET_SOURCE_MUTE, |t| {
+            t.u32(INVALID_INDEX).string(Some(name)).bool(muted);
+        })?;
+        Ok(())
+    }
+
+    /// All capture sources except monitors of playback devices.
+    pub fn sources(&mut self) -> Result<Vec<DeviceInfo>, String> {
+        let reply = self.request(COMMAND_GET_SOURCE_INFO_LIST, |_| {})?;
+        let mut r = TagReader::new(&reply);
+        let mut out = Vec::new();
+        while !r.is_empty() {
+            let info = read_device_info(&mut r, self.version, 22)?;
+            if info.linked_index == INVALID_INDEX {
+                out.push(info);
+            }
+        }
+        Ok(out)
+    }
+
+    /// All playback sinks.
+    pub fn sinks(&mut self) -> Result<Vec<DeviceInfo>, String> {
+        let reply = self.request(COMMAND_GET_SINK_INFO_LIST, |_| {})?;
+        let mut r = TagReader::new(&reply);
+        let mut out = Vec::new();
+        while !r.is_empty() {
+            out.push(read_device_info(&mut r, self.version, 21)?);
+        }
+        Ok(out)
+    }
+
+    /// Names of the current default sink and source.
+    pub fn server_defaults(&mut self) -> Result<ServerDefaults, String> {
+        let reply = self.request(COMMAND_GET_SERVER_INFO, |_| {})?;
+        let mut r = TagReader::new(&reply);
+        r.string()?; // package name
+        r.string()?; // package version
+        r.string()?; // user name
+        r.string()?; // host name
+        r.skip_sample_spec()?;
+        let sink = r.string()?;
+        let source = r.string()?;
+        Ok(ServerDefaults { sink, source })
+    }
+
+    /// Send one command and wait for its REPLY, returning the reply body
+    /// (everything after the command/tag header).
+    pub fn request(
+        &mut self,
+        command: u32,
+        body: impl FnOnce(&mut TagWriter),
+    ) -> Result<Vec<u8>, String> {
+        let tag = self.next_tag;
+        self.next_tag = self.next_tag.wrapping_add(1);
+
+        let mut t = TagWriter::default();
+        t.u32(command).u32(tag);
+        body(&mut t);
+        self.send(&t.buf)?;
+
+        loop {
+            let (channel, payload) = self.recv()?;
+            // Memblocks and unsolicited events (subscriptions, stream
+            // notifications) can interleave with replies; skip them.
+            if channel != CHANNEL_CONTROL {
+                continue;
+            }
+            let mut r = TagReader::new(&payload);
+            let cmd = r.u32()?;
+            let reply_tag = r.u32()?;
+            if reply_tag != tag {
+                continue;
+            }
+            let rest = r.buf.to_vec();
+            return match cmd {
+                COMMAND_REPLY => Ok(rest),
+                COMMAND_ERROR => {
+                    let code = TagReader::new(&rest).u32().unwrap_or(0);
+                    Err(format!("PulseAudio: {}", error_string(code)))
+                }
+                other => Err(format!("PulseAudio: unexpected command {other} in reply")),
+            };
+        }
+    }
+
+    fn send(&mut self, payload: &[u8]) -> Result<(), String> {
+        let mut packet = Vec::with_capacity(20 + payload.len());
+        packet.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        packet.extend_from_slice(&CHANNEL_CONTROL.to_be_bytes());
+        packet.extend_from_slice(&[0u8; 12]); // offset hi/lo + flags
+        packet.extend_from_slice(payload);
+        self.stream
+            .write_all(&packet)
+            .map_err(|e| format!("PulseAudio: write: {e}"))
+    }
+
+    fn recv(&mut self) -> Result<(u32, Vec<u8>), String> {
+        let mut desc = [0u8; 20];
+        self.stream
+            .read_exact(&mut desc)
+            .map_err(|e| format!("PulseAudio: read: {e}"))?;
+        let len = u32::from_be_bytes([desc[0], desc[1], desc[2], desc[3]]) as usize;
+        let channel = u32::from_be_bytes([desc[4], desc[5], desc[6], desc[7]]);
+        if len > MAX_PACKET_LEN {
+            return Err(format!("PulseAudio: packet of {len} bytes exceeds the {MAX_PACKET_LEN}-byte limit"));
+        }
+        let mut payload = vec![0u8; len];
+        self.stream
+            .read_exact(&mut payload)
+            .map_err(|e| format!("PulseAudio: read: {e}"))?;
+        Ok((channel, payload))
+    }
+}
+
+/// Resolve the native-protocol socket the same way libpulse does for the
+/// common cases: $PULSE_SERVER (unix paths only), then the per-user runtime
+/// directory, then the system-wide daemon.
+fn socket_path() -> Option<PathBuf> {
+    if let Ok(server) = std::env::var("PULSE_SERVER") {
+        // PULSE_SERVER may list several servers; use the first local one.
+        for entry in server.split_whitespace() {
+            let p = entry.strip_prefix("unix:").unwrap_or(entry);
+            if p.starts_with('/') {
+                return Some(PathBuf::from(p));
+            }
+        }
+    }
+
+    if let Some(runtime) = std::env::var_os("XDG_RUNTIME_DIR") {
+        let p = PathBuf::from(runtime).join("pulse").join("native");
+        if p.exists() {
+            return Some(p);
+        }
+    }
+
+    let system = PathBuf::from("/var/run/pulse/native");
+    system.exists().then_some(system)
+}
+
+/// PulseAudio authenticates with a shared 256-byte cookie. PipeWire ignores
+/// it, so an all-zero cookie is sent when none can be found.
+fn read_cookie() -> Vec<u8> {
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    if let Some(p) = std::env::var_os("PULSE_COOKIE") {
+        candidates.push(PathBuf::from(p));
+    }
+    if let Some(cfg) = std::env::var_os("XDG_CONFIG_HOME") {
+        candidates.push(PathBuf::from(cfg).join("pulse").join("cookie"));
+    }
+    if let Some(home) = std::env::var_os("HOME") {
+        candidates.push(PathBuf::from(&home).join(".config").join("pulse").join("cookie"));
+        candidates.push(PathBuf::from(&home).join(".pulse-cookie"));
+    }
+
+    candidates
+        .iter()
+        .filter_map(|p| std::fs::read(p).ok())
+        .find(|c| c.len() >= COOKIE_LEN)
+        .map(|mut c| {
+            c.truncate(COOKIE_LEN);
+            c
+        })
+        .unwrap_or_else(|| vec![0u8; COOKIE_LEN])
+}
+
+/// Human-readable names for the error codes in pulse/def.h.
+fn error_string(code: u32) -> &'static str {
+    match code {
+        1 => "access denied",
+        2 => "unknown command",
+        3 => "invalid argument",
+        4 => "entity exists",
+        5 => "no such entity",
+        6 => "connection refused",
+        7 => "protocol error",
+        8 => "timeout",
+        9 => "no authentication key",
+        10 => "internal error",
+        11 => "connection terminated",
+        12 => "entity killed",
+        13 => "invalid server",
+        19 => "not supported",
+        _ => "unknown error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tagstruct_round_trips() {
+        let mut t = TagWriter::default();
+        t.u32(0xDEAD_BEEF)
+            .string(Some("@DEFAULT_SOURCE@"))
+            .string(None)
+            .string(Some(""))
+            .bool(true)
+            .bool(false)
+            .arbitrary(&[1, 2, 3])
+            .proplist(&[("application.name", "Sweetshark Client"), ("a", "b")])
+            .u32(7);
+
+        let mut r = TagReader::new(&t.buf);
+        assert_eq!(r.u32().unwrap(), 0xDEAD_BEEF);
+        assert_eq!(r.string().unwrap().as_deref(), Some("@DEFAULT_SOURCE@"));
+        assert_eq!(r.string().unwrap(), None);
+        assert_eq!(r.string().unwrap().as_deref(), Some(""));
+        assert!(r.bool().unwrap());
+        assert!(!r.bool().unwrap());
+        r.skip_arbitrary().unwrap();
+        r.skip_proplist().unwrap();
+        assert_eq!(r.u32().unwrap(), 7);
+        assert!(r.is_empty());
+    }
+
+    #[test]
+    fn proplist_values_are_nul_terminated_blobs() {
+        let mut t = TagWriter::default();
+        t.proplist(&[("k", "v")]);
+        let mut r = TagReader::new(&t.buf);
+        r.tag(TAG_PROPLIST).unwrap();
+        assert_eq!(r.string().unwrap().as_deref(), Some("k"));
+        assert_eq!(r.u32().unwrap(), 2);
+        r.tag(TAG_ARBITRARY).unwrap();
+        assert_eq!(r.raw_u32().unwrap(), 2);
+        assert_eq!(r.take(2).unwrap(), b"v\0");
+        assert_eq!(r.string().unwrap(), None);
+        assert!(r.is_empty());
+    }
+
+    #[test]
+    fn reader_rejects_wrong_tags_and_truncation() {
+        let mut t = TagWriter::default();
+        t.string(Some("x"));
+        assert!(TagReader::new(&t.buf).u32().unwrap_err().contains("expected tag 'L'"));
+
+        let mut t = TagWriter::default();
+        t.u32(1);
+        assert!(TagReader::new(&t.buf[..3]).u32().unwrap_err().contains("truncated"));
+        assert!(TagReader::new(&[]).bool().unwrap_err().contains("truncated"));
+        assert!(TagReader::new(&[TAG_STRING, b'a']).string().unwrap_err().contains("unterminated"));
+
+        // An arbitrary blob claiming more bytes than the reply holds.
+        let mut buf = vec![TAG_ARBITRARY];
+        buf.extend_from_slice(&u32::MAX.to_be_bytes());
+        assert!(TagReader::new(&buf).skip_arbitrary().unwrap_err().contains("truncated"));
+    }
+
+    fn pair() -> (Connection, UnixStream) {
+        let (client, server) = UnixStream::pair().unwrap();
+        client.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        (Connection { stream: client, next_tag: 0, version: PROTOCOL_VERSION }, server)
+    }
+
+    fn packet(channel: u32, payload: &[u8]) -> Vec<u8> {
+        let mut p = Vec::new();
+        p.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        p.extend_from_slice(&channel.to_be_bytes());
+        p.extend_from_slice(&[0u8; 12]);
+        p.extend_from_slice(payload);
+        p
+    }
+
+    #[test]
+    fn recv_rejects_oversized_packets_before_allocating() {
+        let (mut conn, mut server) = pair();
+        let mut desc = [0u8; 20];
+        desc[..4].copy_from_slice(&u32::MAX.to_be_bytes());
+        server.write_all(&desc).unwrap();
+        assert!(conn.recv().unwrap_err().contains("exceeds"));
+    }
+
+    #[test]
+    fn request_skips_foreign_packets_and_returns_the_matching_reply() {
+        let (mut conn, mut server) = pair();
+        let mut other = TagWriter::default();
+        other.u32(COMMAND_REPLY).u32(99);
+        let mut reply = TagWriter::default();
+        reply.u32(COMMAND_REPLY).u32(0).u32(42);
+        server.write_all(&packet(0, b"memblock")).unwrap();
+        server.write_all(&packet(CHANNEL_CONTROL, &other.buf)).unwrap();
+        server.write_all(&packet(CHANNEL_CONTROL, &reply.buf)).unwrap();
+
+        let body = conn.request(COMMAND_GET_SERVER_INFO, |_| {}).unwrap();
+        assert_eq!(TagReader::new(&body).u32().unwrap(), 42);
+
+        // The request itself went out as one control packet with our tag.
+        let mut desc = [0u8; 20];
+        server.read_exact(&mut desc).unwrap();
+        let len = u32::from_be_bytes([desc[0], desc[1], desc[2], desc[3]]) as usize;
+        let mut sent = vec![0u8; len];
+        server.read_exact(&mut sent).unwrap();
+        let mut r = TagReader::new(&sent);
+        assert_eq!(r.u32().unwrap(), COMMAND_GET_SERVER_INFO);
+        assert_eq!(r.u32().unwrap(), 0);
+        assert!(r.is_empty());
+    }
+
+    #[test]
+    fn request_maps_error_replies() {
+        let (mut conn, mut server) = pair();
+        let mut error = TagWriter::default();
+        error.u32(COMMAND_ERROR).u32(0).u32(5);
+        server.write_all(&packet(CHANNEL_CONTROL, &error.buf)).unwrap();
+        let err = conn.set_source_mute(DEFAULT_SOURCE, true).unwrap_err();
+        assert_eq!(err, "PulseAudio: no such entity");
+    }
+
+    /// Mute round trip against a real sound server. It runs on the monitor
+    /// source of a fresh null sink, made the default source for the
+    /// duration, so no real microphone is touched. Needs a running PulseAudio
+    /// (or pipewire-pulse) and `pactl`; run with `cargo test -- --ignored`.
+    #[test]
+    #[ignore = "needs a running PulseAudio server and pactl"]
+    fn set_source_mute_round_trips_on_a_null_sink() {
+        fn pactl(args: &[&str]) -> String {
+            let out = std::process::Command::new("pactl").args(args).output().expect("pactl");
+            assert!(out.status.success(), "pactl {args:?}: {}", String::from_utf8_lossy(&out.stderr));
+            String::from_utf8(out.stdout).unwrap()
+        }
+        fn muted(source: &str) -> bool {
+            match pactl(&["get-source-mute", source]).trim() {
+                "Mute: yes" => true,
+                "Mute: no" => false,
+                other => panic!("unexpected pactl output {other:?}"),
+            }
+        }
+
+        const SOURCE: &str = "sweetshark_ptt_test.monitor";
+        let module = pactl(&["load-module", "module-null-sink", "sink_name=sweetshark_ptt_test"]);
+        let previous = Connection::connect().unwrap().server_defaults().unwrap().source;
+        pactl(&["set-default-source", SOURCE]);
+
+        let result = std::panic::catch_unwind(|| {
+            let mut conn = Connection::connect().unwrap();
+            for source in [SOURCE, DEFAULT_SOURCE] {
+                conn.set_source_mute(source, true).unwrap();
+                assert!(muted(SOURCE), "muting {source}");
+                conn.set_source_mute(source, false).unwrap();
+                assert!(!muted(SOURCE), "unmuting {source}");
+            }
+        });
+
+        if let Some(previous) = previous {
+            pactl(&["set-default-source", &previous]);
+        }
+        pactl(&["unload-module", module.trim()]);
+        if let Err(panic) = result {
+            std::panic::resume_unwind(panic);
+        }
+    }
+}
@@ -0,0 +1,425 @@
+// ── Global shortcuts ─────────────────────────────────────────────────────────
+//
+// System-wide hotkeys for the voice controls, the window and the server list,
+// registered through tauri-plugin-global-shortcut. The bindings live in the
+// config store and are owned here: the UI replaces them with `set_shortcuts`,
+// which refuses a set with an unreadable key combination, an action bound
+// twice, two actions on the same keys, or keys that an enabled push-to-talk
+// binding holds down. The OS can still refuse a binding another application
+// holds; that is reported on the binding instead of failing the set. Saved
+// bindings go through the same checks on startup and whenever push-to-talk
+// changes, and any that fail stay listed — with the reason — but
+// unregistered.
+
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+use crate::{
+    CONFIG_STORE, PttBinding, PttConfig, PttMode, SharedPttState, SharedServerRegistry, VoiceState,
+    spawn_ptt_toggle, update_voice_state,
+};
+use crate::tray::{SELECT_SERVER_EVENT, SelectServerEvent, TRAY_ID, show_main_window};
+
+/// Key under which the `ShortcutBinding` list is kept in `CONFIG_STORE`.
+const SHORTCUTS_KEY: &str = "shortcuts";
+
+/// "Switch to server N" reaches this far down the sidebar.
+const MAX_SHORTCUT_SERVER: u8 = 9;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ShortcutAction {
+    ToggleMute,
+    ToggleDeafen,
+    /// Bring the main window forward, or hide it when it already has focus.
+    ToggleWindow,
+    /// Switch the all-servers PTT binding on or off.
+    TogglePtt,
+    /// Switch to the `index`-th server in the sidebar, counting from 1.
+    SwitchServer { index: u8 },
+}
+
+impl std::fmt::Display for ShortcutAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ToggleMute => f.write_str("Toggle mute"),
+            Self::ToggleDeafen => f.write_str("Toggle deafen"),
+            Self::ToggleWindow => f.write_str("Show / hide window"),
+            Self::TogglePtt => f.write_str("Toggle push-to-talk"),
+            Self::SwitchServer { index } => write!(f, "Switch to server {index}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ShortcutBinding {
+    pub action: ShortcutAction,
+    /// As the plugin reads it: modifiers then one key, e.g.
+    /// "Control+Shift+KeyM" (key names are `KeyboardEvent.code` values).
+    pub accelerator: String,
+}
+
+/// A binding and whether it is live, as `get_shortcuts` reports it.
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShortcutStatus {
+    #[serde(flatten)]
+    pub binding: ShortcutBinding,
+    /// Why the binding isn't registered; None when it is.
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+pub struct ShortcutRegistry {
+    statuses: Vec<ShortcutStatus>,
+    /// What is registered with the OS, to release on the next change.
+    registered: Vec<tauri_plugin_global_shortcut::Shortcut>,
+}
+
+pub type SharedShortcuts = Arc<Mutex<ShortcutRegistry>>;
+
+/// Parse an accelerator, refusing one that would swallow ordinary typing: a
+/// bare key or Shift+key is only allowed on the function keys.
+pub fn parse_accelerator(accelerator: &str) -> Result<tauri_plugin_global_shortcut::Shortcut, String> {
+    use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut};
+
+    let shortcut: Shortcut = accelerator
+        .parse()
+        .map_err(|e| format!("\"{accelerator}\" is not a key combination: {e}"))?;
+    let function_key = matches!(
+        shortcut.key,
+        Code::F1 | Code::F2 | Code::F3 | Code::F4 | Code::F5 | Code::F6 | Code::F7 | Code::F8
+            | Code::F9 | Code::F10 | Code::F11 | Code::F12 | Code::F13 | Code::F14 | Code::F15
+            | Code::F16 | Code::F17 | Code::F18 | Code::F19 | Code::F20 | Code::F21 | Code::F22
+            | Code::F23 | Code::F24
+    );
+    if !function_key && !shortcut.mods.intersects(Modifiers::CONTROL | Modifiers::ALT | Modifiers::SUPER) {
+        return Err(format!("\"{accelerator}\" needs Ctrl, Alt or Super, or a function key"));
+    }
+    Ok(shortcut)
+}
+
+/// A push-to-talk key token reduced to one name per key, so it can be
+/// compared with a shortcut: modifiers lose their side ("controlleft" and
+/// "rctrl" are both "ctrl") and the aliases `token_to_vk` accepts become the
+/// names the keybind dialog sends.
+fn ptt_key_name(token: &str) -> String {
+    let token = token.trim().to_ascii_lowercase();
+    match token.as_str() {
+        "control" | "lctrl" | "lcontrol" | "controlleft" | "rctrl" | "rcontrol" | "controlright" => "ctrl",
+        "lshift" | "shiftleft" | "rshift" | "shiftright" => "shift",
+        "lalt" | "lmenu" | "altleft" | "menu" | "ralt" | "rmenu" | "altright" | "altgr" => "alt",
+        "meta" | "win" | "metaleft" | "osleft" | "metaright" | "osright" => "super",
+        "return" => "enter",
+        "esc" => "escape",
+        "del" => "delete",
+        "ins" => "insert",
+        "up" => "arrowup",
+        "down" => "arrowdown",
+        "left" => "arrowleft",
+        "right" => "arrowright",
+        "printscreen" => "print",
+        _ => return token,
+    }
+    .to_string()
+}
+
+/// The keys `shortcut` needs held, named like `ptt_key_name`.
+fn shortcut_key_names(shortcut: &tauri_plugin_global_shortcut::Shortcut) -> std::collections::BTreeSet<String> {
+    use tauri_plugin_global_shortcut::Modifiers;
+
+    let mut keys: std::collections::BTreeSet<String> =
+        [(Modifiers::CONTROL, "ctrl"), (Modifiers::SHIFT, "shift"), (Modifiers::ALT, "alt"), (Modifiers::SUPER, "super")]
+            .into_iter()
+            .filter(|(modifier, _)| shortcut.mods.contains(*modifier))
+            .map(|(_, name)| name.to_string())
+            .collect();
+    let code = shortcut.key.to_string();
+    let key = match code.as_str() {
+        "Minus" => "-",
+        "Equal" => "=",
+        "BracketLeft" => "[",
+        "BracketRight" => "]",
+        "Semicolon" => ";",
+        "Quote" => "'",
+        "Backquote" => "`",
+        "Backslash" => "\\",
+        "Comma" => ",",
+        "Period" => ".",
+        "Slash" => "/",
+        other => other.strip_prefix("Key").or_else(|| other.strip_prefix("Digit")).unwrap_or(other),
+    };
+    keys.insert(ptt_key_name(key));
+    keys
+}
+
+/// The enabled push-to-talk binding that holds down every key of `shortcut`,
+/// if any: talking on it would fire the shortcut every time. A chord that is
+/// only part of a shortcut (PTT on Ctrl, mute on Ctrl+Shift+M) is allowed;
+/// the mic opening while the shortcut is pressed does no harm.
+fn ptt_overlap<'a>(
+    shortcut: &tauri_plugin_global_shortcut::Shortcut,
+    ptt: &'a std::collections::HashMap<String, PttConfig>,
+) -> Option<&'a PttBinding> {
+    let keys = shortcut_key_names(shortcut);
+    let mut scopes: Vec<&String> = ptt.keys().collect();
+    scopes.sort();
+    scopes
+        .into_iter()
+        .map(|scope| &ptt[scope])
+        .filter(|c| c.enabled && c.mode != PttMode::VoiceActivity)
+        .flat_map(|c| &c.bindings)
+        .find(|b| {
+            let held: std::collections::BTreeSet<String> = b.keys.iter().map(|k| ptt_key_name(k)).collect();
+            keys.is_subset(&held)
+        })
+}
+
+/// Check `bindings` on their own, against each other and against the
+/// push-to-talk `ptt` configs. One result per binding, in order; of two
+/// bindings that clash, the first one wins.
+pub fn validate_shortcuts(
+    bindings: &[ShortcutBinding],
+    ptt: &std::collections::HashMap<String, PttConfig>,
+) -> Vec<Result<tauri_plugin_global_shortcut::Shortcut, String>> {
+    let mut actions = std::collections::HashSet::new();
+    let mut taken: Vec<(tauri_plugin_global_shortcut::Shortcut, ShortcutAction)> = Vec::new();
+    bindings
+        .iter()
+        .map(|binding| {
+            if let ShortcutAction::SwitchServer { index } = binding.action {
+                if !(1..=MAX_SHORTCUT_SERVER).contains(&index) {
+                    return Err(format!("Server shortcuts go from 1 to {MAX_SHORTCUT_SERVER}, not {index}"));
+                }
+            }
+            if !actions.insert(binding.action) {
+                return Err(format!("{} has more than one shortcut", binding.action));
+            }
+            let shortcut = parse_accelerator(&binding.accelerator)?;
+            if let Some((_, other)) = taken.iter().find(|(s, _)| s.id() == shortcut.id()) {
+                return Err(format!("{} is already used by {other}", binding.accelerator));
+            }
+            if let Some(held) = ptt_overlap(&shortcut, ptt) {
+                return Err(format!(
+                    "{} is held down by the push-to-talk keys \"{}\", so talking would trigger it",
+                    binding.accelerator, held.name
+                ));
+            }
+            taken.push((shortcut, binding.action));
+            Ok(shortcut)
+        })
+        .collect()
+}
+
+/// Bindings saved in the store. Entries that no longer deserialize (e.g. an
+/// action from a newer version) are dropped and logged.
+pub(crate) fn load_shortcuts(app: &AppHandle) -> Vec<ShortcutBinding> {
+    use tauri_plugin_store::StoreExt;
+
+    let Some(serde_json::Value::Array(saved)) = app.store(CONFIG_STORE).ok().and_then(|s| s.get(SHORTCUTS_KEY)) else {
+        return Vec::new();
+    };
+    saved
+        .into_iter()
+        .filter_map(|v| {
+            serde_json::from_value(v.clone())
+                .map_err(|e| log::warn!("Ignoring saved shortcut {v}: {e}"))
+                .ok()
+        })
+        .collect()
+}
+
+fn save_shortcuts(app: &AppHandle, bindings: &[ShortcutBinding]) -> Result<(), String> {
+    use tauri_plugin_store::StoreExt;
+
+    let store = app.store(CONFIG_STORE).map_err(|e| e.to_string())?;
+    store.set(SHORTCUTS_KEY, serde_json::to_value(bindings).map_err(|e| e.to_string())?);
+    store.save().map_err(|e| e.to_string())
+}
+
+/// Replace everything registered with `bindings`. Ones that fail validation
+/// or that the OS refuses are kept in the list, unregistered, with the reason.
+pub(crate) fn register_shortcuts(app: &AppHandle, bindings: Vec<ShortcutBinding>) -> Vec<ShortcutStatus> {
+    use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+    // One replacement at a time. The state lock itself is never held across
+    // plugin calls: they block on the main thread.
+    static SERIAL: Mutex<()> = Mutex::new(());
+    let _serial = SERIAL.lock().unwrap();
+
+    let state = app.state::<SharedShortcuts>();
+    let global = app.global_shortcut();
+    let previous = std::mem::take(&mut state.lock().unwrap().registered);
+    if !previous.is_empty() {
+        if let Err(e) = global.unregister_multiple(previous) {
+            log::warn!("Failed to release global shortcuts: {e}");
+        }
+    }
+
+    let ptt = app.state::<SharedPttState>().lock().unwrap().configs.clone();
+    let checks = validate_shortcuts(&bindings, &ptt);
+    let mut registered = Vec::new();
+    let statuses: Vec<ShortcutStatus> = bindings
+        .into_iter()
+        .zip(checks)
+        .map(|(binding, check)| {
+            let action = binding.action;
+            let result = check.and_then(|shortcut| {
+                global
+                    .on_shortcut(shortcut, move |app, _, event| {
+                        if event.state == ShortcutState::Pressed {
+                            run_shortcut(app, action);
+                        }
+                    })
+                    .map_err(|e| format!("{} is taken, probably by another application ({e})", binding.accelerator))?;
+                registered.push(shortcut);
+                Ok(())
+            });
+            ShortcutStatus { binding, error: result.err() }
+        })
+        .collect();
+
+    let mut locked = state.lock().unwrap();
+    locked.registered = registered;
+    locked.statuses = statuses.clone();
+    statuses
+}
+
+/// Register the current bindings again after push-to-talk changed, so one
+/// that its keys now hold down is released, and one they no longer hold comes
+/// back. Blocks on the main thread; every caller runs off it.
+pub(crate) fn recheck_shortcuts(app: &AppHandle) {
+    let Some(state) = app.try_state::<SharedShortcuts>() else { return };
+    let bindings: Vec<ShortcutBinding> = state.lock().unwrap().statuses.iter().map(|s| s.binding.clone()).collect();
+    if !bindings.is_empty() {
+        register_shortcuts(app, bindings);
+    }
+}
+
+fn run_shortcut(app: &AppHandle, action: ShortcutAction) {
+    match action {
+        ShortcutAction::ToggleMute => update_voice_state(app, VoiceState::toggled_mute),
+        ShortcutAction::ToggleDeafen => update_voice_state(app, VoiceState::toggled_deafen),
+        ShortcutAction::ToggleWindow => toggle_main_window(app),
+        ShortcutAction::TogglePtt => spawn_ptt_toggle(app),
+        ShortcutAction::SwitchServer { index } => {
+            // Registry order is the sidebar's (see `ServerRegistry::servers`).
+            let server_id = app
+                .state::<SharedServerRegistry>()
+                .lock()
+                .unwrap()
+                .list()
+                .get(usize::from(index) - 1)
+                .map(|s| s.id.clone());
+            if let Some(server_id) = server_id {
+                show_main_window(app);
+                let _ = app.emit(SELECT_SERVER_EVENT, SelectServerEvent { server_id });
+            }
+        }
+    }
+}
+
+/// Hide the main window if the user is looking at it, otherwise bring it
+/// forward. Without a tray icon it is minimised instead, so it stays
+/// reachable from the taskbar.
+fn toggle_main_window(app: &AppHandle) {
+    let Some(window) = app.get_window("main") else { return };
+    let in_front = window.is_visible().unwrap_or(false)
+        && !window.is_minimized().unwrap_or(false)
+        && window.is_focused().unwrap_or(false);
+    if !in_front {
+        show_main_window(app);
+    } else if app.tray_by_id(TRAY_ID).is_some() {
+        let _ = window.hide();
+    } else {
+        let _ = window.minimize();
+    }
+}
+
+#[tauri::command]
+pub(crate) fn get_shortcuts(state: tauri::State<'_, SharedShortcuts>) -> Vec<ShortcutStatus> {
+    state.lock().unwrap().statuses.clone()
+}
+
+/// Replace all bindings. A set that doesn't validate is refused as a whole
+/// and nothing changes; otherwise it is saved and registered, and the
+/// returned statuses say which ones the OS accepted.
+#[tauri::command]
+pub(crate) async fn set_shortcuts(app: AppHandle, bindings: Vec<ShortcutBinding>) -> Result<Vec<ShortcutStatus>, String> {
+    let ptt = app.state::<SharedPttState>().lock().unwrap().configs.clone();
+    let problems: Vec<String> = validate_shortcuts(&bindings, &ptt).into_iter().filter_map(Result::err).collect();
+    if !problems.is_empty() {
+        return Err(problems.join("; "));
+    }
+    save_shortcuts(&app, &bindings)?;
+    Ok(register_shortcuts(&app, bindings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::binding;
+    use crate::PTT_ALL_SERVERS;
+
+    fn shortcut(action: ShortcutAction, accelerator: &str) -> ShortcutBinding {
+        ShortcutBinding { action, accelerator: accelerator.into() }
+    }
+
+    fn ptt_configs(enabled: bool, mode: PttMode, keys: &[&str]) -> std::collections::HashMap<String, PttConfig> {
+        let config = PttConfig { enabled, mode, bindings: vec![binding("main", keys)], ..Default::default() };
+        [(PTT_ALL_SERVERS.to_string(), config)].into()
+    }
+
+    #[test]
+    fn validate_shortcuts_refuses_clashes_and_keeps_the_first() {
+        let checks = validate_shortcuts(
+            &[
+                shortcut(ShortcutAction::ToggleMute, "Control+Shift+KeyM"),
+                shortcut(ShortcutAction::ToggleMute, "Control+Shift+KeyN"),
+                shortcut(ShortcutAction::ToggleDeafen, "Control+Shift+KeyM"),
+                shortcut(ShortcutAction::ToggleWindow, "KeyW"),
+                shortcut(ShortcutAction::TogglePtt, "F9"),
+                shortcut(ShortcutAction::SwitchServer { index: 10 }, "Alt+Digit0"),
+            ],
+            &Default::default(),
+        );
+        assert!(checks[0].is_ok());
+        assert!(checks[1].as_ref().unwrap_err().contains("more than one shortcut"));
+        assert!(checks[2].as_ref().unwrap_err().contains("already used by Toggle mute"));
+        assert!(checks[3].as_ref().unwrap_err().contains("needs Ctrl, Alt or Super"));
+        assert!(checks[4].is_ok());
+        assert!(checks[5].as_ref().unwrap_err().contains("from 1 to 9"));
+    }
+
+    #[test]
+    fn validate_shortcuts_refuses_keys_held_for_push_to_talk() {
+        let bindings = [
+            shortcut(ShortcutAction::ToggleMute, "Control+Alt+KeyM"),
+            shortcut(ShortcutAction::ToggleDeafen, "Control+Shift+KeyD"),
+        ];
+        // Either side of a modifier and any alias of it counts.
+        let checks = validate_shortcuts(&bindings, &ptt_configs(true, PttMode::PushToTalk, &["ControlRight", "altleft", "m"]));
+        assert!(checks[0].as_ref().unwrap_err().contains("push-to-talk keys \"main\""));
+        assert!(checks[1].is_ok());
+
+        // A chord that is only part of a shortcut doesn't fire it.
+        let checks = validate_shortcuts(&bindings, &ptt_configs(true, PttMode::Toggle, &["ctrl"]));
+        assert!(checks.iter().all(Result::is_ok));
+
+        // Nothing is held while push-to-talk is off or listening to the mic.
+        let held = ["ctrl", "alt", "m"];
+        for ptt in [ptt_configs(false, PttMode::PushToTalk, &held), ptt_configs(true, PttMode::VoiceActivity, &held)] {
+            assert!(validate_shortcuts(&bindings, &ptt).iter().all(Result::is_ok));
+        }
+    }
+
+    #[test]
+    fn shortcut_key_names_match_ptt_tokens() {
+        let names = |accelerator| shortcut_key_names(&parse_accelerator(accelerator).unwrap()).into_iter().collect::<Vec<_>>();
+        assert_eq!(names("Super+Shift+Digit1"), ["1", "shift", "super"]);
+        assert_eq!(names("Control+Slash"), ["/", "ctrl"]);
+        assert_eq!(names("Alt+ArrowUp"), ["alt", "arrowup"]);
+        assert_eq!(ptt_key_name(" Up "), "arrowup");
+        assert_eq!(ptt_key_name("rmenu"), "alt");
+    }
+
+}
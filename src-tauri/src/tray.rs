@@ -0,0 +1,248 @@
+// ── Tray ─────────────────────────────────────────────────────────────────────
+//
+// The tray icon carries the unread badge (see `show_badge_total`) and a menu
+// for running the client from the background: switch server, mute / deafen,
+// PTT on/off, quit. With close-to-tray on, closing the main window only hides
+// it, so server webviews — and any voice call in them — stay up.
+
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+use crate::{
+    CONFIG_STORE, PTT_ALL_SERVERS, Server, ServerId, SharedPttState, SharedServerRegistry,
+    SharedVoiceState, VoiceState, spawn_ptt_toggle, update_voice_state,
+};
+use crate::badges::{BadgeTotal, SharedBadgeState, tauri_image};
+
+pub(crate) const TRAY_ID: &str = "main";
+
+/// Key under which `TraySettings` is kept in `CONFIG_STORE`.
+const TRAY_KEY: &str = "tray";
+
+/// Emitted when a server is picked from the tray menu or by a global
+/// shortcut. The UI switches to it through its usual show/hide flow, as for
+/// a notification click.
+pub(crate) const SELECT_SERVER_EVENT: &str = "app://select-server";
+
+/// Menu item ids; a server's item is `TRAY_SERVER_PREFIX` + its id.
+const TRAY_SHOW: &str = "show";
+const TRAY_MUTE: &str = "mute";
+const TRAY_DEAFEN: &str = "deafen";
+const TRAY_PTT: &str = "ptt";
+const TRAY_QUIT: &str = "quit";
+const TRAY_SERVER_PREFIX: &str = "server:";
+
+/// Largest edge the tray icon is drawn at; trays show 16–32 px, and HiDPI
+/// panels scale up from this.
+const TRAY_ICON_SIZE: u32 = 64;
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TraySettings {
+    /// Closing the main window hides it to the tray instead of quitting.
+    pub close_to_tray: bool,
+}
+
+impl TraySettings {
+    pub(crate) fn load(app: &AppHandle) -> Self {
+        use tauri_plugin_store::StoreExt;
+
+        app.store(CONFIG_STORE)
+            .ok()
+            .and_then(|store| store.get(TRAY_KEY))
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, app: &AppHandle) -> Result<(), String> {
+        use tauri_plugin_store::StoreExt;
+
+        let store = app.store(CONFIG_STORE).map_err(|e| e.to_string())?;
+        store.set(TRAY_KEY, serde_json::to_value(self).map_err(|e| e.to_string())?);
+        store.save().map_err(|e| e.to_string())
+    }
+}
+
+pub type SharedTraySettings = Arc<Mutex<TraySettings>>;
+
+/// Payload of `app://select-server`.
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectServerEvent {
+    pub server_id: ServerId,
+}
+
+/// The app icon at tray size, without a badge.
+pub(crate) fn tray_base_icon(app: &AppHandle) -> Option<&'static image::RgbaImage> {
+    static BASE: std::sync::OnceLock<Option<image::RgbaImage>> = std::sync::OnceLock::new();
+    BASE.get_or_init(|| {
+        let icon = app.default_window_icon()?;
+        let img = image::RgbaImage::from_raw(icon.width(), icon.height(), icon.rgba().to_vec())?;
+        if img.width() <= TRAY_ICON_SIZE && img.height() <= TRAY_ICON_SIZE {
+            return Some(img);
+        }
+        Some(image::imageops::resize(&img, TRAY_ICON_SIZE, TRAY_ICON_SIZE, image::imageops::FilterType::Lanczos3))
+    })
+    .as_ref()
+}
+
+pub(crate) fn tray_tooltip(app: &AppHandle, total: BadgeTotal) -> String {
+    let name = &app.package_info().name;
+    match total.mentions {
+        0 if total.unread_count == 1 => format!("{name} — 1 unread message"),
+        0 if total.unread_count > 1 => format!("{name} — {} unread messages", total.unread_count),
+        0 if total.unread => format!("{name} — unread messages"),
+        0 => name.clone(),
+        1 => format!("{name} — 1 new notification"),
+        n => format!("{name} — {n} new notifications"),
+    }
+}
+
+/// The tray menu for the current servers, voice state and PTT config. Built
+/// afresh on every change; it is small and changes rarely.
+fn tray_menu(app: &AppHandle, servers: &[Server]) -> tauri::Result<tauri::menu::Menu<tauri::Wry>> {
+    use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem};
+
+    let voice = *app.state::<SharedVoiceState>().lock().unwrap();
+    let viewed = app.state::<SharedBadgeState>().lock().unwrap().viewed().cloned();
+    let ptt = app
+        .state::<SharedPttState>()
+        .lock()
+        .unwrap()
+        .configs
+        .get(PTT_ALL_SERVERS)
+        .map(|c| (c.enabled, c.has_trigger()));
+
+    let menu = Menu::new(app)?;
+    menu.append(&MenuItem::with_id(app, TRAY_SHOW, format!("Show {}", app.package_info().name), true, None::<&str>)?)?;
+    if !servers.is_empty() {
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+        for server in servers {
+            // Menus treat '&' as a mnemonic marker.
+            let name = server.name.replace('&', "&&");
+            let id = format!("{TRAY_SERVER_PREFIX}{}", server.id);
+            let current = viewed.as_ref() == Some(&server.id);
+            menu.append(&CheckMenuItem::with_id(app, id, name, true, current, None::<&str>)?)?;
+        }
+    }
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&CheckMenuItem::with_id(app, TRAY_MUTE, "Mute", true, voice.mic_muted(), None::<&str>)?)?;
+    menu.append(&CheckMenuItem::with_id(app, TRAY_DEAFEN, "Deafen", true, voice.deafened, None::<&str>)?)?;
+    // Greyed out until a binding has been set up in Settings.
+    let (ptt_on, ptt_usable) = ptt.unwrap_or((false, false));
+    menu.append(&CheckMenuItem::with_id(app, TRAY_PTT, "Push-to-talk", ptt_usable, ptt_on, None::<&str>)?)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(app, TRAY_QUIT, "Quit", true, None::<&str>)?)?;
+    Ok(menu)
+}
+
+/// Rebuilds the tray menu on one long-lived thread. Menu calls block on the
+/// main thread, and callers may be on it (sync commands) or holding a lock
+/// something on it waits for, so they only post a request. Requests
+/// coalesce: the rebuild reads the state when it runs, so while one is
+/// pending another adds nothing.
+pub struct TrayMenuWorker {
+    requests: std::sync::mpsc::SyncSender<()>,
+}
+
+impl TrayMenuWorker {
+    fn spawn(app: AppHandle) -> std::io::Result<Self> {
+        let (requests, pending) = std::sync::mpsc::sync_channel(1);
+        std::thread::Builder::new().name("sweetshark-tray-menu".into()).spawn(move || {
+            while pending.recv().is_ok() {
+                let Some(tray) = app.tray_by_id(TRAY_ID) else { continue };
+                let servers = app.state::<SharedServerRegistry>().lock().unwrap().list().to_vec();
+                if let Err(e) = tray_menu(&app, &servers).and_then(|menu| tray.set_menu(Some(menu))) {
+                    log::warn!("Failed to update tray menu: {e}");
+                }
+            }
+        })?;
+        Ok(Self { requests })
+    }
+
+    fn request(&self) {
+        // Full means a rebuild is already pending.
+        let _ = self.requests.try_send(());
+    }
+}
+
+/// Rebuild the tray menu from the current state, soon. Never blocks; does
+/// nothing when there is no tray.
+pub(crate) fn refresh_tray_menu(app: &AppHandle) {
+    if let Some(worker) = app.try_state::<TrayMenuWorker>() {
+        worker.request();
+    }
+}
+
+pub(crate) fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+fn on_tray_menu(app: &AppHandle, id: &str) {
+    match id {
+        TRAY_SHOW => show_main_window(app),
+        TRAY_MUTE => update_voice_state(app, VoiceState::toggled_mute),
+        TRAY_DEAFEN => update_voice_state(app, VoiceState::toggled_deafen),
+        TRAY_PTT => spawn_ptt_toggle(app),
+        TRAY_QUIT => app.exit(0),
+        _ => {
+            let Some(Ok(server_id)) = id.strip_prefix(TRAY_SERVER_PREFIX).map(ServerId::parse) else { return };
+            show_main_window(app);
+            let _ = app.emit(SELECT_SERVER_EVENT, SelectServerEvent { server_id });
+            // The check mark moves once the UI reports the switch; until then
+            // undo the one the click toggled.
+            refresh_tray_menu(app);
+        }
+    }
+}
+
+pub(crate) fn build_tray(app: &AppHandle) -> tauri::Result<()> {
+    let servers = app.state::<SharedServerRegistry>().lock().unwrap().list().to_vec();
+    let mut builder = tauri::tray::TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip(tray_tooltip(app, BadgeTotal::default()))
+        .menu(&tray_menu(app, &servers)?)
+        // Left click brings the window back; the menu is on right click.
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app, event| on_tray_menu(app, event.id().as_ref()))
+        .on_tray_icon_event(|tray, event| {
+            if let tauri::tray::TrayIconEvent::Click {
+                button: tauri::tray::MouseButton::Left,
+                button_state: tauri::tray::MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = tray_base_icon(app) {
+        builder = builder.icon(tauri_image(icon.clone()));
+    }
+    builder.build(app)?;
+    app.manage(TrayMenuWorker::spawn(app.clone())?);
+    Ok(())
+}
+
+/// Whether closing the main window should hide it instead. Never when the
+/// tray icon couldn't be created — there would be no way back.
+pub(crate) fn hides_on_close(app: &AppHandle) -> bool {
+    app.state::<SharedTraySettings>().lock().unwrap().close_to_tray && app.tray_by_id(TRAY_ID).is_some()
+}
+
+#[tauri::command]
+pub(crate) fn get_tray_settings(settings: tauri::State<'_, SharedTraySettings>) -> TraySettings {
+    settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub(crate) fn set_tray_settings(
+    app: AppHandle,
+    state: tauri::State<'_, SharedTraySettings>,
+    settings: TraySettings,
+) -> Result<(), String> {
+    settings.save(&app)?;
+    *state.lock().unwrap() = settings;
+    Ok(())
+}
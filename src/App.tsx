@@ -4,10 +4,13 @@ import iconSrc from "./assets/icon.png";
 import Sidebar, { type PttIndicatorState } from "./components/Sidebar";
 import TitleBar from "./components/TitleBar";
 import HomePage from "./components/HomePage";
-import SettingsPage, { type PttConfig } from "./components/SettingsPage";
+import SettingsPage, {
+  type PttConfig, type PttConfigs, type PttEvent, type PttErrorEvent, type PttEnabledEvent,
+  toRustPttConfig, pttBindings, pttUsable, PTT_SERVER_CONFIGS_KEY,
+} from "./components/SettingsPage";
 import ServerLoadingPage from "./components/ServerLoadingPage";
 import AddServerDialog from "./components/AddServerDialog";
-import { load, Store } from "@tauri-apps/plugin-store";
+import { load } from "@tauri-apps/plugin-store";
 import {
   createServerWebview,
   showServerWebview,
@@ -15,6 +18,11 @@ import {
   destroyServerWebview,
   deleteServerData,
   refreshServerWebview,
+  clearServerData,
+  type ClearScope,
+  probeServer,
+  needsCertificateTrust,
+  type PeerCertificate,
   resizeAllServerWebviews,
   updateServerWebviewDevices,
   type DevicePrefs,
@@ -22,7 +30,18 @@ import {
 import RenameServerDialog from "./components/RenameServerDialog";
 import ChangeIconDialog from "./components/ChangeIconDialog";
 import ConfirmRemoveDialog from "./components/ConfirmRemoveDialog";
+import ClearDataDialog from "./components/ClearDataDialog";
 import UpdateDialog from "./components/UpdateDialog";
+import OrphanDataDialog from "./components/OrphanDataDialog";
+import TrustCertificateDialog from "./components/TrustCertificateDialog";
+import ProxySettingsDialog from "./components/ProxySettingsDialog";
+import SignInDomainsDialog from "./components/SignInDomainsDialog";
+import * as registry from "./lib/serverRegistry";
+import * as storage from "./lib/storage";
+import { onNotificationClicked } from "./lib/notifications";
+import { getBadges, onBadgesChanged, setViewedServer, type ServerBadge } from "./lib/badges";
+import { getVoiceState, onVoiceChanged, setVoiceState, type VoiceState } from "./lib/voice";
+import { onSelectServer } from "./lib/tray";
 
 export interface Server {
   id: string;
@@ -30,8 +49,13 @@ export interface Server {
   url: string;
   iconLetter: string;
   iconColor: string;
-  iconUrl?: string;   // base64 data URL for custom icon, absent = letter
+  iconUrl?: string;   // custom icon not yet cached by Rust (data URL); absent = see iconRev
+  iconRev?: number;   // set while Rust has a cached icon file for this server
   keepLoaded?: boolean; // if false, webview is destroyed when not active (default true)
+  certSha256?: string; // pinned self-signed certificate; set via trustServerCertificate
+  proxy?: registry.ServerProxy; // set via setServerProxy; password is in the OS credential store
+  inAppDomains?: string[]; // SSO hosts kept in the webview; set via setServerInAppDomains
+  notificationsMuted?: boolean; // set via setServerNotificationsMuted
   inviteUrl?: string;  // one-time URL used only on first load (e.g. with ?invite= param)
 }
 
@@ -55,18 +79,26 @@ export default function App() {
   const [renameTarget, setRenameTarget] = useState<Server | null>(null);
   const [iconChangeTarget, setIconChangeTarget] = useState<Server | null>(null);
   const [removeTarget, setRemoveTarget] = useState<Server | null>(null);
+  const [loadErrors, setLoadErrors] = useState<Record<string, string>>({}); // serverId → probe diagnosis
+  const [clearTarget, setClearTarget] = useState<{ server: Server; scope: ClearScope } | null>(null);
+  const [proxyTarget, setProxyTarget] = useState<Server | null>(null);
+  const [domainsTarget, setDomainsTarget] = useState<Server | null>(null);
+  const [certPrompt, setCertPrompt] = useState<{ serverId: string; url: string; certificate: PeerCertificate } | null>(null);
   const [isModalOpen, setIsModalOpen] = useState(false);
   const [pttState,    setPttState]    = useState<PttIndicatorState>("off");
-  const [isStoreLoaded, setIsStoreLoaded] = useState(false);
+  const [pttError,    setPttError]    = useState<string | null>(null);
   const [updateInfo, setUpdateInfo] = useState<{ current: string; latest: string } | null>(null);
+  const [orphans, setOrphans] = useState<storage.ServerStorage[]>([]);
+  const [badges, setBadges] = useState<Record<string, ServerBadge>>({});
+  const [voice, setVoice] = useState<VoiceState>({ muted: false, deafened: false });
 
   // Tracks which server IDs already have a live webview in the pool.
   // Used to lazily create webviews only on first selection.
   const createdWebviews = useRef<Set<string>>(new Set());
-  const storeRef = useRef<Store | null>(null);
   const devicePrefsRef = useRef<DevicePrefs>({});
-  // Stored PTT key tokens (browser key names) for use in the keyboard listener
-  const pttKeysRef = useRef<string[]>([]);
+  // All-servers PTT chords (browser key names) for the keyboard listener;
+  // any one fully held counts as pressed.
+  const pttChordsRef = useRef<string[][]>([]);
   const pttEnabledRef = useRef<boolean>(false);
   const prevActiveView = useRef<ActiveView>("home");
 
@@ -79,11 +111,16 @@ export default function App() {
 
     load("config.json", { autoSave: true })
       .then(async (store) => {
-        storeRef.current = store;
-        const saved = await store.get<Server[]>("servers");
-        if (saved && saved.length > 0) {
-          setServers(saved);
-        }
+        // The server list is owned by the Rust registry (which migrated any
+        // list this store held from older versions).
+        const savedServers = await registry.listServers();
+        setServers(savedServers);
+        // Sweep for data dirs left behind by servers that no longer exist
+        // (crash mid-removal, failed delete). Nothing is touched until the
+        // user picks an action in the dialog.
+        storage.findOrphanedServerData()
+          .then(setOrphans)
+          .catch(e => console.error("Orphaned data sweep failed:", e));
         // Apply saved appearance settings
         const appearance = await store.get<Record<string, string>>("appearance");
         if (appearance) {
@@ -102,21 +139,38 @@ export default function App() {
 
         // Load and apply PTT config immediately on startup — do NOT wait for the
         // user to visit Settings. This ensures PTT works and the mic is muted
-        // (if PTT was enabled) from the moment the app opens.
+        // (if PTT was enabled) from the moment the app opens. A disabled config
+        // is sent too, so the tray can switch it on.
         const savedPtt = await store.get<PttConfig>("pttConfig");
-        if (savedPtt?.enabled && savedPtt.tauriKeys?.length > 0) {
+        if (savedPtt) {
+          const enabled = savedPtt.enabled && pttUsable(savedPtt);
           try {
             const { invoke } = await import("@tauri-apps/api/core");
-            await invoke("set_ptt_config", { keys: savedPtt.tauriKeys, enabled: true });
-            pttKeysRef.current = savedPtt.keys ?? [];
-            pttEnabledRef.current = true;
-            setPttState("muted");
+            await invoke("set_ptt_config", { config: toRustPttConfig({ ...savedPtt, enabled }) });
+            pttChordsRef.current = pttBindings(savedPtt).map(b => b.keys);
+            if (enabled) {
+              pttEnabledRef.current = true;
+              setPttState("muted");
+            }
           } catch (e) {
             console.error("Failed to apply PTT config on startup:", e);
           }
         }
-
-        setIsStoreLoaded(true);
+        // Per-server configs, for servers that still exist.
+        const savedServerPtt = (await store.get<PttConfigs>(PTT_SERVER_CONFIGS_KEY)) ?? {};
+        for (const server of savedServers) {
+          const cfg = savedServerPtt[server.id];
+          if (!cfg) continue;
+          try {
+            const { invoke } = await import("@tauri-apps/api/core");
+            await invoke("set_ptt_config", {
+              serverId: server.id,
+              config: toRustPttConfig({ ...cfg, enabled: cfg.enabled && pttUsable(cfg) }),
+            });
+          } catch (e) {
+            console.error(`Failed to apply PTT config for ${server.name} on startup:`, e);
+          }
+        }
 
         // ── Update check ────────────────────────────────────────────────────
         // Run after store is loaded so we can read the "never ask" preference.
@@ -141,18 +195,44 @@ export default function App() {
           }
         })();
       })
-      .catch((e) => {
-        console.error("Failed to load store:", e);
-        setIsStoreLoaded(true); // still mark loaded so the app isn't stuck
-      });
+      .catch((e) => console.error("Failed to load store:", e));
   }, []);
 
-  // ── Persistence: save servers whenever the list changes ───────────────────
-  // Guard on isStoreLoaded so we don't overwrite saved data with [] on first render.
+  // ── Server list: mirror the Rust registry ────────────────────────────────
+  // Every add/update/remove goes through a registry command; the resulting
+  // list comes back here, so this is the only place servers are set in Tauri.
   useEffect(() => {
-    if (!IS_TAURI || !isStoreLoaded || !storeRef.current) return;
-    storeRef.current.set("servers", servers).catch(console.error);
-  }, [servers, isStoreLoaded]);
+    if (!IS_TAURI) return;
+    let unlisten: (() => void) | null = null;
+    registry.onServersChanged(e => setServers(e.servers))
+      .then(fn => { unlisten = fn; })
+      .catch(console.error);
+    return () => { unlisten?.(); };
+  }, []);
+
+  // ── Server data deletion: surface background failures ───────────────────
+  useEffect(() => {
+    if (!IS_TAURI) return;
+    let unlisten: (() => void) | null = null;
+    storage.onServerDataDeletion(e => {
+      if (e.status === "retrying") console.warn(`[data] ${e.serverId}: attempt ${e.attempt} failed, retrying in ${e.retryInMs} ms: ${e.error}`);
+      if (e.status === "failed") console.error(`[data] ${e.serverId}: giving up until next launch: ${e.error}`);
+    })
+      .then(fn => { unlisten = fn; })
+      .catch(console.error);
+    return () => { unlisten?.(); };
+  }, []);
+
+  // Apply a change to the list: through the registry in Tauri, locally in a
+  // plain browser (dev mode), where there is no backend to own it.
+  const updateServerEntry = useCallback((id: string, patch: (s: Server) => Server) => {
+    if (!IS_TAURI) {
+      setServers(prev => prev.map(s => s.id === id ? patch(s) : s));
+      return;
+    }
+    const current = servers.find(s => s.id === id);
+    if (current) registry.updateServer(patch(current)).catch(console.error);
+  }, [servers]);
 
   // ── Window resize: keep all webview bounds in sync ────────────────────────
   useEffect(() => {
@@ -167,29 +247,54 @@ export default function App() {
     if (!IS_TAURI) return;
     let unlistenPressed: (() => void) | null = null;
     let unlistenReleased: (() => void) | null = null;
+    let unlistenError: (() => void) | null = null;
     (async () => {
       const { listen } = await import("@tauri-apps/api/event");
-      unlistenPressed  = await listen("ptt://pressed",  () => setPttState("active"));
-      unlistenReleased = await listen("ptt://released", () => {
+      unlistenPressed  = await listen<PttEvent>("ptt://pressed", ({ payload }) => {
+        if (payload.muteApplied) setPttError(null);
+        setPttState("active");
+      });
+      unlistenReleased = await listen<PttEvent>("ptt://released", ({ payload }) => {
+        if (payload.muteApplied) setPttError(null);
         // Check if PTT is still enabled (state might have changed)
         setPttState(s => s !== "off" ? "muted" : "off");
       });
+      // Mute/unmute failed — the mic may be live while the indicator says
+      // otherwise, so keep the warning up until a call succeeds again.
+      unlistenError = await listen<PttErrorEvent>("ptt://error", ({ payload }) => {
+        console.error(`[PTT] ${payload.scope}: ${payload.message}`);
+        setPttError(payload.message);
+      });
     })();
     return () => {
       unlistenPressed?.();
       unlistenReleased?.();
+      unlistenError?.();
     };
   }, []);
 
   // ── PTT: update indicator state when ptt config changes ──────────────────
   // SettingsPage manages PTT registration; we just need to keep the indicator
   // in sync. We expose a callback that App.tsx sets the right base state on.
-  const handlePttEnabledChange = useCallback((enabled: boolean, keys?: string[]) => {
+  const handlePttEnabledChange = useCallback((enabled: boolean, chords?: string[][]) => {
     pttEnabledRef.current = enabled;
-    if (keys !== undefined) pttKeysRef.current = keys;
+    if (chords !== undefined) pttChordsRef.current = chords;
     setPttState(enabled ? "muted" : "off");
   }, []);
 
+  // PTT switched on or off from the tray; Rust has applied and saved it.
+  useEffect(() => {
+    if (!IS_TAURI) return;
+    let unlisten: (() => void) | null = null;
+    (async () => {
+      const { listen } = await import("@tauri-apps/api/event");
+      unlisten = await listen<PttEnabledEvent>("ptt://enabled", ({ payload }) => {
+        if (payload.scope === "*") handlePttEnabledChange(payload.enabled);
+      });
+    })();
+    return () => { unlisten?.(); };
+  }, [handlePttEnabledChange]);
+
   // ── PTT: keyboard listener for home/settings pages ────────────────────────
   // When a server webview is NOT active (home or settings page), the main React
   // window has keyboard focus. GetAsyncKeyState in the Rust poll thread can be
@@ -201,9 +306,10 @@ export default function App() {
     const heldKeys = new Set<string>();
 
     const updatePttFromHeld = () => {
-      if (!pttEnabledRef.current || pttKeysRef.current.length === 0) return;
-      // Normalize: browser key names match what's stored in pttConfig.keys
-      const allHeld = pttKeysRef.current.every(k => heldKeys.has(k));
+      const chords = pttChordsRef.current.filter(c => c.length > 0);
+      if (!pttEnabledRef.current || chords.length === 0) return;
+      // Browser key names match what's stored in each binding's keys
+      const allHeld = chords.some(c => c.every(k => heldKeys.has(k)));
       setPttState(s => {
         if (s === "off") return "off"; // PTT disabled, don't touch
         return allHeld ? "active" : "muted";
@@ -234,7 +340,7 @@ export default function App() {
   // overlay (context menu, rename/icon/remove dialog, add server dialog) is open.
   useEffect(() => {
     if (!IS_TAURI) return;
-    const anyOverlayOpen = isModalOpen || !!renameTarget || !!iconChangeTarget || !!removeTarget;
+    const anyOverlayOpen = isModalOpen || !!renameTarget || !!iconChangeTarget || !!removeTarget || !!clearTarget || !!proxyTarget || !!domainsTarget || !!certPrompt || orphans.length > 0;
     if (anyOverlayOpen) {
       hideAllServerWebviews().catch(console.error);
       return;
@@ -245,7 +351,77 @@ export default function App() {
     } else {
       hideAllServerWebviews().catch(console.error);
     }
-  }, [activeView, servers, isModalOpen, renameTarget, iconChangeTarget, removeTarget]);
+  }, [activeView, servers, isModalOpen, renameTarget, iconChangeTarget, removeTarget, clearTarget, proxyTarget, domainsTarget, certPrompt, orphans]);
+
+  // Create a server's webview after Rust has confirmed the server is up. A
+  // failed probe leaves no webview, and the loading page shows why. If it
+  // failed on a self-signed (or changed) certificate, ask whether to trust it.
+  const openServerWebview = useCallback(async (id: string, url: string) => {
+    try {
+      await createServerWebview(id, url, devicePrefsRef.current, true);
+      createdWebviews.current.add(id);
+      setLoadErrors(prev => { const next = { ...prev }; delete next[id]; return next; });
+      await showServerWebview(id);
+    } catch (e) {
+      console.error("Failed to create server webview:", e);
+      setLoadErrors(prev => ({ ...prev, [id]: String(e) }));
+      const report = await probeServer(url, id).catch(() => null);
+      if (report?.certificate && needsCertificateTrust(report)) {
+        setCertPrompt({ serverId: id, url, certificate: report.certificate });
+      }
+    }
+  }, []);
+
+  const trustCertificate = useCallback(async () => {
+    if (!certPrompt) return;
+    const { serverId, url, certificate } = certPrompt;
+    setCertPrompt(null);
+    try {
+      await registry.trustServerCertificate(serverId, certificate.sha256);
+    } catch (e) {
+      console.error("Failed to trust certificate:", e);
+      return;
+    }
+    await openServerWebview(serverId, url);
+  }, [certPrompt, openServerWebview]);
+
+  const forgetCertificate = useCallback((id: string) => {
+    registry.trustServerCertificate(id, null).catch(console.error);
+  }, []);
+
+  // ── Proxy settings ─────────────────────────────────────────────────────────
+  const openProxyDialog = useCallback((id: string) => {
+    const server = servers.find(s => s.id === id);
+    if (!server) return;
+    setProxyTarget(server);
+    openModal();
+  }, [servers, openModal]);
+
+  // The proxy is fixed when a webview is created, so a live one is rebuilt.
+  const commitProxy = useCallback(async (server: Server, proxy: registry.ServerProxy | null, password?: string) => {
+    await registry.setServerProxy(server.id, proxy, password);
+    setProxyTarget(null);
+    closeModal();
+    if (!IS_TAURI || !createdWebviews.current.has(server.id)) return;
+    await destroyServerWebview(server.id).catch(console.error);
+    createdWebviews.current.delete(server.id);
+    if (activeView === server.id) await openServerWebview(server.id, server.url);
+  }, [activeView, closeModal, openServerWebview]);
+
+  // ── Sign-in domains ────────────────────────────────────────────────────────
+  // Read by Rust on every navigation, so live webviews need no rebuild.
+  const openDomainsDialog = useCallback((id: string) => {
+    const server = servers.find(s => s.id === id);
+    if (!server) return;
+    setDomainsTarget(server);
+    openModal();
+  }, [servers, openModal]);
+
+  const commitDomains = useCallback(async (id: string, domains: string[]) => {
+    await registry.setServerInAppDomains(id, domains);
+    setDomainsTarget(null);
+    closeModal();
+  }, [closeModal]);
 
   // ── Server selection: lazily create webview on first visit ───────────────
   const handleSelectView = useCallback(
@@ -267,23 +443,78 @@ export default function App() {
 
       if (!createdWebviews.current.has(view)) {
         // First time this server is selected: create its webview.
-        try {
-          await createServerWebview(view, server.url, devicePrefsRef.current);
-          createdWebviews.current.add(view);
-          await showServerWebview(view);
-        } catch (e) {
-          console.error("Failed to create server webview:", e);
-        }
+        await openServerWebview(view, server.url);
       }
       // If already created, the visibility useEffect above handles show/hide.
     },
-    [servers]
+    [servers, openServerWebview]
   );
 
+  // ── Notifications: a click switches to the server that raised it ─────────
+  // Rust has already brought the window forward.
+  useEffect(() => {
+    if (!IS_TAURI) return;
+    let unlisten: (() => void) | null = null;
+    onNotificationClicked(e => {
+      if (servers.some(s => s.id === e.serverId)) handleSelectView(e.serverId);
+    })
+      .then(fn => { unlisten = fn; })
+      .catch(console.error);
+    return () => { unlisten?.(); };
+  }, [servers, handleSelectView]);
+
+  // ── Tray / shortcuts: picking a server switches to it like a sidebar click
+  useEffect(() => {
+    if (!IS_TAURI) return;
+    let unlisten: (() => void) | null = null;
+    onSelectServer(e => {
+      if (servers.some(s => s.id === e.serverId)) handleSelectView(e.serverId);
+    })
+      .then(fn => { unlisten = fn; })
+      .catch(console.error);
+    return () => { unlisten?.(); };
+  }, [servers, handleSelectView]);
+
+  // ── Mute / deafen: owned by Rust, also switched from the tray ────────────
+  useEffect(() => {
+    if (!IS_TAURI) return;
+    let unlisten: (() => void) | null = null;
+    onVoiceChanged(setVoice)
+      .then(fn => { unlisten = fn; })
+      .catch(console.error);
+    getVoiceState().then(setVoice).catch(console.error);
+    return () => { unlisten?.(); };
+  }, []);
+
+  const clearVoice = useCallback(() => {
+    setVoiceState({ muted: false, deafened: false }).catch(console.error);
+  }, []);
+
+  // ── Unread badges: Rust owns the counts; we report what is on screen ──────
+  useEffect(() => {
+    if (!IS_TAURI) return;
+    let unlisten: (() => void) | null = null;
+    onBadgesChanged(b => setBadges(b.servers))
+      .then(fn => { unlisten = fn; })
+      .catch(console.error);
+    getBadges().then(b => setBadges(b.servers)).catch(console.error);
+    return () => { unlisten?.(); };
+  }, []);
+
+  useEffect(() => {
+    if (!IS_TAURI) return;
+    const isServer = servers.some((s) => s.id === activeView);
+    setViewedServer(isServer ? activeView : null).catch(console.error);
+  }, [activeView, servers]);
+
+  const retryServer = useCallback((id: string) => {
+    const server = servers.find(s => s.id === id);
+    if (server && activeView === id) openServerWebview(id, server.url);
+  }, [servers, activeView, openServerWebview]);
+
   // ── Add server ─────────────────────────────────────────────────────────────
   const addServer = useCallback(
     async (name: string, url: string, iconUrl?: string) => {
-      const id = crypto.randomUUID();
       const iconLetter = name.trim()[0]?.toUpperCase() ?? "?";
       const iconColor = genColor(servers.length);
 
@@ -305,22 +536,25 @@ export default function App() {
         }
       } catch { /* invalid URL — leave as-is */ }
 
-      const server: Server = { id, name, url: baseUrl, iconLetter, iconColor, ...(iconUrl ? { iconUrl } : {}) };
-
-      setServers((prev) => [...prev, server]);
-      setActiveView(id);
+      const entry: registry.NewServer = { name, url: baseUrl, iconLetter, iconColor, ...(iconUrl ? { iconUrl } : {}) };
 
+      let id: string;
       if (IS_TAURI) {
         try {
-          await createServerWebview(id, inviteUrl ?? baseUrl, devicePrefsRef.current);
-          createdWebviews.current.add(id);
-          await showServerWebview(id);
+          id = (await registry.addServer(entry)).id;
         } catch (e) {
-          console.error("Failed to create server webview:", e);
+          console.error("Failed to add server:", e);
+          return;
         }
+      } else {
+        id = crypto.randomUUID();
+        setServers((prev) => [...prev, { id, ...entry }]);
       }
+      setActiveView(id);
+
+      if (IS_TAURI) await openServerWebview(id, inviteUrl ?? baseUrl);
     },
-    [servers.length]
+    [servers.length, openServerWebview]
   );
 
   // ── Change server icon ─────────────────────────────────────────────────────
@@ -332,14 +566,12 @@ export default function App() {
   }, [servers, openModal]);
 
   const commitIconChange = useCallback((id: string, iconUrl: string | null) => {
-    setServers(prev => prev.map(s =>
-      s.id === id
-        ? { ...s, ...(iconUrl ? { iconUrl } : { iconUrl: undefined }) }
-        : s
-    ));
+    // Rust moves a new data URL to the icon cache; clearing both fields
+    // reverts to the letter.
+    updateServerEntry(id, s => ({ ...s, ...(iconUrl ? { iconUrl } : { iconUrl: undefined, iconRev: undefined }) }));
     setIconChangeTarget(null);
     closeModal();
-  }, [closeModal]);
+  }, [closeModal, updateServerEntry]);
 
   // ── Rename server ──────────────────────────────────────────────────────────
   const openRenameDialog = useCallback((id: string) => {
@@ -351,19 +583,26 @@ export default function App() {
 
   const commitRename = useCallback((id: string, newName: string) => {
     const newLetter = newName.trim()[0]?.toUpperCase() ?? "?";
-    setServers(prev => prev.map(s =>
-      s.id === id ? { ...s, name: newName, iconLetter: newLetter } : s
-    ));
+    updateServerEntry(id, s => ({ ...s, name: newName, iconLetter: newLetter }));
     setRenameTarget(null);
     closeModal();
-  }, [closeModal]);
+  }, [closeModal, updateServerEntry]);
 
   // ── Toggle "keep server loaded" ───────────────────────────────────────────
   const toggleKeepLoaded = useCallback((id: string) => {
-    setServers(prev => prev.map(s =>
-      s.id === id ? { ...s, keepLoaded: !(s.keepLoaded ?? true) } : s
-    ));
-  }, []);
+    updateServerEntry(id, s => ({ ...s, keepLoaded: !(s.keepLoaded ?? true) }));
+  }, [updateServerEntry]);
+
+  // ── Mute a server's notifications ─────────────────────────────────────────
+  const toggleNotificationsMuted = useCallback((id: string) => {
+    const server = servers.find(s => s.id === id);
+    if (!server) return;
+    if (!IS_TAURI) {
+      setServers(prev => prev.map(s => s.id === id ? { ...s, notificationsMuted: !s.notificationsMuted } : s));
+      return;
+    }
+    registry.setServerNotificationsMuted(id, !server.notificationsMuted).catch(console.error);
+  }, [servers]);
 
   // ── Refresh server webview ─────────────────────────────────────────────────
   const refreshServer = useCallback(async (id: string) => {
@@ -372,6 +611,31 @@ export default function App() {
     }
   }, []);
 
+  // ── Clear cache / cookies / storage ───────────────────────────────────────
+  const openClearDialog = useCallback((id: string, scope: ClearScope) => {
+    const server = servers.find(s => s.id === id);
+    if (!server) return;
+    setClearTarget({ server, scope });
+    openModal();
+  }, [servers, openModal]);
+
+  const confirmClear = useCallback(async (server: Server, scope: ClearScope) => {
+    setClearTarget(null);
+    closeModal();
+    if (!IS_TAURI) return;
+    try {
+      await clearServerData(server.id, server.url, scope, devicePrefsRef.current);
+    } catch (e) {
+      console.error("Failed to clear server data:", e);
+    }
+    // A recreated webview can come up visible — put the right one back on top.
+    if (activeView === server.id) {
+      await showServerWebview(server.id).catch(console.error);
+    } else {
+      await hideAllServerWebviews().catch(console.error);
+    }
+  }, [closeModal, activeView]);
+
   // ── Remove server ──────────────────────────────────────────────────────────
   const openRemoveDialog = useCallback((id: string) => {
     const server = servers.find(s => s.id === id);
@@ -385,8 +649,10 @@ export default function App() {
       await destroyServerWebview(id).catch(console.error);
       await deleteServerData(id).catch(console.error);
       createdWebviews.current.delete(id);
+      await registry.removeServer(id).catch(console.error);
+    } else {
+      setServers((prev) => prev.filter((s) => s.id !== id));
     }
-    setServers((prev) => prev.filter((s) => s.id !== id));
     setActiveView("home");
     setRemoveTarget(null);
     closeModal();
@@ -416,6 +682,7 @@ export default function App() {
 
   const isServerActive = servers.some((s) => s.id === activeView);
   const activeServer = servers.find((s) => s.id === activeView);
+  const certPromptServer = certPrompt && servers.find((s) => s.id === certPrompt.serverId);
 
   // ── Update dialog handlers ────────────────────────────────────────────────
   const handleUpdateYes = useCallback(async () => {
@@ -439,6 +706,12 @@ export default function App() {
     } catch (e) { console.error(e); }
   }, []);
 
+  // ── Orphaned data dialog handlers ─────────────────────────────────────────
+  const handleOrphansResolve = useCallback(async (action: storage.OrphanAction) => {
+    await storage.resolveOrphanedServerData(orphans.map(o => o.serverId), action);
+    setOrphans([]);
+  }, [orphans]);
+
   return (
     <div className="app-shell">
       <TitleBar iconSrc={iconSrc} />
@@ -446,6 +719,7 @@ export default function App() {
         <Sidebar
           servers={servers}
           activeView={activeView}
+          badges={badges}
           onSelectView={handleSelectView}
           onAddServer={() => {
             openModal();
@@ -453,22 +727,35 @@ export default function App() {
           }}
           onRenameServer={openRenameDialog}
           onRefreshServer={refreshServer}
+          onClearServerData={openClearDialog}
+          onForgetCertificate={forgetCertificate}
+          onEditProxy={openProxyDialog}
+          onEditSignInDomains={openDomainsDialog}
           onRemoveServer={openRemoveDialog}
           onChangeServerIcon={openIconChangeDialog}
           onToggleKeepLoaded={toggleKeepLoaded}
+          onToggleNotificationsMuted={toggleNotificationsMuted}
           onContextMenuOpenChange={(open) => open ? openModal() : closeModal()}
           pttState={pttState}
+          pttError={pttError}
+          voice={voice}
+          onClearVoice={clearVoice}
         />
 
         <main
           className="main-content"
           onContextMenu={(e) => e.preventDefault()}
-          style={{ visibility: IS_TAURI && isServerActive ? "hidden" : "visible" }}
+          style={{ visibility: IS_TAURI && isServerActive && !loadErrors[activeView] ? "hidden" : "visible" }}
         >
           {activeView === "home" && <HomePage />}
-          {activeView === "settings" && <SettingsPage onDevicePrefsChange={handleDevicePrefsChange} onPttEnabledChange={handlePttEnabledChange} />}
+          {activeView === "settings" && <SettingsPage servers={servers} onDevicePrefsChange={handleDevicePrefsChange} onPttEnabledChange={handlePttEnabledChange} />}
           {isServerActive && activeServer && (
-            <ServerLoadingPage server={activeServer} onRemove={openRemoveDialog} />
+            <ServerLoadingPage
+              server={activeServer}
+              error={loadErrors[activeServer.id]}
+              onRetry={retryServer}
+              onRemove={openRemoveDialog}
+            />
           )}
         </main>
       </div>
@@ -511,6 +798,15 @@ export default function App() {
         />
       )}
 
+      {clearTarget && (
+        <ClearDataDialog
+          server={clearTarget.server}
+          scope={clearTarget.scope}
+          onConfirm={() => confirmClear(clearTarget.server, clearTarget.scope)}
+          onCancel={() => { setClearTarget(null); closeModal(); }}
+        />
+      )}
+
       {updateInfo && (
         <UpdateDialog
           currentVersion={updateInfo.current}
@@ -520,6 +816,39 @@ export default function App() {
           onNeverAskAgain={handleUpdateNever}
         />
       )}
+
+      {proxyTarget && (
+        <ProxySettingsDialog
+          server={proxyTarget}
+          onConfirm={(proxy, password) => commitProxy(proxyTarget, proxy, password)}
+          onCancel={() => { setProxyTarget(null); closeModal(); }}
+        />
+      )}
+
+      {domainsTarget && (
+        <SignInDomainsDialog
+          server={domainsTarget}
+          onConfirm={(domains) => commitDomains(domainsTarget.id, domains)}
+          onCancel={() => { setDomainsTarget(null); closeModal(); }}
+        />
+      )}
+
+      {certPrompt && certPromptServer && (
+        <TrustCertificateDialog
+          server={certPromptServer}
+          certificate={certPrompt.certificate}
+          onTrust={trustCertificate}
+          onCancel={() => setCertPrompt(null)}
+        />
+      )}
+
+      {orphans.length > 0 && (
+        <OrphanDataDialog
+          orphans={orphans}
+          onResolve={handleOrphansResolve}
+          onKeep={() => setOrphans([])}
+        />
+      )}
     </div>
   );
 }
@@ -1,5 +1,9 @@
 import { useState, useEffect, useRef, useCallback } from "react";
 import "./AddServerDialog.css";
+import { fetchServerMetadata } from "../lib/serverRegistry";
+import { probeServer, needsCertificateTrust } from "../lib/webviewManager";
+
+const IS_TAURI = typeof (window as any).__TAURI_INTERNALS__ !== "undefined";
 
 interface Props {
   onConfirm: (name: string, url: string, iconUrl?: string) => void;
@@ -21,6 +25,9 @@ export default function AddServerDialog({ onConfirm, onCancel }: Props) {
   const [url, setUrl]         = useState("");
   const [error, setError]     = useState("");
   const [iconUrl, setIconUrl] = useState<string | null>(null);
+  const [fetching, setFetching] = useState(false);
+  const [untrustedCert, setUntrustedCert] = useState(false); // prefill blocked by a self-signed certificate
+  const fetchedFor = useRef<string | null>(null); // URL the last metadata fetch was for
   const nameRef    = useRef<HTMLInputElement>(null);
   const fileRef    = useRef<HTMLInputElement>(null);
 
@@ -49,6 +56,31 @@ export default function AddServerDialog({ onConfirm, onCancel }: Props) {
     e.target.value = "";
   }, []);
 
+  // Once a URL is entered, ask the server for its name and icon and fill in
+  // whatever the user hasn't set themselves.
+  const prefillFromServer = useCallback(async () => {
+    const target = url.trim();
+    if (!IS_TAURI || !target || fetchedFor.current === target) return;
+    fetchedFor.current = target;
+    setFetching(true);
+    setUntrustedCert(false);
+    const fullUrl = /^https?:\/\//i.test(target) ? target : `https://${target}`;
+    try {
+      const meta = await fetchServerMetadata(fullUrl);
+      if (fetchedFor.current !== target) return; // URL changed meanwhile
+      if (meta.name) setName(prev => prev.trim() ? prev : meta.name!.slice(0, 40));
+      if (meta.iconUrl) setIconUrl(prev => prev ?? meta.iconUrl);
+    } catch (e) {
+      console.error("Failed to fetch server info:", e);
+      // A server that isn't saved yet has no pinned certificate, so a
+      // self-signed one blocks the prefill. Say so rather than fail silently.
+      const report = await probeServer(fullUrl).catch(() => null);
+      if (fetchedFor.current === target && report && needsCertificateTrust(report)) setUntrustedCert(true);
+    } finally {
+      setFetching(false);
+    }
+  }, [url]);
+
   const handleSubmit = () => {
     if (!name.trim()) { setError("Server name is required."); return; }
     if (!url.trim())  { setError("Server URL is required."); return; }
@@ -161,12 +193,17 @@ export default function AddServerDialog({ onConfirm, onCancel }: Props) {
                   type="text"
                   placeholder="demo.sharkord.com"
                   value={url.replace(/^https?:\/\//i, "")}
-                  onChange={e => { setUrl(e.target.value); setError(""); }}
+                  onChange={e => { setUrl(e.target.value); setError(""); setUntrustedCert(false); }}
+                  onBlur={prefillFromServer}
                   onKeyDown={e => e.key === "Enter" && handleSubmit()}
                 />
               </div>
               <p className="asd-hint">
-                Default Sharkord port is <code>4991</code>. Type <code>http://</code> explicitly for non-HTTPS servers.
+                {fetching
+                  ? "Fetching server name and icon…"
+                  : untrustedCert
+                  ? "This server's certificate isn't signed by a trusted authority, so its name and icon can't be read yet. Add it anyway — you'll be asked whether to trust the certificate when it first loads."
+                  : <>Default Sharkord port is <code>4991</code>. Type <code>http://</code> explicitly for non-HTTPS servers.</>}
               </p>
             </div>
           </div>
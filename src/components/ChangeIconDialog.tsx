@@ -1,6 +1,7 @@
 import { useState, useRef, useCallback, useEffect } from "react";
 import "./ChangeIconDialog.css";
 import { Server } from "../App";
+import { serverIconSrc } from "../lib/serverRegistry";
 
 interface Props {
   server: Server;
@@ -19,7 +20,8 @@ function fileToDataUrl(file: File): Promise<string> {
 
 export default function ChangeIconDialog({ server, onConfirm, onCancel }: Props) {
   // Start with the server's current icon state so preview is accurate
-  const [iconUrl, setIconUrl] = useState<string | null>(server.iconUrl ?? null);
+  const currentIcon = serverIconSrc(server) ?? null;
+  const [iconUrl, setIconUrl] = useState<string | null>(currentIcon);
   const [error, setError]     = useState("");
   const fileRef = useRef<HTMLInputElement>(null);
 
@@ -46,7 +48,7 @@ export default function ChangeIconDialog({ server, onConfirm, onCancel }: Props)
     e.target.value = "";
   }, []);
 
-  const hasChanged = iconUrl !== (server.iconUrl ?? null);
+  const hasChanged = iconUrl !== currentIcon;
 
   return (
     <div className="cid-overlay" onClick={e => e.target === e.currentTarget && onCancel()}>
@@ -0,0 +1,70 @@
+import { useEffect } from "react";
+import "./ConfirmRemoveDialog.css";
+import { Server } from "../App";
+import { serverIconSrc } from "../lib/serverRegistry";
+import type { ClearScope } from "../lib/webviewManager";
+
+const COPY: Record<ClearScope, { title: string; body: string; action: string }> = {
+  cache: {
+    title: "Clear Cache",
+    body: "Cached files will be re-downloaded the next time it loads. You will stay signed in.",
+    action: "Clear Cache",
+  },
+  cookies: {
+    title: "Sign Out",
+    body: "Cookies and session data will be deleted and you will need to sign in again.",
+    action: "Sign Out",
+  },
+  storage: {
+    title: "Clear Site Storage",
+    body: "IndexedDB, local storage and other saved site data will be deleted. Settings the server keeps in your browser will be reset.",
+    action: "Clear Storage",
+  },
+};
+
+interface Props {
+  server: Server;
+  scope: ClearScope;
+  onConfirm: () => void;
+  onCancel: () => void;
+}
+
+export default function ClearDataDialog({ server, scope, onConfirm, onCancel }: Props) {
+  useEffect(() => {
+    const onKey = (e: KeyboardEvent) => { if (e.key === "Escape") onCancel(); };
+    window.addEventListener("keydown", onKey);
+    return () => window.removeEventListener("keydown", onKey);
+  }, [onCancel]);
+
+  const copy = COPY[scope];
+
+  const iconSrc = serverIconSrc(server);
+
+  return (
+    <div className="crd-overlay" onClick={e => e.target === e.currentTarget && onCancel()}>
+      <div className="crd-modal">
+
+        <div className="crd-icon-wrap">
+          <div className="crd-server-icon" style={{ background: iconSrc ? "transparent" : server.iconColor }}>
+            {iconSrc
+              ? <img src={iconSrc} alt={server.name} className="crd-server-img" />
+              : server.iconLetter
+            }
+          </div>
+        </div>
+
+        <div className="crd-body">
+          <h2 className="crd-title">{copy.title}</h2>
+          <p className="crd-sub">
+            This affects <strong>{server.name}</strong> only. {copy.body}
+          </p>
+
+          <div className="crd-actions">
+            <button className="crd-btn-cancel" onClick={onCancel}>Cancel</button>
+            <button className="crd-btn-remove" onClick={onConfirm}>{copy.action}</button>
+          </div>
+        </div>
+      </div>
+    </div>
+  );
+}
@@ -1,6 +1,7 @@
 import { useEffect } from "react";
 import "./ConfirmRemoveDialog.css";
 import { Server } from "../App";
+import { serverIconSrc } from "../lib/serverRegistry";
 
 interface Props {
   server: Server;
@@ -15,14 +16,16 @@ export default function ConfirmRemoveDialog({ server, onConfirm, onCancel }: Pro
     return () => window.removeEventListener("keydown", onKey);
   }, [onCancel]);
 
+  const iconSrc = serverIconSrc(server);
+
   return (
     <div className="crd-overlay" onClick={e => e.target === e.currentTarget && onCancel()}>
       <div className="crd-modal">
 
         <div className="crd-icon-wrap">
-          <div className="crd-server-icon" style={{ background: server.iconUrl ? "transparent" : server.iconColor }}>
-            {server.iconUrl
-              ? <img src={server.iconUrl} alt={server.name} className="crd-server-img" />
+          <div className="crd-server-icon" style={{ background: iconSrc ? "transparent" : server.iconColor }}>
+            {iconSrc
+              ? <img src={iconSrc} alt={server.name} className="crd-server-img" />
               : server.iconLetter
             }
           </div>
@@ -0,0 +1,70 @@
+import { useState } from "react";
+import "./UpdateDialog.css";
+import "./OrphanDataDialog.css";
+import { type OrphanAction, type ServerStorage, formatBytes } from "../lib/storage";
+
+interface Props {
+  orphans: ServerStorage[];
+  onResolve: (action: OrphanAction) => Promise<void>;
+  onKeep: () => void;
+}
+
+export default function OrphanDataDialog({ orphans, onResolve, onKeep }: Props) {
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const total = orphans.reduce((sum, o) => sum + o.usage.total, 0);
+
+  const resolve = async (action: OrphanAction) => {
+    setBusy(true);
+    setError(null);
+    try {
+      await onResolve(action);
+    } catch (e) {
+      setError(String(e));
+      setBusy(false);
+    }
+  };
+
+  return (
+    <div className="upd-overlay">
+      <div className="upd-modal">
+        <div className="upd-icon-wrap">
+          <div className="upd-icon upd-icon--warn">
+            <svg width="28" height="28" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2" strokeLinecap="round" strokeLinejoin="round">
+              <path d="M22 19a2 2 0 01-2 2H4a2 2 0 01-2-2V5a2 2 0 012-2h5l2 3h9a2 2 0 012 2z"/>
+              <line x1="12" y1="11" x2="12" y2="14"/>
+              <line x1="12" y1="17" x2="12.01" y2="17"/>
+            </svg>
+          </div>
+        </div>
+        <div className="upd-body">
+          <h2 className="upd-title">Leftover Server Data</h2>
+          <p className="upd-sub">
+            Found data for <strong>{orphans.length}</strong> server{orphans.length === 1 ? "" : "s"} that
+            no longer {orphans.length === 1 ? "exists" : "exist"}, using <strong>{formatBytes(total)}</strong>.
+          </p>
+          <ul className="odd-list">
+            {orphans.map(o => (
+              <li key={o.serverId} className="odd-row">
+                <span className="odd-id">{o.serverId}</span>
+                <span className="odd-size">{formatBytes(o.usage.total)}</span>
+              </li>
+            ))}
+          </ul>
+          {error && <p className="odd-error">{error}</p>}
+          <div className="upd-actions upd-actions--col">
+            <button className="upd-btn upd-btn--danger" disabled={busy} onClick={() => resolve("delete")}>
+              Delete
+            </button>
+            <button className="upd-btn upd-btn--ghost" disabled={busy} onClick={() => resolve("quarantine")}>
+              Move to quarantine folder
+            </button>
+            <button className="upd-btn upd-btn--ghost" disabled={busy} onClick={onKeep}>
+              Keep for now
+            </button>
+          </div>
+        </div>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,187 @@
+import { useState, useEffect } from "react";
+import "./RenameServerDialog.css";
+import "./ProxySettingsDialog.css";
+import { Server } from "../App";
+import { serverIconSrc, type ServerProxy } from "../lib/serverRegistry";
+
+type ProxyKind = "none" | "http" | "socks5";
+
+const KINDS: { kind: ProxyKind; label: string }[] = [
+  { kind: "none", label: "None" },
+  { kind: "http", label: "HTTP" },
+  { kind: "socks5", label: "SOCKS5" },
+];
+
+interface Props {
+  server: Server;
+  /** `password` undefined keeps the stored one. Rejects with Rust's message. */
+  onConfirm: (proxy: ServerProxy | null, password?: string) => Promise<void>;
+  onCancel: () => void;
+}
+
+function parseStored(proxy?: ServerProxy): { kind: ProxyKind; host: string; port: string } {
+  if (!proxy) return { kind: "none", host: "", port: "" };
+  try {
+    const url = new URL(proxy.url);
+    const kind = url.protocol === "socks5:" ? "socks5" : "http";
+    // URL drops the http default, which the backend stores the same way.
+    return { kind, host: url.hostname, port: url.port || (kind === "http" ? "80" : "") };
+  } catch {
+    return { kind: "http", host: "", port: "" };
+  }
+}
+
+export default function ProxySettingsDialog({ server, onConfirm, onCancel }: Props) {
+  const stored = parseStored(server.proxy);
+  const [kind, setKind] = useState<ProxyKind>(stored.kind);
+  const [host, setHost] = useState(stored.host);
+  const [port, setPort] = useState(stored.port);
+  const [username, setUsername] = useState(server.proxy?.username ?? "");
+  const [password, setPassword] = useState("");
+  const [error, setError] = useState("");
+  const [saving, setSaving] = useState(false);
+
+  useEffect(() => {
+    const onKey = (e: KeyboardEvent) => { if (e.key === "Escape") onCancel(); };
+    window.addEventListener("keydown", onKey);
+    return () => window.removeEventListener("keydown", onKey);
+  }, [onCancel]);
+
+  // A saved password only carries over while the username stays the same.
+  const hasSavedPassword = !!server.proxy?.username && username.trim() === server.proxy.username;
+
+  const handleSubmit = async () => {
+    let proxy: ServerProxy | null = null;
+    if (kind !== "none") {
+      if (!host.trim()) { setError("Enter the proxy host."); return; }
+      if (!/^\d{1,5}$/.test(port.trim())) { setError("Enter the proxy port."); return; }
+      const hostPart = host.trim().includes(":") && !host.trim().startsWith("[") ? `[${host.trim()}]` : host.trim();
+      proxy = { url: `${kind}://${hostPart}:${port.trim()}`, ...(username.trim() ? { username: username.trim() } : {}) };
+      if (proxy.username && !password && !hasSavedPassword) { setError("Enter the proxy password."); return; }
+    }
+    setSaving(true);
+    try {
+      await onConfirm(proxy, password || undefined);
+    } catch (e) {
+      setError(String(e));
+      setSaving(false);
+    }
+  };
+
+  const iconSrc = serverIconSrc(server);
+
+  return (
+    <div className="rsd-overlay" onClick={e => e.target === e.currentTarget && onCancel()}>
+      <div className="rsd-modal">
+        <div className="rsd-band" />
+
+        <div className="rsd-preview-wrap">
+          <div className="rsd-preview-icon" style={{ background: iconSrc ? "transparent" : server.iconColor }}>
+            {iconSrc
+              ? <img src={iconSrc} alt={server.name} style={{ width: "100%", height: "100%", objectFit: "cover", borderRadius: "inherit" }} />
+              : server.iconLetter
+            }
+          </div>
+        </div>
+
+        <div className="rsd-body">
+          <h2 className="rsd-title">Proxy Settings</h2>
+          <p className="rsd-sub">
+            Connect to <strong>{server.name}</strong> through an HTTP or SOCKS5 proxy. The password is kept
+            in your system's credential store. The server reloads when you save.
+          </p>
+
+          {error && (
+            <div className="rsd-error">
+              <svg width="14" height="14" viewBox="0 0 16 16" fill="currentColor">
+                <path d="M8 1a7 7 0 100 14A7 7 0 008 1zm-.75 3.75a.75.75 0 011.5 0v4a.75.75 0 01-1.5 0v-4zm.75 7a.875.875 0 110-1.75.875.875 0 010 1.75z"/>
+              </svg>
+              {error}
+            </div>
+          )}
+
+          <div className="psd-kinds">
+            {KINDS.map(k => (
+              <button
+                key={k.kind}
+                className={`psd-kind${kind === k.kind ? " psd-kind-active" : ""}`}
+                onClick={() => { setKind(k.kind); setError(""); }}
+              >
+                {k.label}
+              </button>
+            ))}
+          </div>
+
+          {kind !== "none" && (
+            <>
+              <div className="psd-row">
+                <div className="rsd-field psd-host">
+                  <label className="rsd-label">Host</label>
+                  <input
+                    className="rsd-input"
+                    type="text"
+                    placeholder={kind === "socks5" ? "127.0.0.1" : "proxy.example.com"}
+                    value={host}
+                    onChange={e => { setHost(e.target.value); setError(""); }}
+                    autoFocus
+                  />
+                </div>
+                <div className="rsd-field psd-port">
+                  <label className="rsd-label">Port</label>
+                  <input
+                    className="rsd-input"
+                    type="text"
+                    inputMode="numeric"
+                    placeholder={kind === "socks5" ? "1080" : "3128"}
+                    value={port}
+                    onChange={e => { setPort(e.target.value); setError(""); }}
+                  />
+                </div>
+              </div>
+
+              <div className="psd-row">
+                <div className="rsd-field">
+                  <label className="rsd-label">Username (optional)</label>
+                  <input
+                    className="rsd-input"
+                    type="text"
+                    autoComplete="off"
+                    value={username}
+                    onChange={e => { setUsername(e.target.value); setError(""); }}
+                  />
+                </div>
+                <div className="rsd-field">
+                  <label className="rsd-label">Password</label>
+                  <input
+                    className="rsd-input"
+                    type="password"
+                    autoComplete="off"
+                    placeholder={hasSavedPassword ? "Saved" : ""}
+                    disabled={!username.trim()}
+                    value={password}
+                    onChange={e => { setPassword(e.target.value); setError(""); }}
+                    onKeyDown={e => e.key === "Enter" && handleSubmit()}
+                  />
+                </div>
+              </div>
+
+              {kind === "socks5" && username.trim() && (
+                <p className="psd-note">
+                  Server webviews can't sign in to SOCKS5 proxies; the username and password are only
+                  used for Sweetshark's own requests to this server.
+                </p>
+              )}
+            </>
+          )}
+
+          <div className="rsd-actions">
+            <button className="rsd-btn-cancel" onClick={onCancel}>Cancel</button>
+            <button className="rsd-btn-confirm" onClick={handleSubmit} disabled={saving}>
+              {saving ? "Saving…" : "Save"}
+            </button>
+          </div>
+        </div>
+      </div>
+    </div>
+  );
+}
@@ -1,6 +1,7 @@
 import { useState, useEffect, useRef } from "react";
 import "./RenameServerDialog.css";
 import { Server } from "../App";
+import { serverIconSrc } from "../lib/serverRegistry";
 
 interface Props {
   server: Server;
@@ -27,6 +28,7 @@ export default function RenameServerDialog({ server, onConfirm, onCancel }: Prop
   };
 
   const previewLetter = name.trim()[0]?.toUpperCase() ?? server.iconLetter;
+  const iconSrc = serverIconSrc(server);
 
   return (
     <div className="rsd-overlay" onClick={e => e.target === e.currentTarget && onCancel()}>
@@ -34,9 +36,9 @@ export default function RenameServerDialog({ server, onConfirm, onCancel }: Prop
         <div className="rsd-band" />
 
         <div className="rsd-preview-wrap">
-          <div className="rsd-preview-icon" style={{ background: server.iconUrl ? "transparent" : server.iconColor }}>
-            {server.iconUrl
-              ? <img src={server.iconUrl} alt={server.name} style={{ width: "100%", height: "100%", objectFit: "cover", borderRadius: "inherit" }} />
+          <div className="rsd-preview-icon" style={{ background: iconSrc ? "transparent" : server.iconColor }}>
+            {iconSrc
+              ? <img src={iconSrc} alt={server.name} style={{ width: "100%", height: "100%", objectFit: "cover", borderRadius: "inherit" }} />
               : previewLetter
             }
           </div>
@@ -3,10 +3,12 @@ import "./ServerLoadingPage.css";
 
 interface Props {
   server: Server;
+  error?: string;   // probe diagnosis when the server could not be reached
+  onRetry: (id: string) => void;
   onRemove: (id: string) => void;
 }
 
-export default function ServerLoadingPage({ server, onRemove }: Props) {
+export default function ServerLoadingPage({ server, error, onRetry, onRemove }: Props) {
   return (
     <div className="slp-root">
       <div className="slp-card">
@@ -16,11 +18,22 @@ export default function ServerLoadingPage({ server, onRemove }: Props) {
         <div className="slp-name">{server.name}</div>
         <div className="slp-url">{server.url}</div>
 
-        <div className="slp-dots">
-          <span /><span /><span />
-        </div>
+        {error ? (
+          <>
+            <p className="slp-error">{error}</p>
+            <button className="slp-retry" onClick={() => onRetry(server.id)}>
+              Try again
+            </button>
+          </>
+        ) : (
+          <>
+            <div className="slp-dots">
+              <span /><span /><span />
+            </div>
 
-        <p className="slp-hint">Loading Sharkord…</p>
+            <p className="slp-hint">Loading Sharkord…</p>
+          </>
+        )}
 
         <button className="slp-remove" onClick={() => onRemove(server.id)}>
           Remove server
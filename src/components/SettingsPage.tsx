@@ -1,5 +1,13 @@
 import { useState, useEffect, useCallback, useRef } from "react";
 import PttKeybindDialog from "./PttKeybindDialog";
+import { type ServerStorage, getStorageUsage, onServerDataDeletion, formatBytes } from "../lib/storage";
+import { type NotificationSettings, getNotificationSettings, setNotificationSettings, isQuiet } from "../lib/notifications";
+import { type TraySettings, getTraySettings, setTraySettings } from "../lib/tray";
+import {
+  type ShortcutAction, type ShortcutStatus, MAX_SHORTCUT_SERVER,
+  getShortcuts, setShortcuts, sameAction, acceleratorFromEvent, formatAccelerator,
+} from "../lib/shortcuts";
+import type { Server } from "../App";
 import "./SettingsPage.css";
 
 const IS_TAURI = typeof (window as any).__TAURI_INTERNALS__ !== "undefined";
@@ -77,33 +85,198 @@ async function saveDevicePrefs(prefs: DevicePrefs) {
 }
 
 
+// ── Notifications ─────────────────────────────────────────────────────────────
+
+const NOTIFICATIONS_DEFAULT: NotificationSettings = { doNotDisturb: false };
+
+// Do-not-disturb choices; `ms` is how long a timed one lasts.
+const DND_CHOICES: { value: string; label: string; ms?: number }[] = [
+  { value: "off",     label: "Off" },
+  { value: "1h",      label: "For 1 hour",  ms: 60 * 60 * 1000 },
+  { value: "8h",      label: "For 8 hours", ms: 8 * 60 * 60 * 1000 },
+  { value: "24h",     label: "For 24 hours", ms: 24 * 60 * 60 * 1000 },
+  { value: "forever", label: "Until I turn it off" },
+];
+
+async function loadNotificationSettings(): Promise<NotificationSettings> {
+  if (!IS_TAURI) return NOTIFICATIONS_DEFAULT;
+  try { return await getNotificationSettings(); }
+  catch { return NOTIFICATIONS_DEFAULT; }
+}
+
+function dndSettings(choice: string): NotificationSettings {
+  if (choice === "off") return { doNotDisturb: false };
+  const ms = DND_CHOICES.find(c => c.value === choice)?.ms;
+  return { doNotDisturb: true, doNotDisturbUntil: ms ? Date.now() + ms : null };
+}
+
+// ── Tray ──────────────────────────────────────────────────────────────────────
+
+const TRAY_DEFAULT: TraySettings = { closeToTray: false };
+
+async function loadTraySettings(): Promise<TraySettings> {
+  if (!IS_TAURI) return TRAY_DEFAULT;
+  try { return await getTraySettings(); }
+  catch { return TRAY_DEFAULT; }
+}
+
+// ── Global shortcuts ──────────────────────────────────────────────────────────
+
+const SHORTCUT_ACTIONS: { action: ShortcutAction; label: string }[] = [
+  { action: { type: "toggleMute" },   label: "Toggle mute" },
+  { action: { type: "toggleDeafen" }, label: "Toggle deafen" },
+  { action: { type: "toggleWindow" }, label: "Show / hide window" },
+  { action: { type: "togglePtt" },    label: "Toggle push to talk" },
+  ...Array.from({ length: MAX_SHORTCUT_SERVER }, (_, i) => ({
+    action: { type: "switchServer", index: i + 1 } as ShortcutAction,
+    label: `Switch to server ${i + 1}`,
+  })),
+];
+
+function shortcutKey(action: ShortcutAction): string {
+  return action.type === "switchServer" ? `switchServer${action.index}` : action.type;
+}
+
+async function loadShortcuts(): Promise<ShortcutStatus[]> {
+  if (!IS_TAURI) return [];
+  try { return await getShortcuts(); }
+  catch { return []; }
+}
+
 // ── Push-to-talk config ────────────────────────────────────────────────────────
 
-export interface PttConfig {
-  enabled: boolean;
+export type PttTarget = "system" | "tracks";
+export type PttMode = "pushToTalk" | "toggle" | "pushToMute" | "voiceActivity";
+
+export interface VadConfig {
+  thresholdDb: number;   // RMS level in dBFS that counts as speech
+  attackMs: number;
+  releaseMs: number;     // hangover before closing again
+}
+
+// One named key chord. Any binding opens the mic, so a second one can serve
+// as e.g. a priority key.
+export interface PttBinding {
+  name: string;          // unique within a config; reported in PTT events
   keys: string[];        // browser key names, e.g. ["Control", "Space"]
   tauriKeys: string[];   // tauri shortcut tokens, e.g. ["ctrl", "space"]
 }
 
-const PTT_DEFAULT: PttConfig = { enabled: false, keys: [], tauriKeys: [] };
+export interface PttConfig {
+  enabled: boolean;
+  bindings?: PttBinding[];
+  keys?: string[];       // single chord saved before bindings existed;
+  tauriKeys?: string[];  // only read through pttBindings()
+  target?: PttTarget;    // "tracks" (default) gates only Sharkord's audio, "system" the OS mic
+  mode?: PttMode;
+  vad?: VadConfig;
+  releaseDelayMs?: number; // keep the mic open this long after release
+  debounceMs?: number;     // ignore presses shorter than this
+  cues?: boolean;          // play a short tone when the mic opens/closes
+}
+
+// Payload of the Rust `ptt://pressed` / `ptt://released` events.
+export interface PttEvent {
+  scope: string;           // server id, or "*" for the all-servers binding
+  binding: string | null;  // null for voice activity
+  timestampMs: number;     // monotonic, ms since app start
+  muteApplied: boolean;    // false if the OS/webview mute call failed
+}
+
+// Payload of the Rust `ptt://enabled` event (PTT switched from the tray).
+export interface PttEnabledEvent {
+  scope: string;
+  enabled: boolean;
+}
+
+// Payload of the Rust `ptt://error` event.
+export interface PttErrorEvent {
+  scope: string;
+  message: string;
+  timestampMs: number;
+}
 
-async function loadPttConfig(): Promise<PttConfig> {
-  if (!IS_TAURI) return PTT_DEFAULT;
-  try { const store = await getStore(); return (await store.get<PttConfig>("pttConfig")) ?? PTT_DEFAULT; }
-  catch { return PTT_DEFAULT; }
+// A config's bindings, upgrading a pre-bindings single chord to one named "main".
+export function pttBindings(cfg: PttConfig): PttBinding[] {
+  if (cfg.bindings) return cfg.bindings;
+  return cfg.tauriKeys?.length ? [{ name: "main", keys: cfg.keys ?? [], tauriKeys: cfg.tauriKeys }] : [];
+}
+
+// Whether enabling `cfg` would run anything (PttConfig::has_trigger in lib.rs).
+export function pttUsable(cfg: PttConfig): boolean {
+  return cfg.mode === "voiceActivity" || pttBindings(cfg).some(b => b.tauriKeys.length > 0);
+}
+
+// First free default name for a new binding.
+function nextBindingName(bindings: PttBinding[]): string {
+  const taken = new Set(bindings.map(b => b.name));
+  if (!taken.has("main")) return "main";
+  let n = 2;
+  while (taken.has(`binding ${n}`)) n++;
+  return `binding ${n}`;
+}
+
+// Shape expected by the Rust `set_ptt_config` command.
+export function toRustPttConfig(cfg: PttConfig) {
+  return {
+    enabled:  cfg.enabled,
+    bindings: pttBindings(cfg).map(b => ({ name: b.name, keys: b.tauriKeys })),
+    target:   cfg.target ?? "tracks",
+    mode:     cfg.mode ?? "pushToTalk",
+    releaseDelayMs: cfg.releaseDelayMs ?? 0,
+    debounceMs:     cfg.debounceMs ?? 0,
+    cues:           cfg.cues ?? false,
+    ...(cfg.vad ? { vad: cfg.vad } : {}),
+  };
+}
+
+const PTT_DEFAULT: PttConfig = { enabled: false, bindings: [] };
+
+// Scope of the all-servers config (PTT_ALL_SERVERS in lib.rs). It is stored
+// under "pttConfig"; per-server configs live in PTT_SERVER_CONFIGS_KEY, keyed
+// by server id. A server whose own config is off follows the all-servers one.
+export const PTT_ALL_SERVERS = "*";
+export const PTT_SERVER_CONFIGS_KEY = "pttServerConfigs";
+
+export type PttConfigs = Record<string, PttConfig>;
+
+async function loadPttConfigs(): Promise<PttConfigs> {
+  if (!IS_TAURI) return { [PTT_ALL_SERVERS]: PTT_DEFAULT };
+  try {
+    const store = await getStore();
+    const [all, perServer] = await Promise.all([
+      store.get<PttConfig>("pttConfig"),
+      store.get<PttConfigs>(PTT_SERVER_CONFIGS_KEY),
+    ]);
+    return { ...(perServer ?? {}), [PTT_ALL_SERVERS]: all ?? PTT_DEFAULT };
+  }
+  catch { return { [PTT_ALL_SERVERS]: PTT_DEFAULT }; }
 }
 
-async function savePttConfig(cfg: PttConfig) {
+async function savePttConfig(scope: string, cfg: PttConfig) {
   if (!IS_TAURI) return;
-  try { const store = await getStore(); await store.set("pttConfig", cfg); }
+  try {
+    const store = await getStore();
+    if (scope === PTT_ALL_SERVERS) {
+      await store.set("pttConfig", cfg);
+    } else {
+      const perServer = (await store.get<PttConfigs>(PTT_SERVER_CONFIGS_KEY)) ?? {};
+      await store.set(PTT_SERVER_CONFIGS_KEY, { ...perServer, [scope]: cfg });
+    }
+  }
   catch (e) { console.error("Failed to save PTT config:", e); }
 }
 
-async function applyPttConfig(cfg: PttConfig) {
+// Always sent, enabled or not: the tray and shortcuts switch PTT on from the
+// config Rust last received.
+async function applyPttConfig(scope: string, cfg: PttConfig) {
   if (!IS_TAURI) return;
   try {
     const { invoke } = await import("@tauri-apps/api/core");
-    await invoke("set_ptt_config", { keys: cfg.tauriKeys, enabled: cfg.enabled });
+    await invoke("set_ptt_config", {
+      serverId: scope === PTT_ALL_SERVERS ? null : scope,
+      config: toRustPttConfig(cfg),
+    });
   } catch (e) { console.error("Failed to apply PTT config:", e); }
 }
 
@@ -117,11 +290,46 @@ function displayKey(k: string): string {
   return KEY_DISPLAY_MAP[k] ?? (k.length === 1 ? k.toUpperCase() : k);
 }
 
-async function enumerateByKind(kind: MediaDeviceKind): Promise<MediaDeviceInfo[]> {
-  try { return (await navigator.mediaDevices.enumerateDevices()).filter(d => d.kind === kind); }
+// An entry in a device dropdown. `value` is what gets stored in DevicePrefs:
+// a native endpoint id for mic/speaker when available, otherwise the label.
+interface DeviceOption { value: string; label: string; isDefault?: boolean; }
+
+// Shape returned by the Rust `list_audio_devices` command.
+export interface AudioDevice {
+  id: string;            // stable: PulseAudio source/sink name, Core Audio endpoint id
+  name: string;
+  kind: "input" | "output";
+  isDefault: boolean;
+}
+
+async function enumerateByKind(kind: MediaDeviceKind): Promise<DeviceOption[]> {
+  try {
+    return (await navigator.mediaDevices.enumerateDevices())
+      .filter(d => d.kind === kind)
+      .map(d => ({ value: d.label, label: d.label || `Device ${d.deviceId.slice(0, 8)}` }));
+  }
   catch { return []; }
 }
 
+// Native capture/render endpoints, or null if unavailable (browser dev mode,
+// no sound server) so callers can fall back to enumerateDevices.
+async function listNativeAudioDevices(): Promise<AudioDevice[] | null> {
+  if (!IS_TAURI) return null;
+  try {
+    const { invoke } = await import("@tauri-apps/api/core");
+    return await invoke<AudioDevice[]>("list_audio_devices");
+  } catch (e) {
+    console.warn("Native audio device list unavailable:", e);
+    return null;
+  }
+}
+
+function toOptions(devices: AudioDevice[], kind: AudioDevice["kind"]): DeviceOption[] {
+  return devices
+    .filter(d => d.kind === kind)
+    .map(d => ({ value: d.id, label: d.name, isDefault: d.isDefault }));
+}
+
 // ── Permissions helpers ───────────────────────────────────────────────────────
 
 type PermStatus = "granted" | "denied" | "prompt" | "unknown";
@@ -163,6 +371,31 @@ function PermBadge({ status }: { status: PermStatus }) {
   return <span className={`sp-badge ${cls}`}>{label}</span>;
 }
 
+interface BindingNameProps {
+  name: string;
+  taken: string[];       // names of the other bindings
+  onRename: (name: string) => void;
+}
+
+// Edited in place and committed on blur/Enter; an empty or duplicate name is
+// shown as an error and reverted.
+function BindingName({ name, taken, onRename }: BindingNameProps) {
+  const [draft, setDraft] = useState(name);
+  useEffect(() => setDraft(name), [name]);
+  const trimmed = draft.trim();
+  const error = !trimmed ? "Give the binding a name" : taken.includes(trimmed) ? "Another binding has this name" : null;
+  return (
+    <div className="settings-row-info">
+      <input type="text" className="settings-hex-input ptt-binding-name" value={draft}
+        maxLength={32} spellCheck={false}
+        onChange={e => setDraft(e.target.value)}
+        onBlur={() => { if (error) setDraft(name); else if (trimmed !== name) onRename(trimmed); }}
+        onKeyDown={e => { if (e.key === "Enter") e.currentTarget.blur(); }} />
+      {error && <span className="settings-row-desc sp-shortcut-error">{error}</span>}
+    </div>
+  );
+}
+
 interface ColorRowProps {
   label: string; description: string; value: string; defaultValue: string;
   onChange: (val: string) => void; onReset: () => void;
@@ -194,12 +427,15 @@ function ColorRow({ label, description, value, defaultValue, onChange, onReset }
 
 interface DeviceSelectProps {
   label: string; description: string;
-  devices: MediaDeviceInfo[]; selectedId: string | undefined;
-  permStatus: PermStatus; onChange: (id: string) => void;
+  devices: DeviceOption[]; selectedId: string | undefined;
+  permStatus?: PermStatus; // omit when the list doesn't need a permission grant
+  onChange: (id: string) => void;
 }
 
 function DeviceSelect({ label, description, devices, selectedId, permStatus, onChange }: DeviceSelectProps) {
-  const noPermission = permStatus !== "granted";
+  const noPermission = permStatus !== undefined && permStatus !== "granted";
+  // Selections saved before native ids existed are labels — match those too.
+  const selected = devices.find(d => d.value === selectedId || d.label === selectedId);
   return (
     <div className="settings-row">
       <div className="settings-row-info">
@@ -212,12 +448,12 @@ function DeviceSelect({ label, description, devices, selectedId, permStatus, onC
         ) : devices.length === 0 ? (
           <span className="sp-badge sp-badge--prompt">No devices found</span>
         ) : (
-          <select className="sp-device-select" value={selectedId ?? ""}
+          <select className="sp-device-select" value={selected?.value ?? ""}
             onChange={e => onChange(e.target.value)}>
             <option value="">System default</option>
             {devices.map(d => (
-              <option key={d.deviceId} value={d.label}>
-                {d.label || `Device ${d.deviceId.slice(0, 8)}`}
+              <option key={d.value} value={d.value}>
+                {d.isDefault ? `${d.label} (default)` : d.label}
               </option>
             ))}
           </select>
@@ -230,58 +466,103 @@ function DeviceSelect({ label, description, devices, selectedId, permStatus, onC
 // ── Main component ────────────────────────────────────────────────────────────
 
 interface SettingsPageProps {
+  servers: Server[];
   onDevicePrefsChange?: (prefs: DevicePrefs) => void;
-  onPttEnabledChange?: (enabled: boolean, keys?: string[]) => void;
+  onPttEnabledChange?: (enabled: boolean, chords?: string[][]) => void;
 }
 
-export default function SettingsPage({ onDevicePrefsChange, onPttEnabledChange }: SettingsPageProps) {
+export default function SettingsPage({ servers, onDevicePrefsChange, onPttEnabledChange }: SettingsPageProps) {
   const [settings,      setSettings]      = useState<AppearanceSettings>(DEFAULTS);
   const [loaded,        setLoaded]        = useState(false);
   const [micPerm,       setMicPerm]       = useState<PermStatus>("unknown");
   const [camPerm,       setCamPerm]       = useState<PermStatus>("unknown");
   const [requestingPerm, setRequestingPerm] = useState<"microphone" | "camera" | null>(null);
-  const [micDevices,     setMicDevices]     = useState<MediaDeviceInfo[]>([]);
-  const [camDevices,     setCamDevices]     = useState<MediaDeviceInfo[]>([]);
-  const [speakerDevices, setSpeakerDevices] = useState<MediaDeviceInfo[]>([]);
+  const [micDevices,     setMicDevices]     = useState<DeviceOption[]>([]);
+  const [camDevices,     setCamDevices]     = useState<DeviceOption[]>([]);
+  const [speakerDevices, setSpeakerDevices] = useState<DeviceOption[]>([]);
+  const [nativeAudio,    setNativeAudio]    = useState(false);
   const [devicePrefs,    setDevicePrefs]    = useState<DevicePrefs>({});
-  const [pttConfig,      setPttConfig]      = useState<PttConfig>(PTT_DEFAULT);
-  const [showPttDialog,  setShowPttDialog]  = useState(false);
+  const [pttConfigs,     setPttConfigs]     = useState<PttConfigs>({ [PTT_ALL_SERVERS]: PTT_DEFAULT });
+  const [pttScope,       setPttScope]       = useState<string>(PTT_ALL_SERVERS);
+  // Index of the binding whose keys are being captured; one past the end adds one.
+  const [editingBinding, setEditingBinding] = useState<number | null>(null);
+  const [storageUsage,   setStorageUsage]   = useState<ServerStorage[] | null>(null);
+  const [notifications,  setNotifications]  = useState<NotificationSettings>(NOTIFICATIONS_DEFAULT);
+  const [tray,           setTray]           = useState<TraySettings>(TRAY_DEFAULT);
+  const [shortcuts,      setShortcutList]   = useState<ShortcutStatus[]>([]);
+  const [capturing,      setCapturing]      = useState<ShortcutAction | null>(null);
+  const [shortcutError,  setShortcutError]  = useState<string | null>(null);
+
+  const refreshStorage = useCallback(async () => {
+    if (!IS_TAURI) return;
+    try {
+      setStorageUsage(await getStorageUsage());
+    } catch (e) {
+      console.error("Failed to read storage usage:", e);
+    }
+  }, []);
 
   const refreshDevices = useCallback(async () => {
-    const [mics, cams, speakers] = await Promise.all([
-      enumerateByKind("audioinput"),
+    const [native, cams] = await Promise.all([
+      listNativeAudioDevices(),
       enumerateByKind("videoinput"),
-      enumerateByKind("audiooutput"),
     ]);
-    setMicDevices(mics);
+    if (native) {
+      setMicDevices(toOptions(native, "input"));
+      setSpeakerDevices(toOptions(native, "output"));
+    } else {
+      setMicDevices(await enumerateByKind("audioinput"));
+      setSpeakerDevices(await enumerateByKind("audiooutput"));
+    }
+    setNativeAudio(native !== null);
     setCamDevices(cams);
-    setSpeakerDevices(speakers);
   }, []);
 
   useEffect(() => {
     (async () => {
-      const [appearance, prefs, mic, cam, ptt] = await Promise.all([
+      const [appearance, prefs, mic, cam, ptt, notif, traySettings, shortcutList] = await Promise.all([
         loadAppearance(),
         loadDevicePrefs(),
         queryPermStatus("microphone"),
         queryPermStatus("camera"),
-        loadPttConfig(),
+        loadPttConfigs(),
+        loadNotificationSettings(),
+        loadTraySettings(),
+        loadShortcuts(),
       ]);
       setSettings(appearance);
       applySettings(appearance);
       setDevicePrefs(prefs);
       setMicPerm(mic);
       setCamPerm(cam);
-      setPttConfig(ptt);
+      setPttConfigs(ptt);
+      setNotifications(notif);
+      setTray(traySettings);
+      setShortcutList(shortcutList);
       // Sync PTT indicator state — App.tsx already called set_ptt_config on startup,
       // so we just need to update the indicator and key refs here.
-      if (ptt.enabled && ptt.tauriKeys.length > 0) {
-        onPttEnabledChange?.(true, ptt.keys);
+      const allPtt = ptt[PTT_ALL_SERVERS];
+      if (allPtt.enabled && pttUsable(allPtt)) {
+        onPttEnabledChange?.(true, pttBindings(allPtt).map(b => b.keys));
       }
       setLoaded(true);
-      if (mic === "granted" || cam === "granted") await refreshDevices();
+      // The native list needs no permission grant; the browser list is
+      // gated per dropdown until one is given.
+      refreshStorage();
+      await refreshDevices();
     })();
-  }, [refreshDevices]);
+  }, [refreshDevices, refreshStorage]);
+
+  // A removed server's data disappears in the background — refresh the
+  // storage list once it has actually gone.
+  useEffect(() => {
+    if (!IS_TAURI) return;
+    let unlisten: (() => void) | null = null;
+    onServerDataDeletion(e => { if (e.status === "deleted") refreshStorage(); })
+      .then(fn => { unlisten = fn; })
+      .catch(console.error);
+    return () => { unlisten?.(); };
+  }, [refreshStorage]);
 
   const update = useCallback((key: keyof AppearanceSettings, value: string) => {
     setSettings(prev => {
@@ -313,8 +594,95 @@ export default function SettingsPage({ onDevicePrefsChange, onPttEnabledChange }
     });
   }, [onDevicePrefsChange]);
 
+  const updateDnd = useCallback((choice: string) => {
+    const next = dndSettings(choice);
+    setNotifications(next);
+    if (IS_TAURI) setNotificationSettings(next).catch(e => console.error("Failed to save notification settings:", e));
+  }, []);
+
+  // PTT switched from the tray while this page is open; Rust has already
+  // applied and saved it.
+  useEffect(() => {
+    if (!IS_TAURI) return;
+    let unlisten: (() => void) | null = null;
+    (async () => {
+      const { listen } = await import("@tauri-apps/api/event");
+      unlisten = await listen<PttEnabledEvent>("ptt://enabled", ({ payload }) => {
+        setPttConfigs(prev => prev[payload.scope]
+          ? { ...prev, [payload.scope]: { ...prev[payload.scope], enabled: payload.enabled } }
+          : prev);
+      });
+    })();
+    return () => { unlisten?.(); };
+  }, []);
+
+  // The config being edited: all servers, or the server picked above it.
+  // A server without a saved config starts from a disabled copy of the
+  // all-servers one.
+  const pttConfig = pttConfigs[pttScope]
+    ?? { ...pttConfigs[PTT_ALL_SERVERS], enabled: false };
+
+  // The server being configured was removed.
+  useEffect(() => {
+    if (pttScope !== PTT_ALL_SERVERS && !servers.some(s => s.id === pttScope)) setPttScope(PTT_ALL_SERVERS);
+  }, [servers, pttScope]);
+
+  const updatePtt = useCallback((next: PttConfig) => {
+    setPttConfigs(prev => ({ ...prev, [pttScope]: next }));
+    savePttConfig(pttScope, next);
+    applyPttConfig(pttScope, next);
+    if (pttScope === PTT_ALL_SERVERS) onPttEnabledChange?.(next.enabled, pttBindings(next).map(b => b.keys));
+  }, [pttScope, onPttEnabledChange]);
+
+  const bindings = pttBindings(pttConfig);
+  const updateBindings = (next: PttBinding[]) =>
+    updatePtt({ ...pttConfig, bindings: next, keys: undefined, tauriKeys: undefined });
+
+  const toggleCloseToTray = useCallback(() => {
+    setTray(prev => {
+      const next = { ...prev, closeToTray: !prev.closeToTray };
+      if (IS_TAURI) setTraySettings(next).catch(e => console.error("Failed to save tray settings:", e));
+      return next;
+    });
+  }, []);
+
+  // Bind `action` to `accelerator`, or unbind it when null. Rust checks the
+  // whole set and refuses it on a clash, leaving the old bindings in place.
+  const bindShortcut = useCallback((action: ShortcutAction, accelerator: string | null) => {
+    const next = shortcuts
+      .filter(s => !sameAction(s.action, action))
+      .map(s => ({ action: s.action, accelerator: s.accelerator }));
+    if (accelerator) next.push({ action, accelerator });
+    setShortcuts(next)
+      .then(list => { setShortcutList(list); setShortcutError(null); })
+      .catch(e => setShortcutError(String(e)));
+  }, [shortcuts]);
+
+  // While capturing, the next key combination (with at least one non-modifier
+  // key) becomes the binding; Escape on its own cancels.
+  useEffect(() => {
+    if (!capturing) return;
+    const onKeyDown = (e: KeyboardEvent) => {
+      e.preventDefault();
+      e.stopPropagation();
+      if (e.code === "Escape" && !e.ctrlKey && !e.altKey && !e.shiftKey && !e.metaKey) {
+        setCapturing(null);
+        return;
+      }
+      const accelerator = acceleratorFromEvent(e);
+      if (!accelerator) return;
+      setCapturing(null);
+      bindShortcut(capturing, accelerator);
+    };
+    window.addEventListener("keydown", onKeyDown, true);
+    return () => window.removeEventListener("keydown", onKeyDown, true);
+  }, [capturing, bindShortcut]);
+
   if (!loaded) return null;
 
+  const dndUntil = notifications.doNotDisturbUntil;
+  const dndValue = !isQuiet(notifications) ? "off" : dndUntil == null ? "forever" : "until";
+
   return (
     <>
     <div className="settings-page">
@@ -332,19 +700,35 @@ export default function SettingsPage({ onDevicePrefsChange, onPttEnabledChange }
           </div>
           <div className="settings-row">
             <div className="settings-row-info">
-              <span className="settings-row-label">Push to Talk</span>
-              <span className="settings-row-desc">Hold your key to unmute — mic is muted otherwise</span>
+              <span className="settings-row-label">Configure for</span>
+              <span className="settings-row-desc">A server with its own settings switched on ignores the all-servers ones</span>
+            </div>
+            <div className="settings-row-controls">
+              <select className="sp-device-select" value={pttScope} onChange={e => setPttScope(e.target.value)}>
+                <option value={PTT_ALL_SERVERS}>All servers</option>
+                {servers.map(s => (
+                  <option key={s.id} value={s.id}>
+                    {pttConfigs[s.id]?.enabled ? `${s.name} (own settings)` : s.name}
+                  </option>
+                ))}
+              </select>
+            </div>
+          </div>
+          <div className="settings-row">
+            <div className="settings-row-info">
+              <span className="settings-row-label">
+                {pttScope === PTT_ALL_SERVERS ? "Push to Talk" : "Own settings"}
+              </span>
+              <span className="settings-row-desc">
+                {pttScope === PTT_ALL_SERVERS
+                  ? "Hold your key to unmute — mic is muted otherwise"
+                  : "Use the settings below for this server instead of the all-servers ones"}
+              </span>
             </div>
             <div className="settings-row-controls">
               <button
                 className={`ptt-toggle ${pttConfig.enabled ? "ptt-toggle--on" : ""}`}
-                onClick={() => {
-                  const next = { ...pttConfig, enabled: !pttConfig.enabled };
-                  setPttConfig(next);
-                  savePttConfig(next);
-                  applyPttConfig(next);
-                  onPttEnabledChange?.(next.enabled, next.keys);
-                }}
+                onClick={() => updatePtt({ ...pttConfig, enabled: !pttConfig.enabled })}
               >
                 <span className="ptt-toggle-knob" />
               </button>
@@ -355,29 +739,213 @@ export default function SettingsPage({ onDevicePrefsChange, onPttEnabledChange }
           </div>
           <div className="settings-row">
             <div className="settings-row-info">
-              <span className="settings-row-label">Keybind</span>
-              <span className="settings-row-desc">Key or combo to hold while speaking</span>
+              <span className="settings-row-label">Mute scope</span>
+              <span className="settings-row-desc">Mute the whole system mic, or only Sharkord's audio</span>
             </div>
             <div className="settings-row-controls">
-              {pttConfig.keys.length === 0 ? (
-                <span className="sp-badge sp-badge--prompt">Not set</span>
-              ) : (
-                <div className="ptt-keychips">
-                  {pttConfig.keys.map((k, i) => (
-                    <span key={k} className="ptt-chip">
-                      {displayKey(k)}
-                      {i < pttConfig.keys.length - 1 && <span className="ptt-chip-plus">+</span>}
-                    </span>
-                  ))}
-                </div>
-              )}
-              <button className="sp-request-btn" onClick={() => setShowPttDialog(true)}>
-                Edit
+              <select className="sp-device-select" value={pttConfig.target ?? "tracks"}
+                onChange={e => updatePtt({ ...pttConfig, target: e.target.value as PttTarget })}>
+                <option value="tracks">Sharkord only</option>
+                <option value="system">Whole system</option>
+              </select>
+            </div>
+          </div>
+          <div className="settings-row">
+            <div className="settings-row-info">
+              <span className="settings-row-label">Mode</span>
+              <span className="settings-row-desc">Hold, toggle, hold to mute, or open on voice (Sharkord-only scope)</span>
+            </div>
+            <div className="settings-row-controls">
+              <select className="sp-device-select" value={pttConfig.mode ?? "pushToTalk"}
+                onChange={e => {
+                  const mode = e.target.value as PttMode;
+                  updatePtt({ ...pttConfig, mode, ...(mode === "voiceActivity" ? { target: "tracks" as PttTarget } : {}) });
+                }}>
+                <option value="pushToTalk">Push to talk</option>
+                <option value="toggle">Toggle to talk</option>
+                <option value="pushToMute">Push to mute</option>
+                <option value="voiceActivity">Voice activity</option>
+              </select>
+            </div>
+          </div>
+          <div className="settings-row">
+            <div className="settings-row-info">
+              <span className="settings-row-label">Release delay</span>
+              <span className="settings-row-desc">Keep the mic open briefly after release so your last word isn't cut off</span>
+            </div>
+            <div className="settings-row-controls">
+              <select className="sp-device-select" value={pttConfig.releaseDelayMs ?? 0}
+                onChange={e => updatePtt({ ...pttConfig, releaseDelayMs: Number(e.target.value) })}>
+                {[0, 100, 200, 300, 500].map(ms => (
+                  <option key={ms} value={ms}>{ms === 0 ? "Off" : `${ms} ms`}</option>
+                ))}
+              </select>
+            </div>
+          </div>
+          <div className="settings-row">
+            <div className="settings-row-info">
+              <span className="settings-row-label">Press debounce</span>
+              <span className="settings-row-desc">Ignore presses shorter than this, such as accidental taps</span>
+            </div>
+            <div className="settings-row-controls">
+              <select className="sp-device-select" value={pttConfig.debounceMs ?? 0}
+                onChange={e => updatePtt({ ...pttConfig, debounceMs: Number(e.target.value) })}>
+                {[0, 20, 50, 100, 200].map(ms => (
+                  <option key={ms} value={ms}>{ms === 0 ? "Off" : `${ms} ms`}</option>
+                ))}
+              </select>
+            </div>
+          </div>
+          <div className="settings-row">
+            <div className="settings-row-info">
+              <span className="settings-row-label">Sound cues</span>
+              <span className="settings-row-desc">Play a short tone when your mic opens and closes</span>
+            </div>
+            <div className="settings-row-controls">
+              <button
+                className={`ptt-toggle ${pttConfig.cues ? "ptt-toggle--on" : ""}`}
+                onClick={() => updatePtt({ ...pttConfig, cues: !pttConfig.cues })}
+              >
+                <span className="ptt-toggle-knob" />
+              </button>
+            </div>
+          </div>
+          <div className="settings-row">
+            <div className="settings-row-info">
+              <span className="settings-row-label">Keybinds</span>
+              <span className="settings-row-desc">Holding any one of these counts, e.g. a second "priority" key</span>
+            </div>
+            <div className="settings-row-controls">
+              {bindings.length === 0 && <span className="sp-badge sp-badge--prompt">Not set</span>}
+              <button className="sp-request-btn" onClick={() => setEditingBinding(bindings.length)}>
+                Add
+              </button>
+            </div>
+          </div>
+          {bindings.map((b, i) => (
+            <div className="settings-row" key={i}>
+              <BindingName name={b.name}
+                taken={bindings.filter((_, j) => j !== i).map(o => o.name)}
+                onRename={name => updateBindings(bindings.map((o, j) => j === i ? { ...o, name } : o))} />
+              <div className="settings-row-controls">
+                {b.keys.length === 0 ? (
+                  <span className="sp-badge sp-badge--prompt">Not set</span>
+                ) : (
+                  <div className="ptt-keychips">
+                    {b.keys.map((k, ki) => (
+                      <span key={k} className="ptt-chip">
+                        {displayKey(k)}
+                        {ki < b.keys.length - 1 && <span className="ptt-chip-plus">+</span>}
+                      </span>
+                    ))}
+                  </div>
+                )}
+                <button className="sp-request-btn" onClick={() => setEditingBinding(i)}>
+                  Edit
+                </button>
+                <button className="sp-request-btn" onClick={() => updateBindings(bindings.filter((_, j) => j !== i))}>
+                  Remove
+                </button>
+              </div>
+            </div>
+          ))}
+        </div>
+
+        {/* Notifications */}
+        <div className="settings-section">
+          <div className="settings-section-header">
+            <span className="settings-section-label">NOTIFICATIONS</span>
+          </div>
+          <div className="settings-section-note">
+            Servers' notifications are shown by your system. Mute a single server from its right-click menu.
+          </div>
+          <div className="settings-row">
+            <div className="settings-row-info">
+              <span className="settings-row-label">Do not disturb</span>
+              <span className="settings-row-desc">Silence notifications from every server</span>
+            </div>
+            <div className="settings-row-controls">
+              <select className="sp-device-select" value={dndValue} onChange={e => updateDnd(e.target.value)}>
+                {dndValue === "until" && dndUntil != null && (
+                  <option value="until" disabled>
+                    Until {new Date(dndUntil).toLocaleTimeString([], { hour: "2-digit", minute: "2-digit" })}
+                  </option>
+                )}
+                {DND_CHOICES.map(c => <option key={c.value} value={c.value}>{c.label}</option>)}
+              </select>
+            </div>
+          </div>
+        </div>
+
+        {/* Window */}
+        <div className="settings-section">
+          <div className="settings-section-header">
+            <span className="settings-section-label">WINDOW</span>
+          </div>
+          <div className="settings-row">
+            <div className="settings-row-info">
+              <span className="settings-row-label">Close to tray</span>
+              <span className="settings-row-desc">Closing the window keeps servers and calls running — quit from the tray icon</span>
+            </div>
+            <div className="settings-row-controls">
+              <button
+                className={`ptt-toggle ${tray.closeToTray ? "ptt-toggle--on" : ""}`}
+                onClick={toggleCloseToTray}
+              >
+                <span className="ptt-toggle-knob" />
               </button>
+              <span className={`sp-badge ${tray.closeToTray ? "sp-badge--granted" : "sp-badge--prompt"}`}>
+                {tray.closeToTray ? "On" : "Off"}
+              </span>
             </div>
           </div>
         </div>
 
+        {/* Shortcuts */}
+        <div className="settings-section">
+          <div className="settings-section-header">
+            <span className="settings-section-label">SHORTCUTS</span>
+          </div>
+          <div className="settings-section-note">
+            Work from any application. Use Ctrl, Alt or Super with a key, or a function key on its own.
+          </div>
+          <div className="settings-section-note">
+            Mute and deafen are Sweetshark's own: they cut the audio of every server page without going through
+            Sharkord, so Sharkord's mute and deafen buttons don't change and other members won't see you as muted
+            or deafened.
+          </div>
+          {shortcutError && <div className="sp-shortcut-error">{shortcutError}</div>}
+          {SHORTCUT_ACTIONS.map(({ action, label }) => {
+            const status = shortcuts.find(s => sameAction(s.action, action));
+            const isCapturing = capturing !== null && sameAction(capturing, action);
+            return (
+              <div className="settings-row" key={shortcutKey(action)}>
+                <div className="settings-row-info">
+                  <span className="settings-row-label">{label}</span>
+                  {status?.error && <span className="settings-row-desc sp-shortcut-error">{status.error}</span>}
+                </div>
+                <div className="settings-row-controls">
+                  {isCapturing ? (
+                    <span className="sp-badge sp-badge--prompt">Press keys…</span>
+                  ) : status ? (
+                    <span className="ptt-chip">{formatAccelerator(status.accelerator)}</span>
+                  ) : (
+                    <span className="sp-badge sp-badge--prompt">Not set</span>
+                  )}
+                  <button className="sp-request-btn" onClick={() => setCapturing(isCapturing ? null : action)}>
+                    {isCapturing ? "Cancel" : "Edit"}
+                  </button>
+                  {status && !isCapturing && (
+                    <button className="sp-request-btn" onClick={() => bindShortcut(action, null)}>
+                      Clear
+                    </button>
+                  )}
+                </div>
+              </div>
+            );
+          })}
+        </div>
+
         {/* Devices */}
         <div className="settings-section">
           <div className="settings-section-header">
@@ -387,13 +955,13 @@ export default function SettingsPage({ onDevicePrefsChange, onPttEnabledChange }
             Restrict which devices Sharkord servers can use. Only the selected device will be visible to servers — all others are hidden. Applies to newly opened servers.
           </div>
           <DeviceSelect label="Microphone" description="Audio input for voice calls"
-            devices={micDevices} selectedId={devicePrefs.micId} permStatus={micPerm}
+            devices={micDevices} selectedId={devicePrefs.micId} permStatus={nativeAudio ? undefined : micPerm}
             onChange={id => updateDevicePref("micId", id)} />
           <DeviceSelect label="Camera" description="Video input for video calls"
             devices={camDevices} selectedId={devicePrefs.camId} permStatus={camPerm}
             onChange={id => updateDevicePref("camId", id)} />
           <DeviceSelect label="Speaker / Headphones" description="Audio output device for incoming audio"
-            devices={speakerDevices} selectedId={devicePrefs.speakerId} permStatus={micPerm}
+            devices={speakerDevices} selectedId={devicePrefs.speakerId} permStatus={nativeAudio ? undefined : micPerm}
             onChange={id => updateDevicePref("speakerId", id)} />
         </div>
 
@@ -434,6 +1002,36 @@ export default function SettingsPage({ onDevicePrefsChange, onPttEnabledChange }
           </div>
         </div>
 
+        {/* Storage */}
+        {IS_TAURI && (
+          <div className="settings-section">
+            <div className="settings-section-header">
+              <span className="settings-section-label">STORAGE</span>
+              <button className="settings-reset-all-btn" onClick={refreshStorage}><ResetIcon />Refresh</button>
+            </div>
+            <div className="settings-section-note">
+              Disk space used by each server's cache, IndexedDB and local storage.
+            </div>
+            {storageUsage === null ? (
+              <div className="settings-section-note">Measuring…</div>
+            ) : storageUsage.length === 0 ? (
+              <div className="settings-section-note">No server data on disk.</div>
+            ) : storageUsage.map(s => (
+              <div className="settings-row" key={s.serverId}>
+                <div className="settings-row-info">
+                  <span className="settings-row-label">{s.name ?? "Orphaned data"}</span>
+                  <span className="settings-row-desc">
+                    Cache {formatBytes(s.usage.cache)} · IndexedDB {formatBytes(s.usage.indexedDb)} · Local storage {formatBytes(s.usage.localStorage)} · Other {formatBytes(s.usage.other)}
+                  </span>
+                </div>
+                <div className="settings-row-controls">
+                  <span className="sp-storage-total">{formatBytes(s.usage.total)}</span>
+                </div>
+              </div>
+            ))}
+          </div>
+        )}
+
         {/* Appearance */}
         <div className="settings-section">
           <div className="settings-section-header">
@@ -475,20 +1073,16 @@ export default function SettingsPage({ onDevicePrefsChange, onPttEnabledChange }
       </div>
     </div>
 
-    {showPttDialog && (
+    {editingBinding !== null && (
       <PttKeybindDialog
-        currentKeys={pttConfig.keys}
+        currentKeys={bindings[editingBinding]?.keys ?? []}
         onDone={(keys, tauriKeys) => {
-          const next = { ...pttConfig, keys, tauriKeys };
-          setPttConfig(next);
-          savePttConfig(next);
-          if (next.enabled) {
-            applyPttConfig(next);
-            onPttEnabledChange?.(true, keys);
-          }
-          setShowPttDialog(false);
+          updateBindings(editingBinding < bindings.length
+            ? bindings.map((b, i) => i === editingBinding ? { ...b, keys, tauriKeys } : b)
+            : [...bindings, { name: nextBindingName(bindings), keys, tauriKeys }]);
+          setEditingBinding(null);
         }}
-        onCancel={() => setShowPttDialog(false)}
+        onCancel={() => setEditingBinding(null)}
       />
     )}
     </>
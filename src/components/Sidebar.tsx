@@ -2,21 +2,34 @@ import { useState, useCallback } from "react";
 import { ActiveView, Server } from "../App";
 import "./Sidebar.css";
 import ContextMenu from "./ContextMenu";
+import type { ClearScope } from "../lib/webviewManager";
+import { serverIconSrc } from "../lib/serverRegistry";
+import type { ServerBadge } from "../lib/badges";
+import type { VoiceState } from "../lib/voice";
 
 export type PttIndicatorState = "off" | "muted" | "active";
 
 interface Props {
   servers: Server[];
   activeView: ActiveView;
+  badges: Record<string, ServerBadge>; // serverId → unread state; absent = nothing unread
   onSelectView: (view: ActiveView) => void;
   onAddServer: () => void;
   onRenameServer: (id: string) => void;
   onRefreshServer: (id: string) => void;
+  onClearServerData: (id: string, scope: ClearScope) => void;
+  onForgetCertificate: (id: string) => void;
+  onEditProxy: (id: string) => void;
+  onEditSignInDomains: (id: string) => void;
   onRemoveServer: (id: string) => void;
   onChangeServerIcon: (id: string) => void;
   onToggleKeepLoaded: (id: string) => void;
+  onToggleNotificationsMuted: (id: string) => void;
   onContextMenuOpenChange: (open: boolean) => void;
   pttState: PttIndicatorState;
+  pttError?: string | null; // last PTT mute failure, if any
+  voice: VoiceState;
+  onClearVoice: () => void; // unmute and undeafen
 }
 
 const HomeIcon = () => (
@@ -54,6 +67,63 @@ const RefreshIcon = () => (
   </svg>
 );
 
+const BroomIcon = () => (
+  <svg width="14" height="14" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2" strokeLinecap="round" strokeLinejoin="round">
+    <path d="M19 3l-7.5 7.5"/>
+    <path d="M11.5 10.5l2 2c.8.8.8 2 0 2.8L10 19H4v-6l3.7-3.5c.8-.8 2-.8 2.8 0z"/>
+    <path d="M7 19v-3M10 19v-2"/>
+  </svg>
+);
+
+const SignOutIcon = () => (
+  <svg width="14" height="14" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2" strokeLinecap="round" strokeLinejoin="round">
+    <path d="M9 21H5a2 2 0 01-2-2V5a2 2 0 012-2h4"/>
+    <polyline points="16 17 21 12 16 7"/>
+    <line x1="21" y1="12" x2="9" y2="12"/>
+  </svg>
+);
+
+const DatabaseIcon = () => (
+  <svg width="14" height="14" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2" strokeLinecap="round" strokeLinejoin="round">
+    <ellipse cx="12" cy="5" rx="9" ry="3"/>
+    <path d="M21 12c0 1.66-4 3-9 3s-9-1.34-9-3"/>
+    <path d="M3 5v14c0 1.66 4 3 9 3s9-1.34 9-3V5"/>
+  </svg>
+);
+
+const ProxyIcon = () => (
+  <svg width="14" height="14" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2" strokeLinecap="round" strokeLinejoin="round">
+    <circle cx="5" cy="12" r="2"/>
+    <circle cx="19" cy="12" r="2"/>
+    <rect x="9" y="8" width="6" height="8" rx="1.5"/>
+    <path d="M7 12h2M15 12h2"/>
+  </svg>
+);
+
+const KeyIcon = () => (
+  <svg width="14" height="14" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2" strokeLinecap="round" strokeLinejoin="round">
+    <circle cx="7.5" cy="15.5" r="4.5"/>
+    <path d="M10.7 12.3L21 2M16 7l3 3M18.5 4.5l2 2"/>
+  </svg>
+);
+
+const LockIcon = () => (
+  <svg width="14" height="14" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2" strokeLinecap="round" strokeLinejoin="round">
+    <rect x="5" y="11" width="14" height="10" rx="2"/>
+    <path d="M8 11V7a4 4 0 018 0v4"/>
+  </svg>
+);
+
+const BellOffIcon = () => (
+  <svg width="14" height="14" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2" strokeLinecap="round" strokeLinejoin="round">
+    <path d="M13.73 21a2 2 0 01-3.46 0"/>
+    <path d="M18.63 13A17.89 17.89 0 0118 8"/>
+    <path d="M6.26 6.26A5.86 5.86 0 006 8c0 7-3 9-3 9h14"/>
+    <path d="M18 8a6 6 0 00-9.33-5"/>
+    <line x1="1" y1="1" x2="23" y2="23"/>
+  </svg>
+);
+
 const ImageIcon = () => (
   <svg width="14" height="14" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2" strokeLinecap="round" strokeLinejoin="round">
     <rect x="3" y="3" width="18" height="18" rx="2"/>
@@ -98,6 +168,14 @@ const MicOffIcon = () => (
   </svg>
 );
 
+const HeadphonesOffIcon = () => (
+  <svg width="16" height="16" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2" strokeLinecap="round" strokeLinejoin="round">
+    <path d="M3 18v-6a9 9 0 0114.5-7.1M20.6 8.5A9 9 0 0121 12v6"/>
+    <path d="M21 19a2 2 0 01-2 2h-1a2 2 0 01-2-2v-3a2 2 0 012-2h3zM3 19a2 2 0 002 2h1a2 2 0 002-2v-3a2 2 0 00-2-2H3z"/>
+    <line x1="1" y1="1" x2="23" y2="23"/>
+  </svg>
+);
+
 interface ContextState {
   serverId: string;
   x: number;
@@ -107,15 +185,24 @@ interface ContextState {
 export default function Sidebar({
   servers,
   activeView,
+  badges,
   onSelectView,
   onAddServer,
   onRenameServer,
   onRefreshServer,
+  onClearServerData,
+  onForgetCertificate,
+  onEditProxy,
+  onEditSignInDomains,
   onRemoveServer,
   onChangeServerIcon,
   onToggleKeepLoaded,
+  onToggleNotificationsMuted,
   onContextMenuOpenChange,
   pttState,
+  pttError,
+  voice,
+  onClearVoice,
 }: Props) {
   const [contextMenu, setContextMenu] = useState<ContextState | null>(null);
 
@@ -146,21 +233,26 @@ export default function Sidebar({
         </div>
 
         <div className="sidebar-servers">
-          {servers.map(server => (
-            <SidebarBtn
-              key={server.id}
-              active={activeView === server.id}
-              onClick={() => onSelectView(server.id)}
-              onContextMenu={e => handleContextMenu(e, server.id)}
-              tooltip={server.name}
-            >
-              {server.iconUrl ? (
-                <img src={server.iconUrl} alt={server.name} className="server-icon-img" />
-              ) : (
-                <span className="server-letter">{server.iconLetter}</span>
-              )}
-            </SidebarBtn>
-          ))}
+          {/* Registry order, unsorted: the "Switch to server N" shortcuts count in it. */}
+          {servers.map(server => {
+            const iconSrc = serverIconSrc(server);
+            return (
+              <SidebarBtn
+                key={server.id}
+                active={activeView === server.id}
+                onClick={() => onSelectView(server.id)}
+                onContextMenu={e => handleContextMenu(e, server.id)}
+                tooltip={server.name}
+                badge={badges[server.id]}
+              >
+                {iconSrc ? (
+                  <img src={iconSrc} alt={server.name} className="server-icon-img" />
+                ) : (
+                  <span className="server-letter">{server.iconLetter}</span>
+                )}
+              </SidebarBtn>
+            );
+          })}
 
           <SidebarBtn
             active={false}
@@ -174,8 +266,22 @@ export default function Sidebar({
 
         <div className="sidebar-bottom">
           <div className="sidebar-divider" />
+          {(voice.muted || voice.deafened) && (
+            <button
+              className="voice-indicator"
+              onClick={onClearVoice}
+              title={voice.deafened
+                ? "Deafened by Sweetshark (not shown in Sharkord) — click to undeafen"
+                : "Muted by Sweetshark (not shown in Sharkord) — click to unmute"}
+            >
+              {voice.deafened ? <HeadphonesOffIcon /> : <MicOffIcon />}
+            </button>
+          )}
           {pttState !== "off" && (
-            <div className={`ptt-indicator ptt-indicator--${pttState}`} title={pttState === "active" ? "Mic active" : "Mic muted (PTT)"}>
+            <div
+              className={`ptt-indicator ptt-indicator--${pttState} ${pttError ? "ptt-indicator--degraded" : ""}`}
+              title={pttError ? `PTT can't control the mic — it may be live (${pttError})` : pttState === "active" ? "Mic active" : "Mic muted (PTT)"}
+            >
               {pttState === "active" ? <MicOnIcon /> : <MicOffIcon />}
             </div>
           )}
@@ -210,6 +316,43 @@ export default function Sidebar({
               icon: <RefreshIcon />,
               onClick: () => onRefreshServer(contextMenu.serverId),
             },
+            {
+              label: "Clear Cache",
+              icon: <BroomIcon />,
+              onClick: () => onClearServerData(contextMenu.serverId, "cache"),
+            },
+            {
+              label: "Sign Out",
+              icon: <SignOutIcon />,
+              onClick: () => onClearServerData(contextMenu.serverId, "cookies"),
+            },
+            {
+              label: "Clear Site Storage",
+              icon: <DatabaseIcon />,
+              onClick: () => onClearServerData(contextMenu.serverId, "storage"),
+            },
+            {
+              label: "Proxy Settings",
+              icon: <ProxyIcon />,
+              onClick: () => onEditProxy(contextMenu.serverId),
+            },
+            {
+              label: "Sign-in Domains",
+              icon: <KeyIcon />,
+              onClick: () => onEditSignInDomains(contextMenu.serverId),
+            },
+            ...(servers.find(s => s.id === contextMenu.serverId)?.certSha256 ? [{
+              label: "Forget Trusted Certificate",
+              icon: <LockIcon />,
+              onClick: () => onForgetCertificate(contextMenu.serverId),
+            }] : []),
+            {
+              label: "Mute notifications",
+              icon: <BellOffIcon />,
+              checked: servers.find(s => s.id === contextMenu.serverId)?.notificationsMuted ?? false,
+              closeOnClick: false,
+              onClick: () => onToggleNotificationsMuted(contextMenu.serverId),
+            },
             {
               label: "Keep server loaded",
               icon: <LayersIcon />,
@@ -237,12 +380,17 @@ interface BtnProps {
   children: React.ReactNode;
   tooltip: string;
   variant?: "default" | "add";
+  badge?: ServerBadge;
 }
 
-function SidebarBtn({ active, onClick, onContextMenu, children, tooltip, variant = "default" }: BtnProps) {
+function SidebarBtn({ active, onClick, onContextMenu, children, tooltip, variant = "default", badge }: BtnProps) {
+  const mentions = badge?.mentions ?? 0;
   return (
     <div className={`sidebar-btn-wrap ${active ? "active" : ""}`}>
-      {active && <div className="active-indicator" />}
+      {(active || badge?.unread) && <div className="active-indicator" />}
+      {mentions > 0 && (
+        <span className="mention-badge">{mentions > 9 ? "9+" : mentions}</span>
+      )}
       <button
         className={`sidebar-btn ${active ? "active" : ""} ${variant === "add" ? "add-btn" : ""}`}
         onClick={onClick}
@@ -0,0 +1,93 @@
+import { useState, useEffect, useRef } from "react";
+import "./RenameServerDialog.css";
+import "./SignInDomainsDialog.css";
+import { Server } from "../App";
+import { serverIconSrc } from "../lib/serverRegistry";
+
+interface Props {
+  server: Server;
+  /** Rejects with Rust's message when a domain is invalid. */
+  onConfirm: (domains: string[]) => Promise<void>;
+  onCancel: () => void;
+}
+
+export default function SignInDomainsDialog({ server, onConfirm, onCancel }: Props) {
+  const [text, setText] = useState((server.inAppDomains ?? []).join("\n"));
+  const [error, setError] = useState("");
+  const [saving, setSaving] = useState(false);
+  const inputRef = useRef<HTMLTextAreaElement>(null);
+
+  useEffect(() => {
+    inputRef.current?.focus();
+    const onKey = (e: KeyboardEvent) => { if (e.key === "Escape") onCancel(); };
+    window.addEventListener("keydown", onKey);
+    return () => window.removeEventListener("keydown", onKey);
+  }, [onCancel]);
+
+  const handleSubmit = async () => {
+    setSaving(true);
+    try {
+      await onConfirm(text.split(/[\s,]+/).filter(Boolean));
+    } catch (e) {
+      setError(String(e));
+      setSaving(false);
+    }
+  };
+
+  const iconSrc = serverIconSrc(server);
+
+  return (
+    <div className="rsd-overlay" onClick={e => e.target === e.currentTarget && onCancel()}>
+      <div className="rsd-modal">
+        <div className="rsd-band" />
+
+        <div className="rsd-preview-wrap">
+          <div className="rsd-preview-icon" style={{ background: iconSrc ? "transparent" : server.iconColor }}>
+            {iconSrc
+              ? <img src={iconSrc} alt={server.name} style={{ width: "100%", height: "100%", objectFit: "cover", borderRadius: "inherit" }} />
+              : server.iconLetter
+            }
+          </div>
+        </div>
+
+        <div className="rsd-body">
+          <h2 className="rsd-title">Sign-in Domains</h2>
+          <p className="rsd-sub">
+            Links away from {server.name} open in your browser. If the server signs you in through
+            another site (SSO, OAuth), list that site's domain here so the sign-in stays in the app.
+          </p>
+
+          {error && (
+            <div className="rsd-error">
+              <svg width="14" height="14" viewBox="0 0 16 16" fill="currentColor">
+                <path d="M8 1a7 7 0 100 14A7 7 0 008 1zm-.75 3.75a.75.75 0 011.5 0v4a.75.75 0 01-1.5 0v-4zm.75 7a.875.875 0 110-1.75.875.875 0 010 1.75z"/>
+              </svg>
+              {error}
+            </div>
+          )}
+
+          <div className="rsd-field">
+            <label className="rsd-label">Domains, one per line</label>
+            <textarea
+              ref={inputRef}
+              className="rsd-input sid-domains"
+              placeholder={"login.microsoftonline.com\nexample.okta.com"}
+              value={text}
+              onChange={e => { setText(e.target.value); setError(""); }}
+              rows={5}
+              spellCheck={false}
+            />
+            <span className="sid-hint">Subdomains are included. Only https pages are kept in the app.</span>
+          </div>
+
+          <div className="rsd-actions">
+            <button className="rsd-btn-cancel" onClick={onCancel}>Cancel</button>
+            <button className="rsd-btn-confirm" onClick={handleSubmit} disabled={saving}>
+              {saving ? "Saving…" : "Save"}
+            </button>
+          </div>
+        </div>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,76 @@
+import { useEffect } from "react";
+import "./ConfirmRemoveDialog.css";
+import "./TrustCertificateDialog.css";
+import { Server } from "../App";
+import { serverIconSrc } from "../lib/serverRegistry";
+import type { PeerCertificate } from "../lib/webviewManager";
+
+interface Props {
+  server: Server;
+  certificate: PeerCertificate;
+  onTrust: () => void;
+  onCancel: () => void;
+}
+
+export default function TrustCertificateDialog({ server, certificate, onTrust, onCancel }: Props) {
+  useEffect(() => {
+    const onKey = (e: KeyboardEvent) => { if (e.key === "Escape") onCancel(); };
+    window.addEventListener("keydown", onKey);
+    return () => window.removeEventListener("keydown", onKey);
+  }, [onCancel]);
+
+  const changed = certificate.trust === "pinMismatch";
+  const iconSrc = serverIconSrc(server);
+
+  return (
+    <div className="crd-overlay" onClick={e => e.target === e.currentTarget && onCancel()}>
+      <div className="crd-modal tcd-modal">
+
+        <div className="crd-icon-wrap">
+          <div className="crd-server-icon" style={{ background: iconSrc ? "transparent" : server.iconColor }}>
+            {iconSrc
+              ? <img src={iconSrc} alt={server.name} className="crd-server-img" />
+              : server.iconLetter
+            }
+          </div>
+          <div className={`crd-warning-badge${changed ? "" : " tcd-badge-info"}`}>
+            <svg width="14" height="14" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2.4" strokeLinecap="round" strokeLinejoin="round">
+              <rect x="5" y="11" width="14" height="10" rx="2"/>
+              <path d="M8 11V7a4 4 0 018 0v4"/>
+            </svg>
+          </div>
+        </div>
+
+        <div className="crd-body">
+          <h2 className="crd-title">{changed ? "Certificate Changed" : "Untrusted Certificate"}</h2>
+          {changed ? (
+            <p className="crd-sub">
+              <strong>{server.name}</strong> presented a different certificate from the one you trusted before.
+              This happens when the server's certificate is renewed — but it is also what an attacker
+              intercepting the connection would look like. Only continue if you know it changed.
+            </p>
+          ) : (
+            <p className="crd-sub">
+              <strong>{server.name}</strong> uses a certificate that isn't signed by a trusted authority,
+              as self-hosted servers often do. Check that the fingerprint below matches the one on your
+              server before trusting it.
+            </p>
+          )}
+
+          <div className="tcd-fingerprint">
+            <span className="tcd-fingerprint-label">SHA-256</span>
+            <code className="tcd-fingerprint-value">{certificate.sha256}</code>
+          </div>
+          {certificate.error && <p className="tcd-reason">{certificate.error}</p>}
+
+          <div className="crd-actions">
+            <button className="crd-btn-cancel" onClick={onCancel}>Cancel</button>
+            <button className={changed ? "crd-btn-remove" : "tcd-btn-trust"} onClick={onTrust}>
+              {changed ? "Trust New Certificate" : "Trust Certificate"}
+            </button>
+          </div>
+        </div>
+      </div>
+    </div>
+  );
+}
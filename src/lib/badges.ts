@@ -0,0 +1,32 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+
+// Unread / mention badges. Rust keeps the model (fed by the server pages'
+// titles and notifications) and draws the tray and taskbar badges; the UI
+// shows the per-server ones and reports which server is on screen.
+
+/** Mirrors `ServerBadge` in lib.rs. */
+export interface ServerBadge {
+  unread: boolean;
+  unreadCount?: number | null; // when the page title gives a number
+  mentions: number;            // notifications since the server was last viewed
+}
+
+/** Mirrors `BadgesChangedEvent` in lib.rs. */
+export interface Badges {
+  servers: Record<string, ServerBadge>;
+  total: { mentions: number; unread: boolean; unreadCount: number };
+}
+
+export function getBadges(): Promise<Badges> {
+  return invoke<Badges>("get_badges");
+}
+
+/** Tell Rust which server the main window shows; null for home / settings. */
+export function setViewedServer(serverId: string | null): Promise<void> {
+  return invoke<void>("set_viewed_server", { serverId });
+}
+
+export function onBadgesChanged(cb: (badges: Badges) => void): Promise<UnlistenFn> {
+  return listen<Badges>("badges://changed", ({ payload }) => cb(payload));
+}
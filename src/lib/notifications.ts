@@ -0,0 +1,33 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+
+// Native notifications raised on behalf of server pages. Rust shows them;
+// the UI only edits the do-not-disturb policy and follows clicks.
+
+/** Mirrors `NotificationSettings` in lib.rs. */
+export interface NotificationSettings {
+  doNotDisturb: boolean;
+  doNotDisturbUntil?: number | null; // Unix ms; absent = until switched off
+}
+
+export interface NotificationClickedEvent {
+  serverId: string;
+}
+
+export function getNotificationSettings(): Promise<NotificationSettings> {
+  return invoke<NotificationSettings>("get_notification_settings");
+}
+
+export function setNotificationSettings(settings: NotificationSettings): Promise<void> {
+  return invoke<void>("set_notification_settings", { settings });
+}
+
+/** Whether do-not-disturb is in effect now (a timed one may have lapsed). */
+export function isQuiet(settings: NotificationSettings, now = Date.now()): boolean {
+  return settings.doNotDisturb && (settings.doNotDisturbUntil == null || now < settings.doNotDisturbUntil);
+}
+
+/** The user clicked a notification; the main window is already focused. */
+export function onNotificationClicked(cb: (e: NotificationClickedEvent) => void): Promise<UnlistenFn> {
+  return listen<NotificationClickedEvent>("notification://clicked", ({ payload }) => cb(payload));
+}
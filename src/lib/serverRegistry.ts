@@ -0,0 +1,102 @@
+import { invoke, convertFileSrc } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import type { Server } from "../App";
+
+// The server list is owned by the Rust ServerRegistry. These wrappers are the
+// only way the UI changes it; the resulting list always comes back through
+// the "servers://changed" event, so there is a single source of truth.
+
+export interface NewServer {
+  name: string;
+  url: string;
+  iconLetter: string;
+  iconColor: string;
+  iconUrl?: string;
+}
+
+export type ServerChange =
+  | { kind: "added"; id: string }
+  | { kind: "updated"; id: string }
+  | { kind: "removed"; id: string };
+
+export interface ServersChangedEvent {
+  change: ServerChange;
+  servers: Server[];
+}
+
+/** Mirrors `ServerProxy` in lib.rs. The password is never part of it. */
+export interface ServerProxy {
+  url: string;       // http://host:port or socks5://host:port
+  username?: string;
+}
+
+export interface ServerMetadata {
+  name: string | null;
+  iconUrl: string | null; // normalised PNG data URL, ready to pass to addServer
+}
+
+/**
+ * Name and icon advertised by the server's landing page / manifest. Pass
+ * `serverId` for a saved server so its pinned certificate is accepted; a
+ * server being added has no pin yet, so a self-signed one fails here.
+ */
+export function fetchServerMetadata(url: string, serverId?: string): Promise<ServerMetadata> {
+  return invoke<ServerMetadata>("fetch_server_metadata", { url, serverId });
+}
+
+/**
+ * What to put in an <img src> for a server's custom icon, or undefined for
+ * the letter. Cached icons are served by Rust's server-icon:// protocol;
+ * iconRev changes with the file, so it doubles as a cache buster.
+ */
+export function serverIconSrc(server: Server): string | undefined {
+  if (server.iconRev !== undefined) return `${convertFileSrc(server.id, "server-icon")}?v=${server.iconRev}`;
+  return server.iconUrl;
+}
+
+export function listServers(): Promise<Server[]> {
+  return invoke<Server[]>("list_servers");
+}
+
+/** Returns the stored server, including the id the registry assigned. */
+export function addServer(server: NewServer): Promise<Server> {
+  return invoke<Server>("add_server", { server });
+}
+
+export function updateServer(server: Server): Promise<void> {
+  return invoke<void>("update_server", { server });
+}
+
+export function removeServer(serverId: string): Promise<void> {
+  return invoke<void>("remove_server", { serverId });
+}
+
+/**
+ * Set a server's proxy (null removes it). Pass `password` to replace the
+ * stored one; leave it out to keep it.
+ */
+export function setServerProxy(serverId: string, proxy: ServerProxy | null, password?: string): Promise<void> {
+  return invoke<void>("set_server_proxy", { serverId, proxy, password });
+}
+
+/**
+ * Replace the SSO / OAuth domains that stay inside a server's webview.
+ * Resolves to the list as stored (normalised, deduplicated).
+ */
+export function setServerInAppDomains(serverId: string, domains: string[]): Promise<string[]> {
+  return invoke<string[]>("set_server_in_app_domains", { serverId, domains });
+}
+
+/** Drop (or stop dropping) the notifications a server raises. */
+export function setServerNotificationsMuted(serverId: string, muted: boolean): Promise<void> {
+  return invoke<void>("set_server_notifications_muted", { serverId, muted });
+}
+
+/** Pin a certificate fingerprint for a server; null forgets the pin. */
+export function trustServerCertificate(serverId: string, sha256: string | null): Promise<void> {
+  return invoke<void>("trust_server_certificate", { serverId, sha256 });
+}
+
+export function onServersChanged(cb: (e: ServersChangedEvent) => void): Promise<UnlistenFn> {
+  return listen<ServersChangedEvent>("servers://changed", ({ payload }) => cb(payload));
+}
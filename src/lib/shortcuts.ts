@@ -0,0 +1,65 @@
+import { invoke } from "@tauri-apps/api/core";
+
+// Global shortcuts. Rust owns the bindings and registers them with the OS;
+// the UI lists them and replaces the whole set through `setShortcuts`.
+
+/** Mirrors `ShortcutAction` in lib.rs. */
+export type ShortcutAction =
+  | { type: "toggleMute" }
+  | { type: "toggleDeafen" }
+  | { type: "toggleWindow" }
+  | { type: "togglePtt" }
+  | { type: "switchServer"; index: number }; // 1-based position in the sidebar
+
+export interface ShortcutBinding {
+  action: ShortcutAction;
+  accelerator: string; // e.g. "Control+Shift+KeyM"
+}
+
+export interface ShortcutStatus extends ShortcutBinding {
+  error?: string | null; // why it isn't registered; absent = active
+}
+
+export const MAX_SHORTCUT_SERVER = 9;
+
+export function getShortcuts(): Promise<ShortcutStatus[]> {
+  return invoke<ShortcutStatus[]>("get_shortcuts");
+}
+
+/** Rejects (and changes nothing) when two bindings clash or one is invalid. */
+export function setShortcuts(bindings: ShortcutBinding[]): Promise<ShortcutStatus[]> {
+  return invoke<ShortcutStatus[]>("set_shortcuts", { bindings });
+}
+
+export function sameAction(a: ShortcutAction, b: ShortcutAction): boolean {
+  return a.type === b.type && (a.type !== "switchServer" || a.index === (b as { index: number }).index);
+}
+
+const MODIFIER_CODES = new Set([
+  "ShiftLeft", "ShiftRight", "ControlLeft", "ControlRight",
+  "AltLeft", "AltRight", "MetaLeft", "MetaRight", "OSLeft", "OSRight",
+]);
+
+/** The accelerator for a key press, or null while only modifiers are down. */
+export function acceleratorFromEvent(e: KeyboardEvent): string | null {
+  if (MODIFIER_CODES.has(e.code) || !e.code) return null;
+  const parts: string[] = [];
+  if (e.ctrlKey) parts.push("Control");
+  if (e.altKey) parts.push("Alt");
+  if (e.shiftKey) parts.push("Shift");
+  if (e.metaKey) parts.push("Super");
+  parts.push(e.code);
+  return parts.join("+");
+}
+
+/** "Control+Shift+KeyM" → "Ctrl+Shift+M", for display. */
+export function formatAccelerator(accelerator: string): string {
+  return accelerator
+    .split("+")
+    .map(part => {
+      if (/^control$/i.test(part)) return "Ctrl";
+      if (/^super$/i.test(part)) return "Super";
+      return part.replace(/^Key(?=[A-Z]$)/, "").replace(/^Digit(?=\d$)/, "").replace(/^Numpad/, "Num ");
+    })
+    .join("+");
+}
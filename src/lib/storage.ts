@@ -0,0 +1,62 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+
+// Per-server disk usage and cleanup of data dirs that no registry entry owns.
+
+export interface StorageUsage {
+  cache: number;
+  indexedDb: number;
+  localStorage: number;
+  other: number;
+  total: number;
+}
+
+export interface ServerStorage {
+  serverId: string;
+  /** Registry name; null when the directory is orphaned. */
+  name: string | null;
+  usage: StorageUsage;
+}
+
+export type OrphanAction = "delete" | "quarantine";
+
+/** Usage for every server data dir on disk, largest first. */
+export function getStorageUsage(): Promise<ServerStorage[]> {
+  return invoke<ServerStorage[]>("get_storage_usage");
+}
+
+export function findOrphanedServerData(): Promise<ServerStorage[]> {
+  return invoke<ServerStorage[]>("find_orphaned_server_data");
+}
+
+export function resolveOrphanedServerData(serverIds: string[], action: OrphanAction): Promise<void> {
+  return invoke<void>("resolve_orphaned_server_data", { serverIds, action });
+}
+
+export type DeletionStatus =
+  | { status: "tombstoned" }
+  | { status: "retrying"; attempt: number; retryInMs: number; error: string }
+  | { status: "deleted" }
+  | { status: "failed"; error: string }
+  | { status: "cancelled" };
+
+export type DeletionEvent = { serverId: string } & DeletionStatus;
+
+export function cancelServerDataDeletion(serverId: string): Promise<void> {
+  return invoke<void>("cancel_server_data_deletion", { serverId });
+}
+
+export function onServerDataDeletion(cb: (e: DeletionEvent) => void): Promise<UnlistenFn> {
+  return listen<DeletionEvent>("server-data://deletion", ({ payload }) => cb(payload));
+}
+
+export function formatBytes(bytes: number): string {
+  const units = ["B", "KB", "MB", "GB", "TB"];
+  let value = bytes;
+  let unit = 0;
+  while (value >= 1024 && unit < units.length - 1) {
+    value /= 1024;
+    unit++;
+  }
+  return `${unit === 0 ? value : value.toFixed(1)} ${units[unit]}`;
+}
@@ -0,0 +1,28 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+
+// The tray icon and its menu live in Rust; the UI only edits the
+// close-to-tray setting and follows server picks from the menu (and from
+// global shortcuts).
+
+/** Mirrors `TraySettings` in lib.rs. */
+export interface TraySettings {
+  closeToTray: boolean; // closing the window hides it instead of quitting
+}
+
+export interface SelectServerEvent {
+  serverId: string;
+}
+
+export function getTraySettings(): Promise<TraySettings> {
+  return invoke<TraySettings>("get_tray_settings");
+}
+
+export function setTraySettings(settings: TraySettings): Promise<void> {
+  return invoke<void>("set_tray_settings", { settings });
+}
+
+/** A server was picked from the tray menu or by shortcut; the window is already shown. */
+export function onSelectServer(cb: (e: SelectServerEvent) => void): Promise<UnlistenFn> {
+  return listen<SelectServerEvent>("app://select-server", ({ payload }) => cb(payload));
+}
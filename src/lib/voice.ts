@@ -0,0 +1,24 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+
+// App-wide mute and deafen. Rust applies them to every server page and
+// keeps the tray menu in step; they reset on every launch.
+
+/** Mirrors `VoiceState` in lib.rs. */
+export interface VoiceState {
+  muted: boolean;
+  deafened: boolean; // implies a muted mic
+}
+
+export function getVoiceState(): Promise<VoiceState> {
+  return invoke<VoiceState>("get_voice_state");
+}
+
+export function setVoiceState(voice: VoiceState): Promise<void> {
+  return invoke<void>("set_voice_state", { voice });
+}
+
+/** Mute or deafen changed, from the UI, the tray or a shortcut. */
+export function onVoiceChanged(cb: (voice: VoiceState) => void): Promise<UnlistenFn> {
+  return listen<VoiceState>("voice://changed", ({ payload }) => cb(payload));
+}
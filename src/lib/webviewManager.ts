@@ -34,12 +34,21 @@ async function contentBounds() {
 }
 
 export interface DevicePrefs {
-  micId?: string;
-  camId?: string;
-  speakerId?: string;
+  micId?: string;     // native endpoint id (or a legacy label); resolved in Rust
+  camId?: string;     // device label
+  speakerId?: string; // native endpoint id (or a legacy label); resolved in Rust
 }
 
-export async function createServerWebview(serverId: string, url: string, devicePrefs?: DevicePrefs): Promise<Webview> {
+/**
+ * With `probeFirst`, Rust checks the server is reachable before touching the
+ * existing webview and rejects with a one-line diagnosis if it is not.
+ */
+export async function createServerWebview(
+  serverId: string,
+  url: string,
+  devicePrefs?: DevicePrefs,
+  probeFirst = false
+): Promise<Webview> {
   const lbl = webviewLabel(serverId);
 
   if (pool.has(serverId)) {
@@ -60,6 +69,7 @@ export async function createServerWebview(serverId: string, url: string, deviceP
     micId:      devicePrefs?.micId     ?? null,
     camId:      devicePrefs?.camId     ?? null,
     speakerId:  devicePrefs?.speakerId ?? null,
+    probeFirst,
   });
 
   // getByLabel is ASYNC — it queries the Tauri backend to find the webview.
@@ -104,13 +114,71 @@ export async function destroyServerWebview(serverId: string): Promise<void> {
   }
 }
 
+/**
+ * Starts moving the server's data dir aside and removing it in the background.
+ * Rust retries both steps with backoff while WebView2 releases its file locks,
+ * and progress (including a final failure) arrives as "server-data://deletion"
+ * events.
+ */
 export async function deleteServerData(serverId: string): Promise<void> {
-  // Small delay to give WebView2 time to release its file lock on the data
-  // directory after the webview has been closed.
-  await new Promise(resolve => setTimeout(resolve, 300));
   await invoke<void>("delete_server_data", { serverId });
 }
 
+export type ProbeStage = "dns" | "tcp" | "tls" | "http";
+
+/** How a server's TLS certificate was judged. Mirrors `CertTrust` in lib.rs. */
+export type CertTrust = "trusted" | "pinned" | "untrusted" | "pinMismatch";
+
+export interface PeerCertificate {
+  sha256: string;        // colon-separated uppercase hex
+  trust: CertTrust;
+  error: string | null;  // why normal verification rejected it
+}
+
+export interface ProbeReport {
+  url: string;
+  steps: { stage: ProbeStage; elapsedMs: number; error: string | null }[];
+  httpStatus: number | null;
+  sharkord: boolean;
+  certificate: PeerCertificate | null;
+}
+
+/**
+ * Stage-by-stage reachability check; never rejects for a down server. With
+ * `serverId`, that server's pinned certificate is accepted.
+ */
+export async function probeServer(url: string, serverId?: string): Promise<ProbeReport> {
+  return invoke<ProbeReport>("probe_server", { url: normalizeUrl(url), serverId });
+}
+
+/** The probe stopped at a certificate only the user can vouch for. */
+export function needsCertificateTrust(report: ProbeReport): boolean {
+  const trust = report.certificate?.trust;
+  return trust === "untrusted" || trust === "pinMismatch";
+}
+
+export type ClearScope = "cache" | "cookies" | "storage";
+
+/**
+ * Wipes one kind of data for a server without removing it. A live webview is
+ * closed first (its files are locked while open) and recreated afterwards,
+ * even if clearing failed, so the server is never left without one.
+ */
+export async function clearServerData(
+  serverId: string,
+  url: string,
+  scope: ClearScope,
+  devicePrefs?: DevicePrefs
+): Promise<void> {
+  const wasLive = pool.has(serverId);
+  await destroyServerWebview(serverId);
+  try {
+    await invoke<void>("clear_server_data", { serverId, scope });
+  } finally {
+    if (wasLive) await createServerWebview(serverId, url, devicePrefs);
+  }
+}
+
 export async function refreshServerWebview(serverId: string): Promise<void> {
   if (!pool.has(serverId)) return;
   const label = webviewLabel(serverId);